@@ -0,0 +1,67 @@
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+
+/// A C++ exception caught by a FFI wrapper function.
+///
+/// Wrapper functions return `Result<T, CppException>` if the crate was generated
+/// with exception catching enabled and the C++ function is not marked `noexcept`.
+/// Only the exception's message (the result of `std::exception::what()`) is preserved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CppException {
+    message: String,
+}
+
+impl CppException {
+    /// Creates a `CppException` with the specified `message`.
+    pub fn new(message: impl Into<String>) -> Self {
+        CppException {
+            message: message.into(),
+        }
+    }
+
+    /// Creates a `CppException` from a message passed by a FFI wrapper function
+    /// and frees the message buffer.
+    ///
+    /// ### Safety
+    ///
+    /// `message` must be a valid null-terminated string allocated with `malloc`.
+    /// It must not be used after calling this function.
+    pub unsafe fn from_raw(message: *mut c_char) -> Self {
+        let text = CStr::from_ptr(message).to_string_lossy().into_owned();
+        libc::free(message as *mut libc::c_void);
+        CppException::new(text)
+    }
+
+    /// Returns the message of the exception.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for CppException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "C++ exception: {}", self.message)
+    }
+}
+
+impl Error for CppException {}
+
+#[cfg(test)]
+mod tests {
+    use crate::CppException;
+    use std::os::raw::c_char;
+
+    #[test]
+    fn from_raw_copies_message() {
+        let text = b"out of range\0";
+        let exception = unsafe {
+            let buffer = libc::malloc(text.len()) as *mut c_char;
+            buffer.copy_from_nonoverlapping(text.as_ptr() as *const c_char, text.len());
+            CppException::from_raw(buffer)
+        };
+        assert_eq!(exception.message(), "out of range");
+        assert_eq!(exception.to_string(), "C++ exception: out of range");
+    }
+}
@@ -12,6 +12,9 @@
 //! - `Ptr`: possibly owned, possibly null (correspond to C++ pointers)
 //! - `Ref`: not owned, non-null (correspond to C++ references)
 //!
+//! Pointers and references to `volatile` values are represented by `VolatilePtr`
+//! that only allows explicit volatile reads and writes.
+//!
 //! Accessing objects through these pointers is inherently unsafe,
 //! as the compiler cannot make any guarantee about the validity of pointers to objects
 //! managed by C++ libraries.
@@ -56,21 +59,29 @@
 pub use crate::casts::{DynamicCast, StaticDowncast, StaticUpcast};
 pub use crate::convert::{CastFrom, CastInto};
 pub use crate::cpp_box::{CppBox, CppDeletable};
+pub use crate::exception::CppException;
 pub use crate::iterator::{cpp_iter, CppIterator, EndPtr};
 pub use crate::ptr::{NullPtr, Ptr};
 pub use crate::ref_::Ref;
+pub use crate::runtime_library::{RuntimeLibrary, RuntimeLibraryError, RuntimeSymbol};
+pub use crate::var_args::{VarArg, VarArgs, MAX_VAR_ARGS};
+pub use crate::volatile::VolatilePtr;
 pub use libc::wchar_t;
 
 mod casts;
 pub mod cmp;
 mod convert;
 mod cpp_box;
+mod exception;
 mod iterator;
 pub mod ops;
 mod ops_impls;
 mod ptr;
 mod ref_;
+mod runtime_library;
+mod var_args;
 pub mod vector_ops;
+mod volatile;
 
 // C++ doesn't guarantee these types to be exactly u16 and u32,
 // but they are on all supported platforms.
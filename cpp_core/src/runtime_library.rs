@@ -0,0 +1,436 @@
+use std::env;
+use std::error::Error;
+use std::ffi::{CStr, OsString};
+use std::fmt;
+use std::os::raw::c_void;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Once;
+
+/// An error that occurred while loading the C++ wrapper library at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeLibraryError {
+    /// The library file could not be found or loaded.
+    LibraryNotLoaded {
+        /// File name of the library.
+        library: String,
+        /// Error message reported by the system loader.
+        message: String,
+    },
+    /// The library was loaded, but it doesn't export the requested function.
+    SymbolNotFound {
+        /// File name of the library.
+        library: String,
+        /// Name of the missing function.
+        symbol: String,
+    },
+    /// `RuntimeLibrary::load_from` was called after the library had been loaded
+    /// (or after an attempt to load it failed).
+    AlreadyLoaded {
+        /// File name of the library.
+        library: String,
+    },
+}
+
+impl fmt::Display for RuntimeLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeLibraryError::LibraryNotLoaded { library, message } => {
+                write!(f, "failed to load library {}: {}", library, message)
+            }
+            RuntimeLibraryError::SymbolNotFound { library, symbol } => {
+                write!(f, "symbol {} not found in library {}", symbol, library)
+            }
+            RuntimeLibraryError::AlreadyLoaded { library } => {
+                write!(f, "library {} is already loaded", library)
+            }
+        }
+    }
+}
+
+impl Error for RuntimeLibraryError {}
+
+/// Environment variable with directories that contain wrapper libraries loaded at runtime.
+const LIBRARY_PATH_ENV_VAR: &str = "RITUAL_RUNTIME_LIBRARY_PATH";
+
+/// A C++ wrapper library loaded at runtime with `dlopen` (`LoadLibrary` on Windows).
+///
+/// Crates built with runtime loading of the wrapper library
+/// (`RITUAL_LIBRARY_LOADING=runtime`) don't link to the library. Instead, the library
+/// is loaded when any of its functions is called for the first time. The library
+/// is looked up in the following locations:
+///
+/// - directories listed in the `RITUAL_RUNTIME_LIBRARY_PATH` environment variable
+///   (separated in the same way as in `PATH`);
+/// - the build directory of the library (see `with_build_dir`), if it still exists;
+/// - the default search paths of the system loader.
+///
+/// Call `load_from` (or the generated `load_wrapper_library_from` function of the crate)
+/// before using the crate to load the library from a specific file instead.
+///
+/// The generated `load_wrapper_library` function of the crate loads the library
+/// and resolves all of its functions in advance, reporting an error if any of them
+/// is missing. Calls made without that check panic if the library or the function
+/// can't be loaded.
+#[derive(Debug)]
+pub struct RuntimeLibrary {
+    name: &'static str,
+    build_dir: Option<&'static str>,
+    once: Once,
+    handle: AtomicPtr<c_void>,
+    // Set only once and never freed.
+    error: AtomicPtr<String>,
+}
+
+impl RuntimeLibrary {
+    /// Creates an object for the library `name`. `name` doesn't include
+    /// the platform-specific prefix and extension. The library is not loaded
+    /// until it's used for the first time.
+    pub const fn new(name: &'static str) -> Self {
+        RuntimeLibrary {
+            name,
+            build_dir: None,
+            once: Once::new(),
+            handle: AtomicPtr::new(std::ptr::null_mut()),
+            error: AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+
+    /// Creates an object for the library `name` that was built in `build_dir`.
+    /// The build script of the crate uses this constructor, so the library can be
+    /// loaded from the build directory without additional configuration.
+    pub const fn with_build_dir(name: &'static str, build_dir: &'static str) -> Self {
+        RuntimeLibrary {
+            name,
+            build_dir: Some(build_dir),
+            once: Once::new(),
+            handle: AtomicPtr::new(std::ptr::null_mut()),
+            error: AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+
+    /// Returns name of the library file on the current platform.
+    pub fn file_name(&self) -> String {
+        if cfg!(windows) {
+            format!("{}.dll", self.name)
+        } else if cfg!(target_os = "macos") {
+            format!("lib{}.dylib", self.name)
+        } else {
+            format!("lib{}.so", self.name)
+        }
+    }
+
+    /// Returns paths the library is looked up at, in the order of priority,
+    /// given the value of the `RITUAL_RUNTIME_LIBRARY_PATH` environment variable.
+    /// The last path is the file name that is looked up by the system loader.
+    fn search_paths(&self, env_path: Option<OsString>) -> Vec<PathBuf> {
+        let file_name = self.file_name();
+        let mut dirs = env_path
+            .map(|value| env::split_paths(&value).collect::<Vec<_>>())
+            .unwrap_or_default();
+        dirs.extend(self.build_dir.map(PathBuf::from));
+        let mut paths = dirs
+            .into_iter()
+            .map(|dir| dir.join(&file_name))
+            .filter(|path| path.exists())
+            .collect::<Vec<_>>();
+        paths.push(PathBuf::from(file_name));
+        paths
+    }
+
+    /// Loads the library if it's not loaded yet.
+    ///
+    /// If loading fails, the error is remembered and returned on each call.
+    pub fn load(&self) -> Result<(), RuntimeLibraryError> {
+        self.handle().map(|_| ())
+    }
+
+    /// Loads the library from the file `path` instead of looking it up.
+    ///
+    /// This function must be called before any other use of the library.
+    /// If the library is already loaded or an attempt to load it has already been made,
+    /// `RuntimeLibraryError::AlreadyLoaded` is returned.
+    pub fn load_from(&self, path: impl AsRef<Path>) -> Result<(), RuntimeLibraryError> {
+        let mut is_first_attempt = false;
+        self.once.call_once(|| {
+            is_first_attempt = true;
+            self.store(sys::open(path.as_ref()));
+        });
+        if !is_first_attempt {
+            return Err(RuntimeLibraryError::AlreadyLoaded {
+                library: self.file_name(),
+            });
+        }
+        self.load()
+    }
+
+    fn store(&self, result: Result<*mut c_void, String>) {
+        match result {
+            Ok(handle) => self.handle.store(handle, Ordering::Release),
+            Err(message) => self
+                .error
+                .store(Box::into_raw(Box::new(message)), Ordering::Release),
+        }
+    }
+
+    fn handle(&self) -> Result<*mut c_void, RuntimeLibraryError> {
+        self.once.call_once(|| {
+            let paths = self.search_paths(env::var_os(LIBRARY_PATH_ENV_VAR));
+            let mut result = Err(String::new());
+            for path in paths {
+                result = sys::open(&path);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            self.store(result);
+        });
+        let handle = self.handle.load(Ordering::Acquire);
+        if handle.is_null() {
+            let error = self.error.load(Ordering::Acquire);
+            let message = unsafe { error.as_ref() }.cloned().unwrap_or_default();
+            Err(RuntimeLibraryError::LibraryNotLoaded {
+                library: self.file_name(),
+                message,
+            })
+        } else {
+            Ok(handle)
+        }
+    }
+}
+
+/// A function of a `RuntimeLibrary` that is resolved when it's used for the first time.
+#[derive(Debug)]
+pub struct RuntimeSymbol {
+    name: &'static [u8],
+    address: AtomicPtr<c_void>,
+}
+
+impl RuntimeSymbol {
+    /// Creates an object for the symbol `name`. `name` must be null-terminated.
+    pub const fn new(name: &'static [u8]) -> Self {
+        RuntimeSymbol {
+            name,
+            address: AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+
+    /// Returns address of the symbol in `library`, loading the library if necessary.
+    pub fn get(&self, library: &RuntimeLibrary) -> Result<*mut c_void, RuntimeLibraryError> {
+        let address = self.address.load(Ordering::Acquire);
+        if !address.is_null() {
+            return Ok(address);
+        }
+        let handle = library.handle()?;
+        let name =
+            CStr::from_bytes_with_nul(self.name).expect("symbol name is not null-terminated");
+        let address = unsafe { sys::symbol(handle, name) };
+        if address.is_null() {
+            return Err(RuntimeLibraryError::SymbolNotFound {
+                library: library.file_name(),
+                symbol: name.to_string_lossy().into_owned(),
+            });
+        }
+        self.address.store(address, Ordering::Release);
+        Ok(address)
+    }
+
+    /// Same as `get`, but panics if the library or the symbol can't be loaded.
+    /// Used by the generated FFI functions. The panic can only be avoided
+    /// by resolving the symbol with `get` beforehand.
+    pub fn resolve(&self, library: &RuntimeLibrary) -> *mut c_void {
+        match self.get(library) {
+            Ok(address) => address,
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::c_void;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub fn open(path: &Path) -> Result<*mut c_void, String> {
+        let file_name = CString::new(path.as_os_str().as_bytes()).map_err(|err| err.to_string())?;
+        let handle = unsafe { libc::dlopen(file_name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            let message = unsafe { libc::dlerror() };
+            if message.is_null() {
+                Err("unknown error".into())
+            } else {
+                Err(unsafe { CStr::from_ptr(message) }
+                    .to_string_lossy()
+                    .into_owned())
+            }
+        } else {
+            Ok(handle)
+        }
+    }
+
+    pub unsafe fn symbol(handle: *mut c_void, name: &CStr) -> *mut c_void {
+        libc::dlsym(handle, name.as_ptr())
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_void};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LoadLibraryW(file_name: *const u16) -> *mut c_void;
+        fn GetProcAddress(module: *mut c_void, name: *const c_char) -> *mut c_void;
+        fn GetLastError() -> u32;
+    }
+
+    pub fn open(path: &Path) -> Result<*mut c_void, String> {
+        let file_name: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let handle = unsafe { LoadLibraryW(file_name.as_ptr()) };
+        if handle.is_null() {
+            Err(format!("error code {}", unsafe { GetLastError() }))
+        } else {
+            Ok(handle)
+        }
+    }
+
+    pub unsafe fn symbol(handle: *mut c_void, name: &CStr) -> *mut c_void {
+        GetProcAddress(handle, name.as_ptr())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::ffi::CStr;
+    use std::os::raw::c_void;
+    use std::path::Path;
+
+    pub fn open(_path: &Path) -> Result<*mut c_void, String> {
+        Err("runtime loading is not supported on this platform".into())
+    }
+
+    pub unsafe fn symbol(_handle: *mut c_void, _name: &CStr) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RuntimeLibrary, RuntimeLibraryError, RuntimeSymbol};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    static MISSING_LIBRARY: RuntimeLibrary = RuntimeLibrary::new("ritual_missing_library");
+
+    #[test]
+    fn missing_library() {
+        let err = MISSING_LIBRARY.load().unwrap_err();
+        match &err {
+            RuntimeLibraryError::LibraryNotLoaded { library, .. } => {
+                assert!(library.contains("ritual_missing_library"));
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+        // the error is remembered
+        assert_eq!(MISSING_LIBRARY.load().unwrap_err(), err);
+
+        let symbol = RuntimeSymbol::new(b"ritual_missing_symbol\0");
+        assert_eq!(symbol.get(&MISSING_LIBRARY).unwrap_err(), err);
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to load library")]
+    fn resolve_panics() {
+        let symbol = RuntimeSymbol::new(b"ritual_missing_symbol\0");
+        symbol.resolve(&MISSING_LIBRARY);
+    }
+
+    #[test]
+    fn search_paths() {
+        let dir = env::temp_dir().join(format!("ritual_search_paths_{}", std::process::id()));
+        let env_dir = dir.join("env");
+        let build_dir = dir.join("build");
+        let missing_dir = dir.join("missing");
+        fs::create_dir_all(&env_dir).unwrap();
+        fs::create_dir_all(&build_dir).unwrap();
+
+        let build_dir_str: &'static str = Box::leak(build_dir.to_str().unwrap().into());
+        let library = RuntimeLibrary::with_build_dir("ritual_search_test", build_dir_str);
+        let file_name = PathBuf::from(library.file_name());
+        let env_path = env::join_paths(vec![&missing_dir, &env_dir]).unwrap();
+
+        // only the system loader is used if the library is not found in the directories
+        assert_eq!(
+            library.search_paths(Some(env_path.clone())),
+            vec![file_name.clone()]
+        );
+        assert_eq!(library.search_paths(None), vec![file_name.clone()]);
+
+        fs::write(env_dir.join(&file_name), b"").unwrap();
+        fs::write(build_dir.join(&file_name), b"").unwrap();
+        assert_eq!(
+            library.search_paths(Some(env_path)),
+            vec![
+                env_dir.join(&file_name),
+                build_dir.join(&file_name),
+                file_name.clone()
+            ]
+        );
+        assert_eq!(
+            library.search_paths(None),
+            vec![build_dir.join(&file_name), file_name]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_from_missing_file() {
+        let library = RuntimeLibrary::new("ritual_missing_library");
+        let err = library
+            .load_from(
+                env::temp_dir()
+                    .join("ritual_missing_dir")
+                    .join(library.file_name()),
+            )
+            .unwrap_err();
+        match &err {
+            RuntimeLibraryError::LibraryNotLoaded { message, .. } => {
+                assert!(!message.is_empty());
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+        assert_eq!(library.load().unwrap_err(), err);
+        // the library can't be loaded again from another location
+        assert_eq!(
+            library.load_from(library.file_name()).unwrap_err(),
+            RuntimeLibraryError::AlreadyLoaded {
+                library: library.file_name()
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    fn load_from_system_library() {
+        let library = RuntimeLibrary::new("c");
+        library.load_from("libc.so.6").unwrap();
+        library.load().unwrap();
+
+        let symbol = RuntimeSymbol::new(b"strlen\0");
+        assert!(!symbol.get(&library).unwrap().is_null());
+        let missing_symbol = RuntimeSymbol::new(b"ritual_missing_symbol\0");
+        assert_eq!(
+            missing_symbol.get(&library).unwrap_err(),
+            RuntimeLibraryError::SymbolNotFound {
+                library: library.file_name(),
+                symbol: "ritual_missing_symbol".into(),
+            }
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_int, c_void};
+
+/// Maximal number of variadic arguments that can be passed to a C-variadic function.
+///
+/// The FFI wrapper has to call the C++ function with statically known argument types,
+/// so it's instantiated for each combination of argument kinds. This limit
+/// keeps the number of instantiations reasonable.
+pub const MAX_VAR_ARGS: usize = 4;
+
+/// A value passed as a variadic argument of a C++ function (`...` in the declaration).
+///
+/// The caller is responsible for choosing the variant that matches the type
+/// expected by the function (e.g. the format string of `printf`-like functions).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VarArg<'a> {
+    /// `int` value.
+    Int(c_int),
+    /// `double` value. `float` values are also passed as `double`.
+    Double(f64),
+    /// A pointer.
+    Pointer(*const c_void),
+    /// A null-terminated C string (`const char*`).
+    CStr(&'a CStr),
+}
+
+const KIND_INT: c_int = 0;
+const KIND_DOUBLE: c_int = 1;
+const KIND_POINTER: c_int = 2;
+
+/// FFI representation of `VarArg`. Must match `ritual::VarArg` in the C++ wrapper library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct RawVarArg {
+    kind: c_int,
+    int_value: c_int,
+    double_value: f64,
+    pointer_value: *const c_void,
+}
+
+impl RawVarArg {
+    fn new(arg: VarArg<'_>) -> Self {
+        let mut raw = RawVarArg {
+            kind: KIND_INT,
+            int_value: 0,
+            double_value: 0.0,
+            pointer_value: std::ptr::null(),
+        };
+        match arg {
+            VarArg::Int(value) => raw.int_value = value,
+            VarArg::Double(value) => {
+                raw.kind = KIND_DOUBLE;
+                raw.double_value = value;
+            }
+            VarArg::Pointer(value) => {
+                raw.kind = KIND_POINTER;
+                raw.pointer_value = value;
+            }
+            VarArg::CStr(value) => {
+                raw.kind = KIND_POINTER;
+                raw.pointer_value = value.as_ptr() as *const c_void;
+            }
+        }
+        raw
+    }
+}
+
+/// A list of variadic arguments for a C-variadic function.
+///
+/// Generated wrappers of C++ functions declared with `...` accept
+/// an additional `&VarArgs` argument:
+///
+/// ```ignore
+/// // C++: int printf(const char* format, ...);
+/// let name = CString::new("world").unwrap();
+/// printf(
+///     b"Hello, %s! %d\n\0".as_ptr() as *const c_char,
+///     &VarArgs::new(&[VarArg::CStr(&name), VarArg::Int(42)]),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VarArgs<'a> {
+    args: Vec<RawVarArg>,
+    _marker: PhantomData<&'a CStr>,
+}
+
+impl<'a> VarArgs<'a> {
+    /// Creates a list containing `args`.
+    ///
+    /// Panics if the number of arguments exceeds `MAX_VAR_ARGS`.
+    pub fn new(args: &[VarArg<'a>]) -> Self {
+        let mut r = VarArgs::default();
+        for &arg in args {
+            r.push(arg);
+        }
+        r
+    }
+
+    /// Appends `arg` to the list.
+    ///
+    /// Panics if the number of arguments exceeds `MAX_VAR_ARGS`.
+    pub fn push(&mut self, arg: VarArg<'a>) -> &mut Self {
+        assert!(
+            self.args.len() < MAX_VAR_ARGS,
+            "too many variadic arguments (maximum is {})",
+            MAX_VAR_ARGS
+        );
+        self.args.push(RawVarArg::new(arg));
+        self
+    }
+
+    /// Returns number of arguments in the list.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Returns true if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Returns pointer to the FFI representation of the arguments.
+    #[doc(hidden)]
+    pub fn as_ptr(&self) -> *const c_void {
+        self.args.as_ptr() as *const c_void
+    }
+
+    /// Returns number of arguments as passed to the FFI function.
+    #[doc(hidden)]
+    pub fn count(&self) -> c_int {
+        self.args.len() as c_int
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{VarArg, VarArgs, MAX_VAR_ARGS};
+    use std::ffi::CStr;
+
+    #[test]
+    fn var_args() {
+        let text = CStr::from_bytes_with_nul(b"text\0").unwrap();
+        let mut args = VarArgs::new(&[VarArg::Int(1), VarArg::CStr(text)]);
+        args.push(VarArg::Double(2.5));
+        assert_eq!(args.len(), 3);
+        assert_eq!(args.count(), 3);
+        assert!(!args.as_ptr().is_null());
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_var_args() {
+        let args = vec![VarArg::Int(0); MAX_VAR_ARGS + 1];
+        VarArgs::new(&args);
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+use std::ptr;
+
+/// A pointer to a `volatile` C++ value (similar to a C++ `volatile T*` pointer).
+///
+/// `VolatilePtr` is used for pointers and references to `volatile` values
+/// (e.g. hardware registers) returned by or passed to C++ functions.
+/// Unlike `Ptr`, it doesn't provide access to the value through `Deref`.
+/// Instead, each access must be performed explicitly with `read` and `write` methods
+/// that use `std::ptr::read_volatile` and `std::ptr::write_volatile`,
+/// so the compiler will not elide or reorder them.
+///
+/// Note that unlike Rust references, `VolatilePtr` can be freely copied,
+/// producing multiple pointers to the same value.
+///
+/// ### Safety
+///
+/// The user must ensure that the value is alive while `VolatilePtr` is used.
+/// Null pointers must not be read or written.
+pub struct VolatilePtr<T>(*mut T);
+
+/// Creates another pointer to the same value.
+impl<T> Clone for VolatilePtr<T> {
+    fn clone(&self) -> Self {
+        VolatilePtr(self.0)
+    }
+}
+
+/// Creates another pointer to the same value.
+impl<T> Copy for VolatilePtr<T> {}
+
+impl<T> fmt::Debug for VolatilePtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VolatilePtr({:?})", self.0)
+    }
+}
+
+impl<T> VolatilePtr<T> {
+    /// Creates a `VolatilePtr` from a raw pointer.
+    ///
+    /// ### Safety
+    ///
+    /// See type level documentation.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        VolatilePtr(ptr as *mut T)
+    }
+
+    /// Returns the content as a raw mutable pointer.
+    pub fn as_mut_raw_ptr(self) -> *mut T {
+        self.0
+    }
+
+    /// Returns the content as a raw const pointer.
+    pub fn as_raw_ptr(self) -> *const T {
+        self.0
+    }
+
+    /// Returns true if the pointer is null.
+    pub fn is_null(self) -> bool {
+        self.0.is_null()
+    }
+
+    /// Reads the value using a volatile read.
+    ///
+    /// ### Safety
+    ///
+    /// `self` must be valid and properly aligned. See type level documentation.
+    pub unsafe fn read(self) -> T
+    where
+        T: Copy,
+    {
+        ptr::read_volatile(self.0)
+    }
+
+    /// Overwrites the value using a volatile write. The old value is not dropped.
+    ///
+    /// ### Safety
+    ///
+    /// `self` must be valid and properly aligned. The value must not be `const`
+    /// on the C++ side. See type level documentation.
+    pub unsafe fn write(self, value: T) {
+        ptr::write_volatile(self.0, value)
+    }
+}
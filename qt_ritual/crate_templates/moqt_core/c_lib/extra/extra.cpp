@@ -1,3 +1,6 @@
+#include "extra.h"
+#include "moqt_core.h"
+
 int extra_fn() {
     QPoint p(42, 21);
     return p.x();
@@ -0,0 +1,6 @@
+#ifndef MOQT_CORE_EXTRA_H
+#define MOQT_CORE_EXTRA_H
+
+int extra_fn();
+
+#endif // MOQT_CORE_EXTRA_H
@@ -216,6 +216,7 @@ impl DocParser {
                             mismatched_declaration: None,
                             url: Some(format!("{}#{}", file_url, item.anchor)),
                             cross_references: item.cross_references.clone(),
+                            comment: None,
                         });
                     }
                 }
@@ -238,6 +239,7 @@ impl DocParser {
                             mismatched_declaration: None,
                             url: Some(format!("{}#{}", file_url, item.anchor)),
                             cross_references: item.cross_references.clone(),
+                            comment: None,
                         });
                     }
                 }
@@ -261,6 +263,7 @@ impl DocParser {
                 url: Some(format!("{}#{}", file_url, candidates[0].anchor)),
                 mismatched_declaration: Some(candidates[0].declarations[0].clone()),
                 cross_references: candidates[0].cross_references.clone(),
+                comment: None,
             });
         }
         trace!("Declaration mismatch! Declaration 1: {}", declaration1);
@@ -296,6 +299,7 @@ impl DocParser {
                     cross_references: result.cross_references,
                     anchor: None,
                     mismatched_declaration: None,
+                    comment: None,
                 },
                 enum_variants_doc: result.enum_variants,
             });
@@ -339,6 +343,7 @@ impl DocParser {
                 cross_references: cross_references.into_iter().collect(),
                 anchor: None,
                 mismatched_declaration: None,
+                comment: None,
             },
             enum_variants_doc: Vec::new(),
         })
@@ -789,6 +794,7 @@ pub fn parse_docs(
                                 mismatched_declaration: None,
                                 url: None,
                                 cross_references: vec![],
+                                comment: None,
                             },
                         ));
                         parser.mark_enum_variant_used(&data.unscoped_path().doc_id());
@@ -870,6 +876,7 @@ pub fn set_crate_root_doc(data: &mut ProcessorData<'_>) -> Result<()> {
             mismatched_declaration: None,
             url: None,
             cross_references: Vec::new(),
+            comment: None,
         },
     );
 
@@ -206,6 +206,8 @@ pub fn create_config(
 
         config
     };
+    // Qt documentation is loaded from the HTML docs by `qt_doc_parser` instead.
+    config.set_parse_doc_comments(false);
 
     if target::current_env() == target::Env::Msvc {
         config.add_cpp_parser_argument("-std=c++14");
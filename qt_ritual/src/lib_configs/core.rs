@@ -403,6 +403,8 @@ fn add_qpointer(data: &mut ProcessorData<'_>) -> Result<()> {
                         ))]),
                     }),
                     kind: CppTypeDeclarationKind::Class,
+                    deprecation: None,
+                    anonymous_member: None,
                 }),
             )?;
         }
@@ -421,14 +423,20 @@ fn connection_to_bool_function() -> CppFunction {
         arguments: vec![CppFunctionArgument {
             name: "connection".into(),
             has_default_value: false,
+            default_value: None,
             argument_type: CppType::new_reference(
                 true,
                 CppType::Class(CppPath::from_good_str("QMetaObject::Connection")),
             ),
+            type_alias: None,
         }],
         cast: None,
         operator: None,
         declaration_code: None,
         return_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Bool),
+        is_deleted: false,
+        is_noexcept: false,
+        deprecation: None,
+        return_type_alias: None,
     }
 }
@@ -22,6 +22,7 @@ impl MockDocParser {
             mismatched_declaration: None,
             url: None,
             cross_references: vec![],
+            comment: None,
         })
     }
 
@@ -36,6 +37,7 @@ impl MockDocParser {
                 mismatched_declaration: None,
                 url: None,
                 cross_references: vec![],
+                comment: None,
             },
             enum_variants_doc: Vec::new(),
         })
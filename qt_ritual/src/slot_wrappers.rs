@@ -22,6 +22,7 @@ fn generate_slot_wrapper(
 
     let void_ptr = CppType::PointerLike {
         is_const: false,
+        is_volatile: false,
         kind: CppPointerLikeTypeKind::Pointer,
         target: Box::new(CppType::Void),
     };
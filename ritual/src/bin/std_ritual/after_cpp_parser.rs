@@ -42,10 +42,13 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                 is_virtual: false,
                 is_pure_virtual: false,
                 is_const: false,
+                is_volatile: false,
                 is_static: false,
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                is_final: false,
+                is_override: false,
             }),
             operator: None,
             return_type: CppType::Void,
@@ -54,21 +57,31 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                     name: "first".into(),
                     argument_type: CppType::new_pointer(true, t.clone()),
                     has_default_value: false,
+                    default_value: None,
+                    type_alias: None,
                 },
                 CppFunctionArgument {
                     name: "last".into(),
                     argument_type: CppType::new_pointer(true, t.clone()),
                     has_default_value: false,
+                    default_value: None,
+                    type_alias: None,
                 },
                 CppFunctionArgument {
                     name: "alloc".into(),
                     argument_type: allocator_type,
                     has_default_value: true,
+                    default_value: Some("std::allocator<_Tp>()".into()),
+                    type_alias: None,
                 },
             ],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: None,
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }),
     )?;
     data.add_cpp_item(
@@ -92,10 +105,13 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                 is_virtual: false,
                 is_pure_virtual: false,
                 is_const: false,
+                is_volatile: false,
                 is_static: false,
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                is_final: false,
+                is_override: false,
             }),
             operator: None,
             return_type: CppType::Void,
@@ -104,16 +120,24 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                     name: "first".into(),
                     argument_type: CppType::new_pointer(true, t.clone()),
                     has_default_value: false,
+                    default_value: None,
+                    type_alias: None,
                 },
                 CppFunctionArgument {
                     name: "last".into(),
                     argument_type: CppType::new_pointer(true, t),
                     has_default_value: false,
+                    default_value: None,
+                    type_alias: None,
                 },
             ],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: None,
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }),
     )?;
     Ok(())
@@ -198,6 +222,8 @@ fn add_vector_instantiations(data: &mut ProcessorData<'_>) -> Result<()> {
                     name: "vector".into(),
                     template_arguments: Some(vec![arg.clone(), allocator_type]),
                 }),
+                deprecation: None,
+                anonymous_member: None,
             }),
         )?;
     }
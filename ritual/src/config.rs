@@ -214,6 +214,7 @@ pub struct Config {
     processing_steps: ProcessingSteps,
     movable_types_hook: Option<Box<dyn Fn(&CppPath) -> Result<MovableTypesHookOutput>>>,
     cpp_parser_path_hook: Option<Box<dyn Fn(&CppPath) -> Result<bool>>>,
+    cpp_parser_macro_hook: Option<Box<dyn Fn(&str) -> Result<bool>>>,
     rust_path_scope_hook: Option<Box<RustPathScopeHook>>,
     rust_path_hook: Option<Box<RustPathHook>>,
     rust_item_hook: Option<Box<RustItemHook>>,
@@ -222,6 +223,9 @@ pub struct Config {
     cluster_config: Option<ClusterConfig>,
     cpp_checker_tests: Vec<PreliminaryTest>,
     write_dependencies_local_paths: bool,
+    parse_doc_comments: bool,
+    catch_exceptions: bool,
+    subclassable_classes: Vec<CppPath>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -247,6 +251,7 @@ impl Config {
             processing_steps: Default::default(),
             cpp_lib_version: Default::default(),
             cpp_parser_path_hook: Default::default(),
+            cpp_parser_macro_hook: Default::default(),
             rust_path_scope_hook: Default::default(),
             rust_path_hook: Default::default(),
             rust_item_hook: Default::default(),
@@ -255,6 +260,9 @@ impl Config {
             cluster_config: None,
             cpp_checker_tests: Default::default(),
             write_dependencies_local_paths: true,
+            parse_doc_comments: true,
+            catch_exceptions: false,
+            subclassable_classes: Default::default(),
         }
     }
 
@@ -276,6 +284,14 @@ impl Config {
     /// If the crate template contains `rustfmt.toml` file, it's used to format the generated
     /// Rust code instead of the default `rustfmt.toml`.
     ///
+    /// Extra C++ code for the wrapper library can be put in the `c_lib/extra` directory
+    /// of the crate template. Headers from this directory are included
+    /// in the global header of the library (`<crate name>_c_global.h`), so their declarations
+    /// are available to the generated code. `.cpp` files from this directory are compiled
+    /// as separate sources of the library. Each of them must include the headers it uses
+    /// (usually the global header), and inline functions and templates defined in them
+    /// are not visible to the generated code, so such definitions should be placed in headers.
+    ///
     /// Creating crate template is optional. The generator can make a crate without a template.
     pub fn set_crate_template_path<P: Into<PathBuf>>(&mut self, path: P) {
         self.crate_template_path = Some(path.into());
@@ -437,6 +453,23 @@ impl Config {
         self.cpp_parser_path_hook.as_deref()
     }
 
+    /// Sets a filter for preprocessor macros. Object-like macros
+    /// defined in the target headers that expand to a literal expression
+    /// (e.g. `#define VERSION 0x0502` or `#define NAME "lib"`)
+    /// are converted to Rust constants if the hook returns true
+    /// for the macro name. If the hook is not set, all such macros are converted.
+    pub fn set_cpp_parser_macro_hook(&mut self, hook: impl Fn(&str) -> Result<bool> + 'static) {
+        assert!(
+            self.cpp_parser_macro_hook.is_none(),
+            "only one hook can be set"
+        );
+        self.cpp_parser_macro_hook = Some(Box::new(hook));
+    }
+
+    pub fn cpp_parser_macro_hook(&self) -> Option<&(dyn Fn(&str) -> Result<bool> + 'static)> {
+        self.cpp_parser_macro_hook.as_deref()
+    }
+
     pub fn set_rust_path_scope_hook(
         &mut self,
         hook: impl Fn(&CppPath) -> Result<Option<RustPathScope>> + 'static,
@@ -522,6 +555,38 @@ impl Config {
     pub fn write_dependencies_local_paths(&self) -> bool {
         self.write_dependencies_local_paths
     }
+
+    /// Enables or disables importing documentation comments (e.g. Doxygen comments)
+    /// from the C++ headers. Enabled by default.
+    pub fn set_parse_doc_comments(&mut self, value: bool) {
+        self.parse_doc_comments = value;
+    }
+
+    pub fn parse_doc_comments(&self) -> bool {
+        self.parse_doc_comments
+    }
+
+    /// Enables or disables catching C++ exceptions in FFI wrappers.
+    /// If enabled, Rust wrappers of functions not marked `noexcept` return
+    /// `Result<T, cpp_core::CppException>`. Disabled by default.
+    pub fn set_catch_exceptions(&mut self, value: bool) {
+        self.catch_exceptions = value;
+    }
+
+    pub fn catch_exceptions(&self) -> bool {
+        self.catch_exceptions
+    }
+
+    /// Adds a class that can be subclassed from Rust. A C++ subclass
+    /// forwarding calls of virtual methods to Rust callbacks and a Rust trait
+    /// with the overridable methods are generated for each added class.
+    pub fn add_subclassable_class(&mut self, path: CppPath) {
+        self.subclassable_classes.push(path);
+    }
+
+    pub fn subclassable_classes(&self) -> &[CppPath] {
+        &self.subclassable_classes
+    }
 }
 
 #[derive(Default)]
@@ -23,10 +23,16 @@ fn create_cast_method(cast: CppCast, from: &CppType, to: &CppType) -> Result<Cpp
             name: "ptr".to_string(),
             argument_type: from.clone(),
             has_default_value: false,
+            default_value: None,
+            type_alias: None,
         }],
         allows_variadic_arguments: false,
         declaration_code: None,
         cast: Some(cast),
+        is_deleted: false,
+        is_noexcept: true,
+        deprecation: None,
+        return_type_alias: None,
     };
     Ok(CppItem::Function(function))
 }
@@ -43,11 +49,13 @@ fn generate_casts_one(
 ) -> Result<Vec<CppItem>> {
     let target_ptr_type = CppType::PointerLike {
         is_const: false,
+        is_volatile: false,
         kind: CppPointerLikeTypeKind::Pointer,
         target: Box::new(CppType::Class(target_type.clone())),
     };
     let base_ptr_type = CppType::PointerLike {
         is_const: false,
+        is_volatile: false,
         kind: CppPointerLikeTypeKind::Pointer,
         target: Box::new(CppType::Class(base_type.clone())),
     };
@@ -1,9 +1,11 @@
 use crate::config::Config;
 use crate::cpp_checks::CppChecksItem;
-use crate::cpp_code_generator::{all_include_directives, write_include_directives};
+use crate::cpp_code_generator::{
+    all_include_directives, anonymous_type_aliases, write_include_directives,
+};
 use crate::cpp_data::{CppItem, CppPath};
 use crate::cpp_ffi_data::CppFfiItem;
-use crate::cpp_type::CppType;
+use crate::cpp_type::{CppTemplateArgumentValue, CppType};
 use crate::database::{DatabaseClient, DbItem, ItemId};
 use crate::processor::ProcessorData;
 use crate::{cluster_api, cpp_code_generator};
@@ -37,7 +39,12 @@ fn snippet_for_item(item: DbItem<&CppFfiItem>, database: &DatabaseClient) -> Res
     match &item.item {
         CppFfiItem::Function(_) => {
             let item = item.map(|item| item.as_function_ref().unwrap());
-            let item_code = cpp_code_generator::function_implementation(database, item.clone())?;
+            let mut item_code =
+                cpp_code_generator::function_implementation(database, item.clone())?;
+            if let Some(publicist) = cpp_code_generator::function_publicist(database, item.clone())?
+            {
+                item_code = format!("{}\n{}", publicist, item_code);
+            }
             let mut needs_moc = false;
 
             let source_ffi_item = database.source_ffi_item(&item.id)?;
@@ -59,6 +66,10 @@ fn snippet_for_item(item: DbItem<&CppFfiItem>, database: &DatabaseClient) -> Res
             item.item.source_item_cpp_code(database)?,
             true,
         )),
+        CppFfiItem::Subclass(_) => Ok(Snippet::new_global(
+            item.item.source_item_cpp_code(database)?,
+            false,
+        )),
     }
 }
 
@@ -269,6 +280,7 @@ impl PreliminaryTest {
 pub struct LocalCppChecker {
     parent_path: PathBuf,
     all_include_directives: Vec<PathBuf>,
+    type_aliases: String,
     crate_name: String,
     cpp_build_config: CppBuildConfigData,
     cpp_build_paths: CppBuildPaths,
@@ -284,6 +296,7 @@ impl LocalCppChecker {
         Ok(LocalCppChecker {
             parent_path: parent_path.into(),
             all_include_directives: all_include_directives(config)?,
+            type_aliases: String::new(),
             crate_name: config.crate_properties().name().to_string(),
             crate_template_path: config.crate_template_path().cloned(),
             cpp_build_paths: {
@@ -296,6 +309,12 @@ impl LocalCppChecker {
         })
     }
 
+    /// Sets C++ code added to the global header after the include directives
+    /// (see `cpp_code_generator::anonymous_type_aliases`).
+    pub fn set_type_aliases(&mut self, type_aliases: String) {
+        self.type_aliases = type_aliases;
+    }
+
     pub fn get(&self, id: &str) -> Result<CppCheckerInstance> {
         let root_path = self.parent_path.join(id);
         if root_path.exists() {
@@ -327,6 +346,7 @@ impl LocalCppChecker {
             include_str!("../templates/c_lib/global.h"),
         )?;
         write_include_directives(&mut global_file, &self.all_include_directives)?;
+        write!(global_file, "{}", self.type_aliases)?;
 
         let cmake_config = CMakeConfigData {
             cpp_build_config_data: &self.cpp_build_config,
@@ -482,10 +502,11 @@ impl CppChecker<'_, '_> {
     }
 
     fn run_local(&mut self) -> Result<()> {
-        let instance_provider = LocalCppChecker::new(
+        let mut instance_provider = LocalCppChecker::new(
             self.data.workspace.tmp_path().join("cpp_checker"),
             &self.data.config,
         )?;
+        instance_provider.set_type_aliases(anonymous_type_aliases(&self.data.db)?);
 
         let env = self.env();
 
@@ -665,8 +686,11 @@ fn type_paths(type1: &CppType) -> Vec<&CppPath> {
         | CppType::BuiltInNumeric(_)
         | CppType::SpecificNumeric(_)
         | CppType::PointerSizedInteger { .. }
-        | CppType::TemplateParameter { .. } => Vec::new(),
-        CppType::Enum { path } | CppType::Class(path) => vec![path],
+        | CppType::TemplateParameter { .. }
+        | CppType::TemplateArgumentValue(CppTemplateArgumentValue::Integer(_)) => Vec::new(),
+        CppType::Enum { path }
+        | CppType::Class(path)
+        | CppType::TemplateArgumentValue(CppTemplateArgumentValue::EnumValue(path)) => vec![path],
         CppType::FunctionPointer(function) => function
             .arguments
             .iter()
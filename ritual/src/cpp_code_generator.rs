@@ -1,27 +1,65 @@
 use crate::config::Config;
 use crate::cpp_checks::Condition;
+use crate::cpp_data::{CppItem, CppPath, CppTypeDeclarationKind};
 use crate::cpp_ffi_data::{
-    CppFfiArgumentMeaning, CppFfiFunctionKind, CppFfiType, CppFieldAccessorType,
-    CppToFfiTypeConversion, QtSignalWrapper, QtSlotWrapper,
+    CppFfiArgumentMeaning, CppFfiFunctionKind, CppFfiType, CppFieldAccessorType, CppSubclass,
+    CppSubclassFunction, CppToFfiTypeConversion, QtSignalWrapper, QtSlotWrapper,
 };
-use crate::cpp_ffi_data::{CppFfiFunction, CppFfiItem};
+use crate::cpp_ffi_data::{CppFfiFunction, CppFfiFunctionArgument, CppFfiItem};
 use crate::cpp_function::{CppFunction, ReturnValueAllocationPlace};
 use crate::cpp_type::CppPointerLikeTypeKind;
-use crate::cpp_type::CppType;
-use crate::database::{DatabaseClient, DbItem};
+use crate::cpp_type::{
+    CppBuiltInNumericType, CppFunctionPointerType, CppSpecificNumericType,
+    CppSpecificNumericTypeKind, CppType,
+};
+use crate::database::{DatabaseClient, DbItem, ItemId};
 use crate::rust_info::{RustItem, RustStructKind};
 use itertools::Itertools;
+use log::debug;
 use ritual_common::cpp_lib_builder::version_to_number;
 use ritual_common::errors::{bail, err_msg, format_err, Result};
-use ritual_common::file_utils::{create_file, os_str_to_str, path_to_str, read_dir};
+use ritual_common::file_utils::{
+    create_file, file_to_string, os_str_to_str, path_to_str, read_dir,
+};
+use ritual_common::string_utils::CaseOperations;
 use ritual_common::utils::MapIfOk;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
 use std::io::Write;
 use std::iter::once;
 use std::path::{Path, PathBuf};
 
 struct Generator<'a>(&'a DatabaseClient);
 
+/// Returns a condition that is satisfied if any of `conditions` is satisfied.
+fn any_condition(mut conditions: Vec<Condition>) -> Condition {
+    if conditions.contains(&Condition::True) {
+        Condition::True
+    } else if conditions.len() == 1 {
+        conditions.pop().unwrap()
+    } else {
+        Condition::Or(conditions)
+    }
+}
+
+/// Joins C code of function arguments. Empty arguments list is spelled as `void` in C.
+fn c_arguments_list(arg_texts: Vec<String>) -> String {
+    if arg_texts.is_empty() {
+        "void".to_string()
+    } else {
+        arg_texts.join(", ")
+    }
+}
+
+/// Generates declaration of a variable or an argument `name` of type `cpp_type`.
+fn variable_declaration(cpp_type: &CppType, name: &str) -> Result<String> {
+    if cpp_type.is_function_pointer() {
+        cpp_type.to_cpp_code(Some(name))
+    } else {
+        Ok(format!("{} {}", cpp_type.to_cpp_code(None)?, name))
+    }
+}
+
 impl Generator<'_> {
     /// Generates function name, return type and arguments list
     /// as it appears in both function declaration and implementation.
@@ -30,7 +68,11 @@ impl Generator<'_> {
         for arg in &method.arguments {
             arg_texts.push(arg.to_cpp_code()?);
         }
-        let name_with_args = format!("{}({})", method.path.to_cpp_code()?, arg_texts.join(", "));
+        let mut name_with_args =
+            format!("{}({})", method.path.to_cpp_code()?, arg_texts.join(", "));
+        if method.is_noexcept {
+            name_with_args.push_str(" noexcept");
+        }
         let return_type = method.return_type.ffi_type();
         let r = if let CppType::FunctionPointer(..) = return_type {
             return_type.to_cpp_code(Some(&name_with_args))?
@@ -96,6 +138,276 @@ impl Generator<'_> {
         ))
     }
 
+    /// Generates code for a subclass that forwards calls of virtual methods
+    /// to callbacks
+    fn subclass(&self, subclass: &CppSubclass) -> Result<String> {
+        let class_name = subclass.class_path.to_cpp_code()?;
+        let base_class = subclass.base_class.to_cpp_code()?;
+
+        let mut constructors = String::new();
+        for constructor in &subclass.constructors {
+            let args = constructor
+                .arguments
+                .iter()
+                .enumerate()
+                .map_if_ok(|(num, arg)| {
+                    variable_declaration(&arg.argument_type, &format!("arg{}", num))
+                })?
+                .join(", ");
+            let base_args = constructor
+                .arguments
+                .iter()
+                .enumerate()
+                .map(|(num, arg)| {
+                    if let CppType::PointerLike {
+                        kind: CppPointerLikeTypeKind::RValueReference,
+                        ..
+                    } = arg.argument_type
+                    {
+                        format!("std::move(arg{})", num)
+                    } else {
+                        format!("arg{}", num)
+                    }
+                })
+                .join(", ");
+            writeln!(
+                constructors,
+                "    {}({}) : {}({}) {{}}",
+                class_name, args, base_class, base_args
+            )?;
+        }
+
+        let mut methods = String::new();
+        let mut fields = String::new();
+        for function in &subclass.functions {
+            let callback_type = CppType::FunctionPointer(function.callback_type.clone());
+            let field_name = format!("m_{}_callback", function.name);
+            writeln!(
+                fields,
+                "    {} = nullptr;",
+                callback_type.to_cpp_code(Some(&field_name))?
+            )?;
+            writeln!(
+                methods,
+                "\n    void set_{}_callback({}) noexcept {{\n        {} = callback;\n    }}",
+                function.name,
+                callback_type.to_cpp_code(Some("callback"))?,
+                field_name
+            )?;
+            writeln!(
+                methods,
+                "{}",
+                self.subclass_method(subclass, function, &field_name)?
+            )?;
+        }
+
+        Ok(format!(
+            include_str!("../templates/c_lib/subclass.h"),
+            class_name = class_name,
+            base_class = base_class,
+            constructors = constructors,
+            methods = methods,
+            fields = fields,
+        ))
+    }
+
+    /// Generates the overriding method and the method calling
+    /// the base implementation (if available) for a subclass.
+    fn subclass_method(
+        &self,
+        subclass: &CppSubclass,
+        function: &CppSubclassFunction,
+        field_name: &str,
+    ) -> Result<String> {
+        let member = function
+            .function
+            .member
+            .as_ref()
+            .ok_or_else(|| err_msg("virtual method must be a member"))?;
+        let method_name = &function.function.path.last().name;
+        let return_type = function.function.return_type.to_cpp_code(None)?;
+        let args = function
+            .function
+            .arguments
+            .iter()
+            .enumerate()
+            .map_if_ok(|(num, arg)| {
+                variable_declaration(&arg.argument_type, &format!("arg{}", num))
+            })?
+            .join(", ");
+        let arg_names = (0..function.arguments.len())
+            .map(|num| format!("arg{}", num))
+            .join(", ");
+        let const_text = if member.is_const { " const" } else { "" };
+        let base_call = format!(
+            "{}::{}({})",
+            subclass.base_class.to_cpp_code()?,
+            method_name,
+            arg_names
+        );
+
+        let callback_args =
+            once("m_data".to_string())
+                .chain(once("this".to_string()))
+                .chain(function.arguments.iter().enumerate().map_if_ok(
+                    |(num, t)| -> Result<_> {
+                        let name = format!("arg{}", num);
+                        Ok(match t.conversion() {
+                            CppToFfiTypeConversion::NoChange => name,
+                            // the value is only borrowed by the callback
+                            CppToFfiTypeConversion::ValueToPointer { .. }
+                            | CppToFfiTypeConversion::ReferenceToPointer => format!("&{}", name),
+                            CppToFfiTypeConversion::QFlagsToInt => format!("int({})", name),
+                            _ => bail!("unsupported argument type of a subclass callback"),
+                        })
+                    },
+                )?)
+                .join(", ");
+        let callback_call = format!("{}({})", field_name, callback_args);
+        let callback_result = match function.return_type.conversion() {
+            CppToFfiTypeConversion::NoChange => callback_call,
+            // the callback returns an object owned by the caller
+            CppToFfiTypeConversion::ValueToPointer { .. } => format!(
+                "std::move(*std::unique_ptr< {} >({}))",
+                function.return_type.original_type().to_cpp_code(None)?,
+                callback_call
+            ),
+            CppToFfiTypeConversion::ReferenceToPointer => format!("*{}", callback_call),
+            CppToFfiTypeConversion::QFlagsToInt => {
+                format!("{}({})", return_type, callback_call)
+            }
+            _ => bail!("unsupported return type of a subclass callback"),
+        };
+        let fallback = if member.is_pure_virtual {
+            // there is no implementation to fall back to
+            "std::abort();".to_string()
+        } else {
+            format!("return {};", base_call)
+        };
+
+        let mut code = format!(
+            "    {return_type} {name}({args}){const_text} override {{\n        \
+             if ({field}) {{\n            return {result};\n        }}\n        \
+             {fallback}\n    }}",
+            return_type = return_type,
+            name = method_name,
+            args = args,
+            const_text = const_text,
+            field = field_name,
+            result = callback_result,
+            fallback = fallback,
+        );
+        if !member.is_pure_virtual {
+            // the base implementation may throw, in which case the exception
+            // is caught by the FFI wrapper of this method (if enabled)
+            let noexcept_text = if function.function.is_noexcept {
+                " noexcept"
+            } else {
+                ""
+            };
+            write!(
+                code,
+                "\n    {} base_{}({}){}{} {{\n        return {};\n    }}",
+                return_type, function.name, args, const_text, noexcept_text, base_call
+            )?;
+        }
+        Ok(code)
+    }
+
+    /// Returns name of the publicist class that provides access to
+    /// protected members of `class_path`.
+    fn publicist_name(&self, class_path: &CppPath) -> String {
+        format!(
+            "ctr_{}_ffi_publicist_{}",
+            self.0.crate_name(),
+            class_path.ascii_caption()
+        )
+    }
+
+    /// Returns the class path and the name of the protected member accessed by
+    /// the FFI function `ffi_id`, or `None` if the accessed member is not protected.
+    fn protected_member(&self, ffi_id: &ItemId) -> Result<Option<(CppPath, String)>> {
+        let cpp_item = match self.0.source_cpp_item(ffi_id)? {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        if !cpp_item.item.is_protected() {
+            return Ok(None);
+        }
+        let path = cpp_item
+            .item
+            .path()
+            .ok_or_else(|| err_msg("protected member expected to have a path"))?;
+        Ok(Some((path.parent()?, path.last().name.clone())))
+    }
+
+    /// Generates a publicist class that makes protected `members` of `class_path`
+    /// accessible from FFI functions. Each member is only made accessible
+    /// if its condition is satisfied.
+    fn publicist(&self, class_path: &CppPath, members: &[(String, Condition)]) -> Result<String> {
+        let class_name = class_path.to_cpp_code()?;
+        let mut using_declarations = String::new();
+        for (name, condition) in members {
+            let code = format!("    using {}::{};", class_name, name);
+            if condition == &Condition::True {
+                writeln!(using_declarations, "{}", code)?;
+            } else {
+                write!(
+                    using_declarations,
+                    "{}",
+                    self.wrap_with_condition(&code, condition)
+                )?;
+            }
+        }
+        // Pointers to members obtained through the publicist refer to
+        // the base class, so they can be applied to any object of the class.
+        Ok(format!(
+            "// Provides access to protected members of `{class_name}`.\n\
+             struct {publicist} : public {class_name} {{\n{using_declarations}}};\n",
+            class_name = class_name,
+            publicist = self.publicist_name(class_path),
+            using_declarations = using_declarations,
+        ))
+    }
+
+    /// Generates an expression that accesses protected member `cpp_item`
+    /// of the object pointed by `this_name` through its publicist class.
+    fn protected_member_access(&self, this_name: &str, cpp_item: &CppItem) -> Result<String> {
+        let path = cpp_item
+            .path()
+            .ok_or_else(|| err_msg("protected member expected to have a path"))?;
+        let class_path = path.parent()?;
+        let member_pointer = format!(
+            "&{}::{}",
+            self.publicist_name(&class_path),
+            path.last().to_cpp_code()?
+        );
+        if let Some(function) = cpp_item.as_function_ref() {
+            let member = function
+                .member
+                .as_ref()
+                .ok_or_else(|| err_msg("protected function must be a member"))?;
+            let arguments = function
+                .arguments
+                .iter()
+                .map_if_ok(|arg| arg.argument_type.to_cpp_code(None))?
+                .join(", ");
+            // the cast selects the overload
+            Ok(format!(
+                "({}->*static_cast< {} ({}::*)({}){}{} >({}))",
+                this_name,
+                function.return_type.to_cpp_code(None)?,
+                class_path.to_cpp_code()?,
+                arguments,
+                if member.is_const { " const" } else { "" },
+                if member.is_volatile { " volatile" } else { "" },
+                member_pointer
+            ))
+        } else {
+            Ok(format!("({}->*{})", this_name, member_pointer))
+        }
+    }
+
     /// Generates code that wraps `expression` of type `type1.original_type` and
     /// converts it to type `type1.ffi_type`
     fn convert_type_to_ffi(&self, type1: &CppFfiType, expression: String) -> Result<String> {
@@ -109,10 +421,55 @@ impl Generator<'_> {
                 expression
             ),
             CppToFfiTypeConversion::ReferenceToPointer => format!("&{}", expression),
+            CppToFfiTypeConversion::RValueReferenceToPointer => {
+                bail!("rvalue references can't be passed to a callback");
+            }
             CppToFfiTypeConversion::QFlagsToInt => format!("int({})", expression),
+            CppToFfiTypeConversion::StdFunctionToCallback { .. } => {
+                bail!("std::function can't be passed to a callback");
+            }
         })
     }
 
+    /// Generates code that constructs a `std::function` calling the callback
+    /// passed in the FFI argument `name`. The user data is kept alive by
+    /// the `<name>_holder` variable declared in the function body.
+    fn std_function_expression(
+        &self,
+        name: &str,
+        original_type: &CppType,
+        arguments: &[CppFfiType],
+    ) -> Result<String> {
+        let function_type = original_type
+            .as_std_function()
+            .ok_or_else(|| err_msg("std::function type expected"))?;
+        let lambda_args = function_type
+            .arguments
+            .iter()
+            .enumerate()
+            .map_if_ok(|(num, t)| -> Result<_> {
+                Ok(format!("{} arg{}", t.to_cpp_code(None)?, num))
+            })?
+            .join(", ");
+        let callback_args = once(format!("{}_holder.get()", name))
+            .chain(
+                arguments
+                    .iter()
+                    .enumerate()
+                    .map_if_ok(|(num, t)| self.convert_type_to_ffi(t, format!("arg{}", num)))?,
+            )
+            .join(", ");
+        Ok(format!(
+            "std::function< {function_type} >([=]({lambda_args}) -> {return_type} {{ \
+             return {name}({callback_args}); }})",
+            function_type = function_type.to_cpp_function_type_code()?,
+            lambda_args = lambda_args,
+            return_type = function_type.return_type.to_cpp_code(None)?,
+            name = name,
+            callback_args = callback_args,
+        ))
+    }
+
     /// Wraps `expression` returned by the original C++ method to
     /// convert it to return type of the FFI method.
     fn convert_return_type(
@@ -158,9 +515,15 @@ impl Generator<'_> {
             CppToFfiTypeConversion::ReferenceToPointer => {
                 result = format!("&{}", result);
             }
+            CppToFfiTypeConversion::RValueReferenceToPointer => {
+                bail!("rvalue reference return type is not supported");
+            }
             CppToFfiTypeConversion::QFlagsToInt => {
                 result = format!("int({})", result);
             }
+            CppToFfiTypeConversion::StdFunctionToCallback { .. } => {
+                bail!("std::function return type is not supported");
+            }
         }
 
         if method.allocation_place == ReturnValueAllocationPlace::Stack && !is_constructor {
@@ -187,6 +550,9 @@ impl Generator<'_> {
                 match argument.argument_type.conversion() {
                     CppToFfiTypeConversion::ValueToPointer { .. }
                     | CppToFfiTypeConversion::ReferenceToPointer => result = format!("*{}", result),
+                    CppToFfiTypeConversion::RValueReferenceToPointer => {
+                        result = format!("std::move(*{})", result);
+                    }
                     CppToFfiTypeConversion::NoChange
                     | CppToFfiTypeConversion::ImplicitCast { .. } => {}
                     CppToFfiTypeConversion::QFlagsToInt => {
@@ -194,6 +560,7 @@ impl Generator<'_> {
                             kind,
                             is_const,
                             target,
+                            ..
                         } = argument.argument_type.original_type()
                         {
                             if *kind == CppPointerLikeTypeKind::Reference && *is_const {
@@ -206,41 +573,23 @@ impl Generator<'_> {
                         };
                         result = format!("{}({})", type_text, result);
                     }
+                    CppToFfiTypeConversion::StdFunctionToCallback { arguments, .. } => {
+                        result = self.std_function_expression(
+                            &argument.name,
+                            argument.argument_type.original_type(),
+                            arguments,
+                        )?;
+                    }
                 }
                 Ok(result)
             })?;
         Ok(r.join(", "))
     }
 
-    /// Generates code for the value returned by the FFI method.
-    #[allow(clippy::collapsible_if)]
-    fn returned_expression(&self, item: DbItem<&CppFfiFunction>) -> Result<String> {
-        let cpp_item = self
-            .0
-            .source_cpp_item(&item.id)?
-            .ok_or_else(|| format_err!("failed to find original cpp item for {:?}", item))?;
-        let is_destructor = cpp_item
-            .item
-            .as_function_ref()
-            .map_or(false, |f| f.is_destructor());
-
-        let method = item.item;
-        let result = if is_destructor {
-            if let Some(arg) = method
-                .arguments
-                .iter()
-                .find(|x| x.meaning == CppFfiArgumentMeaning::This)
-            {
-                format!("ritual::call_destructor({})", arg.name)
-            } else {
-                bail!("no this arg in destructor");
-            }
-        } else {
-            let result_without_args = if let Some(cpp_function) = cpp_item
-                .item
-                .as_function_ref()
-                .filter(|m| m.is_constructor())
-            {
+    /// Generates the expression that refers to the called function or the accessed field.
+    fn callee_expression(&self, method: &CppFfiFunction, cpp_item: &CppItem) -> Result<String> {
+        let result =
+            if let Some(cpp_function) = cpp_item.as_function_ref().filter(|m| m.is_constructor()) {
                 match method.allocation_place {
                     ReturnValueAllocationPlace::Stack => {
                         if let Some(arg) = method
@@ -265,20 +614,161 @@ impl Generator<'_> {
                     }
                 }
             } else {
-                let path = cpp_item.item.path().ok_or_else(|| {
+                let path = cpp_item.path().ok_or_else(|| {
                     err_msg("cpp item (function or field) expected to have a path")
                 })?;
+                let is_protected = cpp_item.is_protected();
 
                 if let Some(arg) = method
                     .arguments
                     .iter()
                     .find(|x| x.meaning == CppFfiArgumentMeaning::This)
                 {
-                    format!("{}->{}", arg.name, path.last().to_cpp_code()?)
+                    if is_protected {
+                        self.protected_member_access(&arg.name, cpp_item)?
+                    } else {
+                        format!("{}->{}", arg.name, path.last().to_cpp_code()?)
+                    }
+                } else if is_protected {
+                    format!(
+                        "{}::{}",
+                        self.publicist_name(&path.parent()?),
+                        path.last().to_cpp_code()?
+                    )
                 } else {
                     path.to_cpp_code()?
                 }
             };
+        Ok(result)
+    }
+
+    /// Returns arguments of a C-variadic FFI function that are stored
+    /// in its functor (see `var_args_functor`).
+    fn var_args_functor_members<'a>(
+        &self,
+        method: &'a CppFfiFunction,
+    ) -> impl Iterator<Item = &'a CppFfiFunctionArgument> {
+        method.arguments.iter().filter(|arg| {
+            arg.meaning != CppFfiArgumentMeaning::VarArgs
+                && arg.meaning != CppFfiArgumentMeaning::VarArgCount
+                && arg.meaning != CppFfiArgumentMeaning::Exception
+        })
+    }
+
+    /// Returns C++ type of the value returned by the call of a C-variadic function.
+    fn var_args_call_type(&self, cpp_item: &CppItem) -> Result<String> {
+        let function = cpp_item
+            .as_function_ref()
+            .ok_or_else(|| err_msg("variadic arguments are only supported for functions"))?;
+        if function.is_constructor() {
+            Ok(format!("{}*", function.class_path()?.to_cpp_code()?))
+        } else if function.return_type.is_function_pointer() {
+            bail!("function pointer return type is not supported for variadic functions");
+        } else {
+            function.return_type.to_cpp_code(None)
+        }
+    }
+
+    /// Generates a functor that calls a C-variadic function with variadic
+    /// arguments of any types. `ritual::call_var_args` calls the functor
+    /// with values converted to their actual types.
+    fn var_args_functor(&self, item: DbItem<&CppFfiFunction>) -> Result<Option<String>> {
+        let method = item.item;
+        if method.var_args_argument().is_none() {
+            return Ok(None);
+        }
+        let cpp_item = self
+            .0
+            .source_cpp_item(&item.id)?
+            .ok_or_else(|| format_err!("failed to find original cpp item for {:?}", item))?;
+
+        let mut members = String::new();
+        for arg in self.var_args_functor_members(method) {
+            writeln!(
+                members,
+                "  {};",
+                variable_declaration(arg.argument_type.ffi_type(), &arg.name)?
+            )?;
+        }
+        let fixed_arguments = self.arguments_values(method)?;
+        let arguments = if fixed_arguments.is_empty() {
+            "ritual_var_args...".to_string()
+        } else {
+            format!("{}, ritual_var_args...", fixed_arguments)
+        };
+        // templates can't have C linkage
+        Ok(Some(format!(
+            "extern \"C++\" {{\n\
+             struct {name} {{\n\
+             {members}  \
+             template<class... Args>\n  \
+             {return_type} operator()(Args... ritual_var_args) const {{\n    \
+             return {callee}({arguments});\n  \
+             }}\n\
+             }};\n\
+             }}\n",
+            name = self.var_args_functor_name(method)?,
+            members = members,
+            return_type = self.var_args_call_type(&cpp_item.item)?,
+            callee = self.callee_expression(method, &cpp_item.item)?,
+            arguments = arguments,
+        )))
+    }
+
+    /// Returns name of the functor generated by `var_args_functor`.
+    fn var_args_functor_name(&self, method: &CppFfiFunction) -> Result<String> {
+        Ok(format!("{}_var_args", method.path.to_cpp_code()?))
+    }
+
+    /// Generates a call of a C-variadic function through its functor.
+    fn var_args_call(&self, method: &CppFfiFunction, cpp_item: &CppItem) -> Result<String> {
+        let var_args = method
+            .var_args_argument()
+            .ok_or_else(|| err_msg("var args argument not found"))?;
+        let count = method
+            .arguments
+            .iter()
+            .find(|arg| arg.meaning == CppFfiArgumentMeaning::VarArgCount)
+            .ok_or_else(|| err_msg("var arg count argument not found"))?;
+        let members = self
+            .var_args_functor_members(method)
+            .map(|arg| arg.name.as_str())
+            .join(", ");
+        Ok(format!(
+            "ritual::call_var_args< {} >({}{{{}}}, static_cast<const ritual::VarArg*>({}), {})",
+            self.var_args_call_type(cpp_item)?,
+            self.var_args_functor_name(method)?,
+            members,
+            var_args.name,
+            count.name
+        ))
+    }
+
+    /// Generates code for the value returned by the FFI method.
+    #[allow(clippy::collapsible_if)]
+    fn returned_expression(&self, item: DbItem<&CppFfiFunction>) -> Result<String> {
+        let cpp_item = self
+            .0
+            .source_cpp_item(&item.id)?
+            .ok_or_else(|| format_err!("failed to find original cpp item for {:?}", item))?;
+        let is_destructor = cpp_item
+            .item
+            .as_function_ref()
+            .map_or(false, |f| f.is_destructor());
+
+        let method = item.item;
+        let result = if is_destructor {
+            if let Some(arg) = method
+                .arguments
+                .iter()
+                .find(|x| x.meaning == CppFfiArgumentMeaning::This)
+            {
+                format!("ritual::call_destructor({})", arg.name)
+            } else {
+                bail!("no this arg in destructor");
+            }
+        } else {
+            let result_without_args = self.callee_expression(method, &cpp_item.item)?;
             if let CppFfiFunctionKind::FieldAccessor { accessor_type, .. } = &method.kind {
                 if accessor_type == &CppFieldAccessorType::Setter {
                     format!(
@@ -286,9 +776,18 @@ impl Generator<'_> {
                         result_without_args,
                         self.arguments_values(method)?
                     )
+                } else if cpp_item
+                    .item
+                    .as_field_ref()
+                    .map_or(false, |field| field.array_size.is_some())
+                {
+                    // reference to the first item is returned for an array
+                    format!("{}[0]", result_without_args)
                 } else {
                     result_without_args
                 }
+            } else if method.var_args_argument().is_some() {
+                self.var_args_call(method, &cpp_item.item)?
             } else {
                 format!(
                     "{}({})",
@@ -324,22 +823,53 @@ impl Generator<'_> {
                 panic!("Error: no this argument found\n{:?}", method);
             }
         } else {
-            Ok(format!(
-                "{}{};\n",
-                if method.return_type.ffi_type().is_void() {
-                    ""
-                } else {
-                    "return "
-                },
+            // user data of callbacks is owned by all copies of the `std::function`
+            let holders = method
+                .arguments
+                .iter()
+                .filter(|arg| {
+                    matches!(
+                        arg.argument_type.conversion(),
+                        CppToFfiTypeConversion::StdFunctionToCallback { .. }
+                    )
+                })
+                .map(|arg| {
+                    format!(
+                        "std::shared_ptr<void> {0}_holder = \
+                         ritual::callback_data({0}_data, {0}_deleter);\n  ",
+                        arg.name
+                    )
+                })
+                .join("");
+            let is_void = method.return_type.ffi_type().is_void();
+            let body = format!(
+                "{}{}{};\n",
+                holders,
+                if is_void { "" } else { "return " },
                 self.returned_expression(item)?
-            ))
+            );
+            if let Some(arg) = method.exception_argument() {
+                Ok(format!(
+                    "try {{\n    {body}  }} catch (const std::exception& e) {{\n    \
+                     *{arg} = ritual::exception_message(e.what());\n  \
+                     }} catch (...) {{\n    \
+                     *{arg} = ritual::exception_message(\"unknown C++ exception\");\n  \
+                     }}\n{ret}",
+                    body = body,
+                    arg = arg.name,
+                    ret = if is_void { "" } else { "  return {};\n" },
+                ))
+            } else {
+                Ok(body)
+            }
         }
     }
 
     /// Generates implementation of the FFI method for the source file.
     fn function_implementation(&self, method: DbItem<&CppFfiFunction>) -> Result<String> {
         Ok(format!(
-            "RITUAL_EXPORT {} {{\n  {}}}\n\n",
+            "{}RITUAL_EXPORT {} {{\n  {}}}\n\n",
+            self.var_args_functor(method.clone())?.unwrap_or_default(),
             self.function_signature(method.item)?,
             self.source_body(method)?
         ))
@@ -382,11 +912,9 @@ impl Generator<'_> {
         )
     }
 
-    /// Generates a source file with the specified FFI methods.
-    fn generate_cpp_file(&self, file_path: &Path, global_header_name: &str) -> Result<()> {
-        let mut cpp_file = create_file(file_path)?;
-        writeln!(cpp_file, "#include \"{}\"", global_header_name)?;
-
+    /// Returns FFI items that should be included in the wrapper library.
+    /// FFI functions not used by the Rust crate are skipped.
+    fn used_ffi_items(&self) -> Vec<DbItem<&CppFfiItem>> {
         let used_ffi_functions = self
             .0
             .rust_items()
@@ -395,17 +923,98 @@ impl Generator<'_> {
             .map(|item| item.path.last())
             .collect::<HashSet<&str>>();
 
-        let ffi_items = self
-            .0
+        self.0
             .ffi_items()
             .filter(|item| {
                 !item.item.is_function()
                     || used_ffi_functions.contains(item.item.path().last().name.as_str())
             })
-            .collect_vec();
+            .collect_vec()
+    }
+
+    /// Returns name of the Rust module that contains the API for the C++ item `path`.
+    /// Items placed directly in the crate root belong to the `lib` module.
+    fn rust_module_name(&self, path: &CppPath) -> String {
+        let rust_path = self
+            .0
+            .find_rust_items_for_cpp_path(path, false)
+            .ok()
+            .and_then(|mut items| {
+                items
+                    .find(|item| matches!(item.item, RustItem::Struct(_) | RustItem::Module(_)))
+                    .and_then(|item| item.item.path().cloned())
+            });
+        match rust_path {
+            Some(rust_path) if rust_path.parts().len() > 1 => rust_path.parts()[1].to_snake_case(),
+            _ => "lib".to_string(),
+        }
+    }
+
+    /// Returns name of the C++ source file (without extension) that should contain `ffi_item`.
+    ///
+    /// The wrapper library is split by the top level Rust modules, so that
+    /// its parts can be compiled in parallel. All items of a top level class
+    /// or namespace, including its nested types, are placed in the same file.
+    fn cpp_file_stem(
+        &self,
+        ffi_item: &DbItem<&CppFfiItem>,
+        cache: &mut HashMap<CppPath, String>,
+    ) -> Result<String> {
+        let path = if ffi_item.item.is_function() {
+            match self.0.source_cpp_item(&ffi_item.id)? {
+                Some(cpp_item) => cpp_item.item.path().cloned(),
+                None => None,
+            }
+        } else {
+            Some(ffi_item.item.path().clone())
+        };
+        let top_level_path = match path {
+            Some(path) if path.items().len() > 1 || !ffi_item.item.is_function() => {
+                CppPath::from_item(path.items()[0].clone())
+            }
+            _ => return Ok("lib".to_string()),
+        };
+        if let Some(name) = cache.get(&top_level_path) {
+            return Ok(name.clone());
+        }
+        let name = self.rust_module_name(&top_level_path);
+        cache.insert(top_level_path, name.clone());
+        Ok(name)
+    }
+
+    /// Generates source files of the wrapper library in `dir`
+    /// and returns their names.
+    fn generate_cpp_files(&self, dir: &Path, global_header_name: &str) -> Result<Vec<String>> {
+        let mut files = BTreeMap::new();
+        // ensure that the library has at least one source file
+        files.insert("lib".to_string(), Vec::new());
+        let mut cache = HashMap::new();
+        for ffi_item in self.used_ffi_items() {
+            let stem = self.cpp_file_stem(&ffi_item, &mut cache)?;
+            files.entry(stem).or_insert_with(Vec::new).push(ffi_item);
+        }
+
+        let mut file_names = Vec::new();
+        for (stem, ffi_items) in files {
+            let file_name = format!("{}.cpp", stem);
+            self.generate_cpp_file(&dir.join(&file_name), global_header_name, &ffi_items)?;
+            file_names.push(file_name);
+        }
+        Ok(file_names)
+    }
+
+    /// Generates a source file with the specified FFI methods.
+    fn generate_cpp_file(
+        &self,
+        file_path: &Path,
+        global_header_name: &str,
+        ffi_items: &[DbItem<&CppFfiItem>],
+    ) -> Result<()> {
+        let mut cpp_file = create_file(file_path)?;
+        writeln!(cpp_file, "#include \"{}\"", global_header_name)?;
 
         let mut needs_moc = false;
-        for ffi_item in &ffi_items {
+        for ffi_item in ffi_items {
             match &ffi_item.item {
                 CppFfiItem::QtSlotWrapper(qt_slot_wrapper) => {
                     let checks = self.0.cpp_checks(&ffi_item.id)?;
@@ -427,12 +1036,61 @@ impl Generator<'_> {
                     let code = self.qt_signal_wrapper(qt_signal_wrapper)?;
                     write!(cpp_file, "{}", self.wrap_with_condition(&code, &condition))?;
                 }
-                _ => {}
+                CppFfiItem::Subclass(subclass) => {
+                    let checks = self.0.cpp_checks(&ffi_item.id)?;
+                    if !checks.any_success() {
+                        continue;
+                    }
+                    let condition = checks.condition(self.0.environments());
+                    let code = self.subclass(subclass)?;
+                    write!(cpp_file, "{}", self.wrap_with_condition(&code, &condition))?;
+                }
+                CppFfiItem::Function(_) => {}
             }
         }
 
+        // protected members used by the FFI functions and their conditions
+        let mut publicists: Vec<(CppPath, Vec<(String, Vec<Condition>)>)> = Vec::new();
+        for ffi_item in ffi_items {
+            if !ffi_item.item.is_function() {
+                continue;
+            }
+            if let Some((class_path, name)) = self.protected_member(&ffi_item.id)? {
+                let checks = self.0.cpp_checks(&ffi_item.id)?;
+                if !checks.any_success() {
+                    continue;
+                }
+                let condition = checks.condition(self.0.environments());
+                let index = match publicists.iter().position(|(path, _)| path == &class_path) {
+                    Some(index) => index,
+                    None => {
+                        publicists.push((class_path, Vec::new()));
+                        publicists.len() - 1
+                    }
+                };
+                let members = &mut publicists[index].1;
+                match members.iter_mut().find(|(member, _)| member == &name) {
+                    Some((_, conditions)) => conditions.push(condition),
+                    None => members.push((name, vec![condition])),
+                }
+            }
+        }
+        for (class_path, members) in publicists {
+            let members = members
+                .into_iter()
+                .map(|(name, conditions)| (name, any_condition(conditions)))
+                .collect_vec();
+            let class_condition = any_condition(members.iter().map(|(_, c)| c.clone()).collect());
+            let code = self.publicist(&class_path, &members)?;
+            write!(
+                cpp_file,
+                "{}",
+                self.wrap_with_condition(&code, &class_condition)
+            )?;
+        }
+
         writeln!(cpp_file, "extern \"C\" {{")?;
-        for ffi_item in &ffi_items {
+        for ffi_item in ffi_items {
             if let Some(item) = ffi_item.clone().filter_map(|item| item.as_function_ref()) {
                 let checks = self.0.cpp_checks(&ffi_item.id)?;
                 if !checks.any_success() {
@@ -454,6 +1112,180 @@ impl Generator<'_> {
         Ok(())
     }
 
+    /// Returns the integer type used to represent values of the enum `path`.
+    fn enum_underlying_type(&self, path: &CppPath) -> Result<&CppType> {
+        self.0
+            .all_cpp_items()
+            .filter_map(|item| item.item.as_type_ref())
+            .filter(|data| &data.path == path)
+            .find_map(|data| match &data.kind {
+                CppTypeDeclarationKind::Enum { underlying_type } => Some(underlying_type),
+                _ => None,
+            })
+            .ok_or_else(|| format_err!("enum not found: {}", path.to_cpp_pseudo_code()))
+    }
+
+    /// Generates C code for `cpp_type` as it appears in the C header of the wrapper library.
+    /// Class types are replaced with opaque structs, and their names are added to
+    /// `opaque_types`. Enums are replaced with their underlying types.
+    ///
+    /// If `name` is specified, the result is a declaration of a variable with this name.
+    fn c_type_code(
+        &self,
+        cpp_type: &CppType,
+        name: Option<&str>,
+        opaque_types: &mut BTreeSet<String>,
+    ) -> Result<String> {
+        let type_code = match cpp_type {
+            CppType::Void => "void".to_string(),
+            CppType::BuiltInNumeric(numeric) => match numeric {
+                CppBuiltInNumericType::Char16 => "uint_least16_t".to_string(),
+                CppBuiltInNumericType::Char32 => "uint_least32_t".to_string(),
+                CppBuiltInNumericType::Int128 | CppBuiltInNumericType::UInt128 => {
+                    bail!("unsupported numeric type in C header: {:?}", numeric);
+                }
+                _ => numeric.to_cpp_code().to_string(),
+            },
+            CppType::SpecificNumeric(CppSpecificNumericType { bits, kind, .. }) => match kind {
+                CppSpecificNumericTypeKind::Integer { is_signed } => {
+                    format!("{}int{}_t", if *is_signed { "" } else { "u" }, bits)
+                }
+                CppSpecificNumericTypeKind::FloatingPoint => match bits {
+                    32 => "float".to_string(),
+                    64 => "double".to_string(),
+                    _ => bail!("unsupported floating point type in C header: {} bits", bits),
+                },
+            },
+            CppType::PointerSizedInteger { is_signed, .. } => {
+                if *is_signed { "intptr_t" } else { "uintptr_t" }.to_string()
+            }
+            CppType::Enum { path } => {
+                let underlying_type = self.enum_underlying_type(path)?;
+                self.c_type_code(underlying_type, None, opaque_types)?
+            }
+            CppType::Class(path) => {
+                let name = format!("ritual_{}", path.ascii_caption());
+                opaque_types.insert(name.clone());
+                name
+            }
+            CppType::PointerLike {
+                is_const, target, ..
+            } => {
+                if target.is_function_pointer() {
+                    bail!("pointers to function pointers are not supported in C header");
+                }
+                let target_code = self.c_type_code(target, None, opaque_types)?;
+                format!("{}{} *", target_code, if *is_const { " const" } else { "" })
+            }
+            CppType::FunctionPointer(CppFunctionPointerType {
+                return_type,
+                arguments,
+                allows_variadic_arguments,
+            }) => {
+                if *allows_variadic_arguments {
+                    bail!("function pointers with variadic arguments are not supported");
+                }
+                if return_type.is_function_pointer() {
+                    bail!("function pointers returning function pointers are not supported");
+                }
+                let arg_texts = arguments
+                    .iter()
+                    .map_if_ok(|arg| self.c_type_code(arg, None, opaque_types))?;
+                return Ok(format!(
+                    "{} (*{})({})",
+                    self.c_type_code(return_type, None, opaque_types)?,
+                    name.unwrap_or(""),
+                    c_arguments_list(arg_texts)
+                ));
+            }
+            CppType::TemplateParameter { .. } | CppType::TemplateArgumentValue(_) => {
+                bail!("template parameters are not allowed in C header");
+            }
+        };
+        Ok(match name {
+            Some(name) => format!("{} {}", type_code, name),
+            None => type_code,
+        })
+    }
+
+    /// Generates C declaration of an FFI function.
+    fn c_function_declaration(
+        &self,
+        method: &CppFfiFunction,
+        opaque_types: &mut BTreeSet<String>,
+    ) -> Result<String> {
+        let arg_texts = method.arguments.iter().map_if_ok(|arg| {
+            self.c_type_code(arg.argument_type.ffi_type(), Some(&arg.name), opaque_types)
+        })?;
+        let name_with_args = format!(
+            "{}({})",
+            method.path.to_cpp_code()?,
+            c_arguments_list(arg_texts)
+        );
+        let return_type = method.return_type.ffi_type();
+        if return_type.is_function_pointer() {
+            bail!("FFI functions returning function pointers are not supported in C header");
+        }
+        Ok(format!(
+            "{};",
+            self.c_type_code(return_type, Some(&name_with_args), opaque_types)?
+        ))
+    }
+
+    /// Generates a C header that declares all FFI functions of the wrapper library,
+    /// so that it can be used without the Rust crate.
+    fn generate_c_header(&self, file_path: &Path) -> Result<()> {
+        let mut opaque_types = BTreeSet::new();
+        let mut declarations = Vec::new();
+        for ffi_item in self.used_ffi_items() {
+            if let Some(item) = ffi_item.clone().filter_map(|item| item.as_function_ref()) {
+                let checks = self.0.cpp_checks(&ffi_item.id)?;
+                if !checks.any_success() {
+                    continue;
+                }
+                let condition = checks.condition(self.0.environments());
+                match self.c_function_declaration(item.item, &mut opaque_types) {
+                    Ok(code) => declarations.push(self.wrap_with_condition(&code, &condition)),
+                    Err(err) => {
+                        debug!(
+                            "skipping {} in C header: {}",
+                            item.item.path.to_cpp_pseudo_code(),
+                            err
+                        );
+                    }
+                }
+            }
+        }
+
+        let guard = format!("{}_FFI_H", self.0.crate_name().to_uppercase());
+        let mut file = create_file(file_path)?;
+        writeln!(
+            file,
+            include_str!("../templates/c_lib/ffi.h"),
+            crate_name = self.0.crate_name(),
+            guard = guard,
+        )?;
+        for name in opaque_types {
+            writeln!(
+                file,
+                "#ifndef RITUAL_OPAQUE_{name}\n\
+                 #define RITUAL_OPAQUE_{name}\n\
+                 typedef struct {name} {name};\n\
+                 #endif\n",
+                name = name
+            )?;
+        }
+        for declaration in declarations {
+            writeln!(file, "{}", declaration)?;
+        }
+        writeln!(
+            file,
+            "#ifdef __cplusplus\n}} // extern \"C\"\n#endif\n\n#endif // {}",
+            guard
+        )?;
+        Ok(())
+    }
+
     /// Generates a C++ program that determines sizes of target C++ types
     /// on the current platform and outputs the Rust code for `sized_types.rs` module
     /// to the standard output.
@@ -465,6 +1297,7 @@ impl Generator<'_> {
         for dir in include_directives {
             writeln!(output, "#include <{}>", path_to_str(dir)?)?;
         }
+        write!(output, "{}", anonymous_type_aliases(self.0)?)?;
         writeln!(output, "#include <stdio.h>\n\nint main() {{")?;
 
         let rust_items = self.0.rust_items().map(|i| i.item);
@@ -509,12 +1342,35 @@ pub fn qt_signal_wrapper(db: &DatabaseClient, wrapper: &QtSignalWrapper) -> Resu
     Generator(db).qt_signal_wrapper(wrapper)
 }
 
-pub fn generate_cpp_file(
+/// Generates the publicist class required by the FFI function `item`
+/// if it accesses a protected member.
+pub fn function_publicist(
     db: &DatabaseClient,
-    file_path: &Path,
+    item: DbItem<&CppFfiFunction>,
+) -> Result<Option<String>> {
+    let generator = Generator(db);
+    match generator.protected_member(&item.id)? {
+        Some((class_path, name)) => Ok(Some(
+            generator.publicist(&class_path, &[(name, Condition::True)])?,
+        )),
+        None => Ok(None),
+    }
+}
+
+pub fn subclass(db: &DatabaseClient, subclass: &CppSubclass) -> Result<String> {
+    Generator(db).subclass(subclass)
+}
+
+pub fn generate_cpp_files(
+    db: &DatabaseClient,
+    dir: &Path,
     global_header_name: &str,
-) -> Result<()> {
-    Generator(db).generate_cpp_file(file_path, global_header_name)
+) -> Result<Vec<String>> {
+    Generator(db).generate_cpp_files(dir, global_header_name)
+}
+
+pub fn generate_c_header(db: &DatabaseClient, file_path: &Path) -> Result<()> {
+    Generator(db).generate_c_header(file_path)
 }
 
 pub fn generate_cpp_type_size_requester(
@@ -525,24 +1381,62 @@ pub fn generate_cpp_type_size_requester(
     Generator(db).generate_cpp_type_size_requester(include_directives, output)
 }
 
-pub fn all_include_directives(config: &Config) -> Result<Vec<PathBuf>> {
-    let mut all_include_directives = config.include_directives().to_vec();
-
+/// Returns paths (relative to the wrapper library's directory) of the files
+/// in the `c_lib/extra` directory of the crate template.
+/// If `sources` is true, returns source files, otherwise returns headers.
+fn extra_files(config: &Config, sources: bool) -> Result<Vec<String>> {
+    let mut files = Vec::new();
     if let Some(crate_template_path) = config.crate_template_path() {
         let extra_template = crate_template_path.join("c_lib/extra");
         if extra_template.exists() {
             for item in read_dir(&extra_template)? {
-                all_include_directives.push(PathBuf::from(format!(
-                    "extra/{}",
-                    os_str_to_str(&item?.file_name())?
-                )));
+                let file_name = item?.file_name();
+                let file_name = os_str_to_str(&file_name)?;
+                if file_name.ends_with(".cpp") == sources {
+                    files.push(format!("extra/{}", file_name));
+                }
             }
         }
     }
+    files.sort();
+    Ok(files)
+}
 
+/// Returns include directives of the library, including the headers
+/// from the `c_lib/extra` directory of the crate template.
+pub fn all_include_directives(config: &Config) -> Result<Vec<PathBuf>> {
+    let mut all_include_directives = config.include_directives().to_vec();
+    all_include_directives.extend(extra_files(config, false)?.into_iter().map(PathBuf::from));
     Ok(all_include_directives)
 }
 
+/// Returns `.cpp` files from the `c_lib/extra` directory of the crate template.
+/// They are compiled as separate sources of the wrapper library,
+/// so they may contain non-inline definitions, but they must include
+/// all headers they use.
+pub fn extra_sources(config: &Config) -> Result<Vec<String>> {
+    let sources = extra_files(config, true)?;
+    if let Some(crate_template_path) = config.crate_template_path() {
+        for source in &sources {
+            let path = crate_template_path.join("c_lib").join(source);
+            let code = file_to_string(&path)?;
+            if !code
+                .lines()
+                .any(|line| line.trim_start().starts_with("#include"))
+            {
+                bail!(
+                    "{} doesn't include any headers. Extra .cpp files are compiled \
+                     as separate sources of the wrapper library, so they must include \
+                     the headers they use (e.g. the library's global header) \
+                     and can't provide inline or template definitions for the generated code",
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(sources)
+}
+
 pub fn write_include_directives(mut destination: impl Write, directives: &[PathBuf]) -> Result<()> {
     for directive in directives {
         writeln!(
@@ -553,3 +1447,449 @@ pub fn write_include_directives(mut destination: impl Write, directives: &[PathB
     }
     Ok(())
 }
+
+/// Returns C++ code of typedefs naming anonymous types of class members
+/// (e.g. `typedef decltype(S::u) S_u;`), so that generated code
+/// can refer to these types. Types from dependencies are included as well.
+pub fn anonymous_type_aliases(db: &DatabaseClient) -> Result<String> {
+    let mut code = String::new();
+    for item in db.all_cpp_items() {
+        let type1 = if let Some(type1) = item.item.as_type_ref() {
+            type1
+        } else {
+            continue;
+        };
+        let member = if let Some(member) = &type1.anonymous_member {
+            member
+        } else {
+            continue;
+        };
+        let namespaces = &type1.path.items()[..type1.path.items().len() - 1];
+        for namespace in namespaces {
+            write!(code, "namespace {} {{ ", namespace.name)?;
+        }
+        write!(
+            code,
+            "typedef decltype({}) {};",
+            member,
+            type1.path.last().name
+        )?;
+        writeln!(code, "{}", " }".repeat(namespaces.len()))?;
+    }
+    Ok(code)
+}
+
+#[test]
+fn any_condition_is_simplified() {
+    let version = Condition::CppLibraryVersion("5.12.0".into());
+    assert_eq!(
+        any_condition(vec![version.clone(), Condition::True]),
+        Condition::True
+    );
+    assert_eq!(any_condition(vec![version.clone()]), version);
+    let other = Condition::CppLibraryVersion("5.13.0".into());
+    assert_eq!(
+        any_condition(vec![version.clone(), other.clone()]),
+        Condition::Or(vec![version, other])
+    );
+}
+
+#[test]
+fn protected_member_access() {
+    use crate::cpp_checks::CppChecksItem;
+    use crate::cpp_data::{CppClassField, CppVisibility};
+    use crate::cpp_ffi_data::CppFieldAccessorType;
+    use crate::cpp_ffi_generator::{to_ffi_method, FfiNameProvider, NewFfiFunctionKind};
+    use crate::cpp_function::{CppFunctionArgument, CppFunctionKind, CppFunctionMemberData};
+    use crate::database::{Database, IndexedDatabase};
+    use ritual_common::target::{current_target, LibraryTarget};
+    use ritual_common::ReadOnly;
+
+    let mut db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("test_crate".into()),
+            PathBuf::from("test_crate"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let env = LibraryTarget {
+        target: current_target(),
+        cpp_library_version: None,
+    };
+    db.add_environment(env.clone());
+
+    let method = |name: &str, arguments: &[&str]| CppFunction {
+        path: CppPath::from_good_str(&format!("Base::{}", name)),
+        member: Some(CppFunctionMemberData {
+            kind: CppFunctionKind::Regular,
+            is_virtual: false,
+            is_pure_virtual: false,
+            is_const: false,
+            is_volatile: false,
+            is_static: false,
+            visibility: CppVisibility::Protected,
+            is_signal: false,
+            is_slot: false,
+            is_final: false,
+            is_override: false,
+        }),
+        return_type: CppType::Void,
+        arguments: arguments
+            .iter()
+            .map(|name| CppFunctionArgument {
+                name: name.to_string(),
+                argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+                has_default_value: false,
+                default_value: None,
+                type_alias: None,
+            })
+            .collect(),
+        allows_variadic_arguments: false,
+        operator: None,
+        declaration_code: None,
+        cast: None,
+        is_deleted: false,
+        is_noexcept: false,
+        deprecation: None,
+        return_type_alias: None,
+    };
+    let field = CppClassField {
+        path: CppPath::from_good_str("Base::m_count"),
+        field_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        visibility: CppVisibility::Protected,
+        is_static: false,
+        is_const: false,
+        is_volatile: false,
+        value: None,
+        bit_width: None,
+        array_size: None,
+    };
+    let kinds = vec![
+        NewFfiFunctionKind::Function {
+            cpp_function: method("update", &[]),
+        },
+        NewFfiFunctionKind::FieldAccessor {
+            accessor_type: CppFieldAccessorType::CopyGetter,
+            field: field.clone(),
+        },
+        NewFfiFunctionKind::FieldAccessor {
+            accessor_type: CppFieldAccessorType::Setter,
+            field: field.clone(),
+        },
+        NewFfiFunctionKind::Function {
+            cpp_function: method("resize", &["size"]),
+        },
+        NewFfiFunctionKind::Function {
+            cpp_function: method("resize", &["width", "height"]),
+        },
+    ];
+
+    let mut name_provider = FfiNameProvider::testing();
+    let mut ffi_ids = Vec::new();
+    for kind in kinds {
+        let cpp_item = match &kind {
+            NewFfiFunctionKind::Function { cpp_function } => {
+                CppItem::Function(cpp_function.clone())
+            }
+            NewFfiFunctionKind::FieldAccessor { field, .. } => CppItem::ClassField(field.clone()),
+        };
+        let cpp_id = match db
+            .add_cpp_item_without_hook(None, cpp_item.clone())
+            .unwrap()
+        {
+            Some(id) => id,
+            None => db.find_same_cpp_item(&cpp_item).unwrap().id,
+        };
+        let ffi_function = to_ffi_method(kind, &[], &mut name_provider).unwrap();
+        let ffi_id = db
+            .add_ffi_item(Some(cpp_id), CppFfiItem::Function(ffi_function))
+            .unwrap()
+            .unwrap();
+        db.add_cpp_checks_item(
+            ffi_id.clone(),
+            CppChecksItem {
+                env: env.clone(),
+                is_success: true,
+            },
+        );
+        ffi_ids.push(ffi_id);
+    }
+
+    let dir = tempdir::TempDir::new("test_protected_member_access").unwrap();
+    let file_path = dir.path().join("base.cpp");
+    let generator = Generator(&db);
+    let ffi_items = ffi_ids
+        .iter()
+        .map(|id| db.ffi_item(id).unwrap())
+        .collect_vec();
+    generator
+        .generate_cpp_file(&file_path, "test_crate_global.h", &ffi_items)
+        .unwrap();
+    let code = file_to_string(&file_path).unwrap();
+
+    // one using declaration makes all overloads of a method accessible
+    assert!(code.contains(
+        "// Provides access to protected members of `Base`.\n\
+         struct ctr_test_crate_ffi_publicist_Base : public Base {\n    \
+         using Base::update;\n    \
+         using Base::m_count;\n    \
+         using Base::resize;\n\
+         };\n"
+    ));
+    assert_eq!(code.matches("using Base::resize;").count(), 1);
+
+    assert!(code.contains(
+        "(this_ptr->*static_cast< void (Base::*)() >\
+         (&ctr_test_crate_ffi_publicist_Base::update))()"
+    ));
+    // the cast selects the overload
+    assert!(code.contains(
+        "(this_ptr->*static_cast< void (Base::*)(int) >\
+         (&ctr_test_crate_ffi_publicist_Base::resize))(size)"
+    ));
+    assert!(code.contains(
+        "(this_ptr->*static_cast< void (Base::*)(int, int) >\
+         (&ctr_test_crate_ffi_publicist_Base::resize))(width, height)"
+    ));
+    assert!(code.contains("return (this_ptr->*&ctr_test_crate_ffi_publicist_Base::m_count);"));
+    assert!(code.contains("(this_ptr->*&ctr_test_crate_ffi_publicist_Base::m_count) = value;"));
+
+    // a single FFI function gets a publicist with only its member
+    assert_eq!(
+        function_publicist(
+            &db,
+            db.ffi_item(&ffi_ids[3])
+                .unwrap()
+                .filter_map(|item| item.as_function_ref())
+                .unwrap()
+        )
+        .unwrap()
+        .unwrap(),
+        "// Provides access to protected members of `Base`.\n\
+         struct ctr_test_crate_ffi_publicist_Base : public Base {\n    \
+         using Base::resize;\n\
+         };\n"
+    );
+}
+
+#[test]
+fn c_type_code() {
+    use crate::database::{Database, IndexedDatabase};
+    use ritual_common::ReadOnly;
+
+    let db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("c_type_code".into()),
+            PathBuf::from("c_type_code"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let generator = Generator(&db);
+    let mut opaque_types = BTreeSet::new();
+    let mut code = |cpp_type: &CppType, name: Option<&str>| {
+        generator
+            .c_type_code(cpp_type, name, &mut opaque_types)
+            .unwrap()
+    };
+
+    let class_ptr = CppType::new_pointer(
+        true,
+        CppType::Class(CppPath::from_good_str("ns::Class<int>")),
+    );
+    assert_eq!(
+        code(&class_ptr, Some("value")),
+        "ritual_ns_Class_int const * value"
+    );
+    assert_eq!(
+        code(
+            &CppType::BuiltInNumeric(CppBuiltInNumericType::Char16),
+            None
+        ),
+        "uint_least16_t"
+    );
+    let int64 = CppType::SpecificNumeric(CppSpecificNumericType {
+        path: CppPath::from_good_str("qint64"),
+        bits: 64,
+        kind: CppSpecificNumericTypeKind::Integer { is_signed: true },
+    });
+    assert_eq!(code(&int64, None), "int64_t");
+    let callback = CppType::FunctionPointer(CppFunctionPointerType {
+        return_type: Box::new(CppType::Void),
+        arguments: Vec::new(),
+        allows_variadic_arguments: false,
+    });
+    assert_eq!(code(&callback, Some("callback")), "void (*callback)(void)");
+
+    assert_eq!(
+        opaque_types.into_iter().collect_vec(),
+        vec!["ritual_ns_Class_int".to_string()]
+    );
+}
+
+#[test]
+fn cpp_file_partitioning() {
+    use crate::cpp_data::{CppNamespace, CppTypeDeclaration};
+    use crate::database::{Database, IndexedDatabase};
+    use crate::rust_info::{RustModule, RustModuleKind, RustStruct, RustWrapperTypeKind};
+    use crate::rust_type::RustPath;
+    use ritual_common::ReadOnly;
+
+    let mut db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("test_crate".into()),
+            PathBuf::from("test_crate"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let namespace_id = db
+        .add_cpp_item_without_hook(
+            None,
+            CppItem::Namespace(CppNamespace {
+                path: CppPath::from_good_str("ns"),
+            }),
+        )
+        .unwrap()
+        .unwrap();
+    db.add_rust_item(
+        Some(namespace_id),
+        RustItem::Module(RustModule {
+            is_public: true,
+            path: RustPath::from_good_str("test_crate::ns"),
+            kind: RustModuleKind::CppNamespace,
+        }),
+    )
+    .unwrap();
+    let class_id = db
+        .add_cpp_item_without_hook(
+            None,
+            CppItem::Type(CppTypeDeclaration {
+                path: CppPath::from_good_str("TopClass"),
+                kind: CppTypeDeclarationKind::Class,
+                deprecation: None,
+                anonymous_member: None,
+            }),
+        )
+        .unwrap()
+        .unwrap();
+    db.add_rust_item(
+        Some(class_id),
+        RustItem::Struct(RustStruct {
+            path: RustPath::from_good_str("test_crate::TopClass"),
+            kind: RustStructKind::WrapperType(RustWrapperTypeKind::ImmovableClassWrapper),
+            is_public: true,
+            qt_receiver_data: None,
+            deprecation: None,
+        }),
+    )
+    .unwrap();
+
+    let add_function = |db: &mut DatabaseClient, path: &str| {
+        let cpp_id = db
+            .add_cpp_item_without_hook(
+                None,
+                CppItem::Function(CppFunction {
+                    path: CppPath::from_good_str(path),
+                    member: None,
+                    return_type: CppType::Void,
+                    arguments: vec![],
+                    allows_variadic_arguments: false,
+                    operator: None,
+                    declaration_code: None,
+                    cast: None,
+                    is_deleted: false,
+                    is_noexcept: false,
+                    deprecation: None,
+                    return_type_alias: None,
+                }),
+            )
+            .unwrap()
+            .unwrap();
+        db.add_ffi_item(
+            Some(cpp_id),
+            CppFfiItem::Function(CppFfiFunction {
+                arguments: vec![],
+                return_type: CppFfiType::void(),
+                allocation_place: ReturnValueAllocationPlace::NotApplicable,
+                path: CppPath::from_good_str(&format!("ctr_ffi_{}", path.replace("::", "_"))),
+                kind: CppFfiFunctionKind::Function,
+                is_noexcept: false,
+            }),
+        )
+        .unwrap()
+        .unwrap()
+    };
+    let namespace_function = add_function(&mut db, "ns::f");
+    let nested_type_function = add_function(&mut db, "ns::Nested::g");
+    let class_method = add_function(&mut db, "TopClass::h");
+    let free_function = add_function(&mut db, "free_function");
+    let unknown_class_method = add_function(&mut db, "UnknownClass::m");
+
+    let generator = Generator(&db);
+    let mut cache = HashMap::new();
+    let mut stem = |id: &ItemId| {
+        generator
+            .cpp_file_stem(&db.ffi_item(id).unwrap(), &mut cache)
+            .unwrap()
+    };
+    // items are grouped by the top level namespace or class, including nested types
+    assert_eq!(stem(&namespace_function), "ns");
+    assert_eq!(stem(&nested_type_function), "ns");
+    assert_eq!(stem(&class_method), "top_class");
+    // items without a Rust module go to the common file
+    assert_eq!(stem(&free_function), "lib");
+    assert_eq!(stem(&unknown_class_method), "lib");
+
+    assert_eq!(
+        generator.rust_module_name(&CppPath::from_good_str("ns")),
+        "ns"
+    );
+    assert_eq!(
+        generator.rust_module_name(&CppPath::from_good_str("TopClass")),
+        "top_class"
+    );
+    assert_eq!(
+        generator.rust_module_name(&CppPath::from_good_str("UnknownClass")),
+        "lib"
+    );
+}
+
+#[test]
+fn extra_sources_must_include_headers() {
+    use crate::config::CrateProperties;
+    use ritual_common::file_utils::create_dir_all;
+
+    let dir = tempdir::TempDir::new("test_extra_sources").unwrap();
+    let extra_dir = dir.path().join("c_lib/extra");
+    create_dir_all(&extra_dir).unwrap();
+    let write_file = |name: &str, code: &str| {
+        let mut file = create_file(extra_dir.join(name)).unwrap();
+        writeln!(file, "{}", code).unwrap();
+    };
+    write_file("extra.h", "int extra_fn();");
+    write_file(
+        "extra.cpp",
+        "#include \"extra.h\"\nint extra_fn() { return 42; }",
+    );
+
+    let mut config = Config::new(CrateProperties::new("A", "0.0.0"));
+    config.add_include_directive("a.h");
+    config.set_crate_template_path(dir.path());
+    assert_eq!(
+        all_include_directives(&config).unwrap(),
+        vec![PathBuf::from("a.h"), PathBuf::from("extra/extra.h")]
+    );
+    assert_eq!(
+        extra_sources(&config).unwrap(),
+        vec!["extra/extra.cpp".to_string()]
+    );
+
+    // a file relying on the includes of the generated code
+    write_file(
+        "old.cpp",
+        "inline int old_fn() { return QPoint(1, 2).x(); }",
+    );
+    let err = extra_sources(&config).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("old.cpp doesn't include any headers"));
+}
@@ -2,22 +2,34 @@
 
 use crate::cpp_function::CppFunction;
 pub use crate::cpp_operator::CppOperator;
-use crate::cpp_type::{CppTemplateParameter, CppType};
+use crate::cpp_type::{std_function_type, CppBuiltInNumericType, CppTemplateParameter, CppType};
 use crate::database::DatabaseClient;
 use itertools::Itertools;
 use ritual_common::errors::{bail, ensure, Error, Result};
 use ritual_common::utils::MapIfOk;
 use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::fmt;
 use std::str::FromStr;
 
+/// Information about a C++ item marked as deprecated
+/// (e.g. with `[[deprecated]]` or `Q_DECL_DEPRECATED`)
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Hash)]
+pub struct CppDeprecation {
+    /// Message of the deprecation attribute, if any
+    pub message: Option<String>,
+}
+
 /// One item of a C++ enum declaration
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct CppEnumValue {
     /// Full path containing enum path and variant name.
     pub path: CppPath,
-    /// Corresponding value
+    /// Corresponding value. Values of enums with an unsigned underlying type
+    /// that don't fit in `i64` are stored with wrapping.
     pub value: i64,
+    /// Set if the value is deprecated
+    pub deprecation: Option<CppDeprecation>,
 }
 
 impl CppEnumValue {
@@ -41,6 +53,7 @@ fn unscoped_path_should_work() {
         let v = CppEnumValue {
             path: CppPath::from_good_str(path),
             value: 0,
+            deprecation: None,
         };
         assert_eq!(v.unscoped_path(), CppPath::from_good_str(result));
     }
@@ -58,6 +71,18 @@ pub struct CppClassField {
     /// Visibility
     pub visibility: CppVisibility,
     pub is_static: bool,
+    /// True if the field is `const` or `constexpr`
+    pub is_const: bool,
+    /// True if the field is `volatile`
+    pub is_volatile: bool,
+    /// Value of a static constant field if it could be evaluated
+    pub value: Option<CppConstantValue>,
+    /// Width of a bit-field in bits. Address of a bit-field can't be taken,
+    /// so only a copy getter and a setter are available for it.
+    pub bit_width: Option<usize>,
+    /// Number of items in a fixed-size array field (e.g. `4` for `int data[4]`).
+    /// `field_type` is the type of an item in this case.
+    pub array_size: Option<usize>,
 }
 
 impl CppClassField {
@@ -66,6 +91,10 @@ impl CppClassField {
             && self.field_type == other.field_type
             && self.visibility == other.visibility
             && self.is_static == other.is_static
+            && self.is_const == other.is_const
+            && self.is_volatile == other.is_volatile
+            && self.bit_width == other.bit_width
+            && self.array_size == other.array_size
     }
 
     pub fn short_text(&self) -> String {
@@ -75,10 +104,134 @@ impl CppClassField {
             CppVisibility::Private => "private ",
         };
         format!(
-            "{}{} {}",
+            "{}{}{}{}{} {}{}{}",
             visibility_text,
+            if self.is_static { "static " } else { "" },
+            if self.is_const { "const " } else { "" },
+            if self.is_volatile { "volatile " } else { "" },
             self.field_type.to_cpp_pseudo_code(),
             self.path.to_cpp_pseudo_code(),
+            match self.array_size {
+                Some(size) => format!("[{}]", size),
+                None => String::new(),
+            },
+            match self.bit_width {
+                Some(width) => format!(" : {}", width),
+                None => String::new(),
+            },
+        )
+    }
+}
+
+/// Value of a C++ constant evaluated by `clang`
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub enum CppConstantValue {
+    Integer(i64),
+    /// Floating point value in its shortest exact textual form (e.g. `0.5`)
+    Float(String),
+    String(String),
+}
+
+/// Namespace-scope C++ variable
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppGlobalVariable {
+    pub path: CppPath,
+    /// Variable type
+    pub variable_type: CppType,
+    /// True if the variable is `const` or `constexpr`
+    pub is_const: bool,
+    /// True if the variable is `volatile`
+    pub is_volatile: bool,
+    /// Value of the variable's initializer if it could be evaluated
+    pub value: Option<CppConstantValue>,
+}
+
+impl CppGlobalVariable {
+    pub fn is_same(&self, other: &CppGlobalVariable) -> bool {
+        self.path == other.path
+            && self.variable_type == other.variable_type
+            && self.is_const == other.is_const
+            && self.is_volatile == other.is_volatile
+    }
+
+    /// Returns a static public class field equivalent to this variable.
+    /// Global variables are accessed in the same way as static fields.
+    pub fn to_static_field(&self) -> CppClassField {
+        CppClassField {
+            path: self.path.clone(),
+            field_type: self.variable_type.clone(),
+            visibility: CppVisibility::Public,
+            is_static: true,
+            is_const: self.is_const,
+            is_volatile: self.is_volatile,
+            value: self.value.clone(),
+            bit_width: None,
+            array_size: None,
+        }
+    }
+
+    pub fn short_text(&self) -> String {
+        format!(
+            "{}{}{} {}",
+            if self.is_const { "const " } else { "" },
+            if self.is_volatile { "volatile " } else { "" },
+            self.variable_type.to_cpp_pseudo_code(),
+            self.path.to_cpp_pseudo_code(),
+        )
+    }
+}
+
+/// Object-like preprocessor macro that expands to a literal expression
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppMacroConstant {
+    /// Name of the macro
+    pub path: CppPath,
+    /// Evaluated value of the macro
+    pub value: CppConstantValue,
+}
+
+impl CppMacroConstant {
+    /// Returns the C++ type used for the value of the macro in Rust.
+    pub fn value_type(&self) -> CppType {
+        match &self.value {
+            CppConstantValue::Integer(value) => {
+                if i32::try_from(*value).is_ok() {
+                    CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+                } else {
+                    CppType::BuiltInNumeric(CppBuiltInNumericType::LongLong)
+                }
+            }
+            CppConstantValue::Float(_) => CppType::BuiltInNumeric(CppBuiltInNumericType::Double),
+            CppConstantValue::String(_) => {
+                CppType::new_pointer(true, CppType::BuiltInNumeric(CppBuiltInNumericType::Char))
+            }
+        }
+    }
+
+    pub fn short_text(&self) -> String {
+        format!(
+            "#define {} {:?}",
+            self.path.to_cpp_pseudo_code(),
+            self.value
+        )
+    }
+}
+
+/// Type alias declared with `typedef` or `using`
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppTypeAlias {
+    /// Name of the alias
+    pub path: CppPath,
+    /// Type the alias refers to
+    pub target_type: CppType,
+}
+
+impl CppTypeAlias {
+    pub fn short_text(&self) -> String {
+        format!(
+            "using {} = {}",
+            self.path.to_cpp_pseudo_code(),
+            self.target_type.to_cpp_pseudo_code()
         )
     }
 }
@@ -154,6 +307,13 @@ impl CppPath {
     }
 
     pub fn to_cpp_code(&self) -> Result<String> {
+        if let Some(function_type) = std_function_type(self) {
+            // template argument is a function type, not a function pointer type
+            return Ok(format!(
+                "std::function< {} >",
+                function_type.to_cpp_function_type_code()?
+            ));
+        }
         Ok(self
             .items
             .iter()
@@ -369,8 +529,12 @@ impl fmt::Debug for CppPathItem {
 /// Information about a C++ type declaration
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Hash)]
 pub enum CppTypeDeclarationKind {
-    Enum,
+    Enum {
+        /// Integer type used to represent values of the enum
+        underlying_type: CppType,
+    },
     Class,
+    Union,
 }
 
 /// Information about a C++ type declaration
@@ -379,22 +543,55 @@ pub struct CppTypeDeclaration {
     /// Identifier, including namespaces and nested classes
     pub path: CppPath,
     pub kind: CppTypeDeclarationKind,
+    /// Set if the type is deprecated
+    pub deprecation: Option<CppDeprecation>,
+    /// C++ code referring to the class member declared with this anonymous type
+    /// (e.g. `S::u` for `struct S { union { int i; float f; } u; };`).
+    /// Such types are referred to by typedefs generated by ritual.
+    #[serde(default)]
+    pub anonymous_member: Option<String>,
 }
 
 impl CppTypeDeclaration {
     pub fn is_same(&self, other: &CppTypeDeclaration) -> bool {
         self.path == other.path
     }
+
+    /// Returns true if this is a class template partial specialization.
+    /// The last item of its path contains the specialization pattern
+    /// (e.g. `Traits<T*>`) in terms of its own template parameters.
+    pub fn is_template_partial_specialization(&self) -> bool {
+        let args = if let Some(args) = &self.path.last().template_arguments {
+            args
+        } else {
+            return false;
+        };
+        let is_primary_template = args.iter().enumerate().all(|(index, arg)| {
+            if let CppType::TemplateParameter(param) = arg {
+                param.index == index
+            } else {
+                false
+            }
+        });
+        !is_primary_template && args.iter().any(CppType::is_or_contains_template_parameter)
+    }
 }
 
 impl CppTypeDeclarationKind {
-    /// Checks if the type is a class type.
+    /// Checks if the type is a class type. Unions are class types as well.
     pub fn is_class(&self) -> bool {
-        matches!(self, CppTypeDeclarationKind::Class { .. })
+        matches!(
+            self,
+            CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union
+        )
+    }
+
+    pub fn is_union(&self) -> bool {
+        matches!(self, CppTypeDeclarationKind::Union)
     }
 
     pub fn is_enum(&self) -> bool {
-        matches!(self, CppTypeDeclarationKind::Enum)
+        matches!(self, CppTypeDeclarationKind::Enum { .. })
     }
 }
 
@@ -412,6 +609,9 @@ pub enum CppItem {
     Function(CppFunction),
     ClassField(CppClassField),
     ClassBase(CppBaseSpecifier),
+    GlobalVariable(CppGlobalVariable),
+    MacroConstant(CppMacroConstant),
+    TypeAlias(CppTypeAlias),
 }
 
 impl CppItem {
@@ -461,6 +661,27 @@ impl CppItem {
                     false
                 }
             }
+            GlobalVariable(v) => {
+                if let GlobalVariable(v2) = &other {
+                    v.is_same(v2)
+                } else {
+                    false
+                }
+            }
+            MacroConstant(v) => {
+                if let MacroConstant(v2) = &other {
+                    v == v2
+                } else {
+                    false
+                }
+            }
+            TypeAlias(v) => {
+                if let TypeAlias(v2) = &other {
+                    v == v2
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -471,18 +692,32 @@ impl CppItem {
             CppItem::EnumValue(data) => &data.path,
             CppItem::Function(data) => &data.path,
             CppItem::ClassField(data) => &data.path,
+            CppItem::GlobalVariable(data) => &data.path,
+            CppItem::MacroConstant(data) => &data.path,
+            CppItem::TypeAlias(data) => &data.path,
             CppItem::ClassBase(_) => return None,
         };
         Some(path)
     }
 
+    /// Returns true if this is a protected method or field of a class.
+    pub fn is_protected(&self) -> bool {
+        match self {
+            CppItem::Function(function) => function.is_protected(),
+            CppItem::ClassField(field) => field.visibility == CppVisibility::Protected,
+            _ => false,
+        }
+    }
+
     pub fn all_involved_types(&self) -> Vec<CppType> {
         match self {
             CppItem::Type(t) => match t.kind {
-                CppTypeDeclarationKind::Enum => vec![CppType::Enum {
+                CppTypeDeclarationKind::Enum { .. } => vec![CppType::Enum {
                     path: t.path.clone(),
                 }],
-                CppTypeDeclarationKind::Class { .. } => vec![CppType::Class(t.path.clone())],
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => {
+                    vec![CppType::Class(t.path.clone())]
+                }
             },
             CppItem::EnumValue(enum_value) => vec![CppType::Enum {
                 path: enum_value
@@ -501,6 +736,9 @@ impl CppItem {
                 CppType::Class(base.base_class_type.clone()),
                 CppType::Class(base.derived_class_type.clone()),
             ],
+            CppItem::GlobalVariable(variable) => vec![variable.variable_type.clone()],
+            CppItem::MacroConstant(_) => Vec::new(),
+            CppItem::TypeAlias(alias) => vec![alias.target_type.clone()],
         }
     }
 
@@ -525,6 +763,27 @@ impl CppItem {
             None
         }
     }
+    pub fn as_global_variable_ref(&self) -> Option<&CppGlobalVariable> {
+        if let CppItem::GlobalVariable(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+    pub fn as_macro_constant_ref(&self) -> Option<&CppMacroConstant> {
+        if let CppItem::MacroConstant(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+    pub fn as_type_alias_ref(&self) -> Option<&CppTypeAlias> {
+        if let CppItem::TypeAlias(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
     pub fn as_enum_value_ref(&self) -> Option<&CppEnumValue> {
         if let CppItem::EnumValue(data) = self {
             Some(data)
@@ -562,6 +821,9 @@ impl CppItem {
             CppItem::Function(value) => value.short_text(),
             CppItem::ClassField(value) => value.short_text(),
             CppItem::ClassBase(_) => format!("{:?}", self),
+            CppItem::GlobalVariable(value) => value.short_text(),
+            CppItem::MacroConstant(value) => value.short_text(),
+            CppItem::TypeAlias(value) => value.short_text(),
         }
     }
 }
@@ -573,10 +835,15 @@ impl fmt::Display for CppItem {
                 format!("namespace {}", namespace.path.to_cpp_pseudo_code())
             }
             CppItem::Type(type1) => match type1.kind {
-                CppTypeDeclarationKind::Enum => format!("enum {}", type1.path.to_cpp_pseudo_code()),
+                CppTypeDeclarationKind::Enum { .. } => {
+                    format!("enum {}", type1.path.to_cpp_pseudo_code())
+                }
                 CppTypeDeclarationKind::Class { .. } => {
                     format!("class {}", type1.path.to_cpp_pseudo_code())
                 }
+                CppTypeDeclarationKind::Union => {
+                    format!("union {}", type1.path.to_cpp_pseudo_code())
+                }
             },
             CppItem::Function(method) => method.short_text(),
             CppItem::EnumValue(value) => format!(
@@ -585,6 +852,9 @@ impl fmt::Display for CppItem {
                 value.value
             ),
             CppItem::ClassField(field) => field.short_text(),
+            CppItem::GlobalVariable(variable) => variable.short_text(),
+            CppItem::MacroConstant(value) => value.short_text(),
+            CppItem::TypeAlias(value) => value.short_text(),
             CppItem::ClassBase(class_base) => {
                 let virtual_text = if class_base.is_virtual {
                     "virtual "
@@ -1,11 +1,12 @@
 use crate::cpp_code_generator;
 use crate::cpp_data::CppPath;
-use crate::cpp_function::ReturnValueAllocationPlace;
+use crate::cpp_function::{CppFunction, ReturnValueAllocationPlace};
 use crate::cpp_type::{CppBuiltInNumericType, CppFunctionPointerType, CppType};
 use crate::database::DatabaseClient;
 use itertools::Itertools;
 use ritual_common::errors::{bail, Result};
 use serde_derive::{Deserialize, Serialize};
+use std::iter::once;
 
 /// Variation of a field accessor method
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
@@ -83,11 +84,25 @@ pub enum CppToFfiTypeConversion {
     /// C++ argument is a reference (like QPoint&)
     /// and FFI argument is a pointer (like QPoint*)
     ReferenceToPointer,
+    /// C++ argument is an rvalue reference (like QPoint&&)
+    /// and FFI argument is a pointer (like QPoint*).
+    /// The value is moved from the pointed object.
+    RValueReferenceToPointer,
     /// C++ argument is QFlags<T>
     /// and FFI argument is uint
     QFlagsToInt,
     /// Implicit conversion is used.
     ImplicitCast { ffi_type: CppType },
+    /// C++ argument is a `std::function` (or a const reference to it)
+    /// and FFI argument is a function pointer that receives
+    /// user data as its first argument. The user data and its deleter
+    /// are passed in two additional FFI arguments.
+    StdFunctionToCallback {
+        /// FFI types of the function's arguments
+        arguments: Vec<CppFfiType>,
+        /// FFI type of the function's return value
+        return_type: Box<CppFfiType>,
+    },
 }
 
 /// Information that indicates how an FFI function argument
@@ -104,6 +119,18 @@ pub enum CppFfiArgumentMeaning {
     /// This argument receives pointer to the buffer where
     /// the return value should be transferred to using placement new.
     ReturnValue,
+    /// This argument contains user data or its deleter for the callback
+    /// passed as a `std::function` argument. Associated value is index of the
+    /// C++ method's argument (counting from 0).
+    CallbackContext(usize),
+    /// This argument receives a message of the C++ exception
+    /// thrown by the original C++ method, if any.
+    Exception,
+    /// This argument contains values passed as variadic arguments
+    /// of the original C++ method (an array of `ritual::VarArg`).
+    VarArgs,
+    /// This argument contains the number of values passed as variadic arguments.
+    VarArgCount,
 }
 
 impl CppFfiArgumentMeaning {
@@ -159,6 +186,9 @@ pub struct CppFfiFunction {
     pub path: CppPath,
 
     pub kind: CppFfiFunctionKind,
+
+    /// True if the wrapper cannot throw a C++ exception
+    pub is_noexcept: bool,
 }
 
 impl CppFfiFunction {
@@ -175,6 +205,20 @@ impl CppFfiFunction {
         })
     }
 
+    /// Returns the argument receiving the message of a caught C++ exception, if any.
+    pub fn exception_argument(&self) -> Option<&CppFfiFunctionArgument> {
+        self.arguments
+            .iter()
+            .find(|arg| arg.meaning == CppFfiArgumentMeaning::Exception)
+    }
+
+    /// Returns the argument containing values of variadic arguments, if any.
+    pub fn var_args_argument(&self) -> Option<&CppFfiFunctionArgument> {
+        self.arguments
+            .iter()
+            .find(|arg| arg.meaning == CppFfiArgumentMeaning::VarArgs)
+    }
+
     pub fn has_same_kind(&self, other: &Self) -> bool {
         match &self.kind {
             CppFfiFunctionKind::Function { .. } => {
@@ -223,8 +267,20 @@ impl CppFfiType {
             CppToFfiTypeConversion::ReferenceToPointer => {
                 let target = original_type.pointer_like_to_target()?;
                 let is_const = original_type.pointer_like_is_const()?;
+                let mut ffi_type = CppType::new_pointer(is_const, target.clone());
+                if original_type.pointer_like_is_volatile()? {
+                    ffi_type = ffi_type.with_volatile_target()?;
+                }
                 Ok(CppFfiType {
-                    ffi_type: CppType::new_pointer(is_const, target.clone()),
+                    ffi_type,
+                    original_type,
+                    conversion,
+                })
+            }
+            CppToFfiTypeConversion::RValueReferenceToPointer => {
+                let target = original_type.pointer_like_to_target()?;
+                Ok(CppFfiType {
+                    ffi_type: CppType::new_pointer(false, target),
                     original_type,
                     conversion,
                 })
@@ -239,6 +295,24 @@ impl CppFfiType {
                 ffi_type,
                 conversion,
             }),
+            CppToFfiTypeConversion::StdFunctionToCallback {
+                arguments,
+                return_type,
+            } => {
+                let void_ptr = CppType::new_pointer(false, CppType::Void);
+                let ffi_type = CppType::FunctionPointer(CppFunctionPointerType {
+                    return_type: Box::new(return_type.ffi_type.clone()),
+                    arguments: once(void_ptr)
+                        .chain(arguments.iter().map(|arg| arg.ffi_type.clone()))
+                        .collect(),
+                    allows_variadic_arguments: false,
+                });
+                Ok(CppFfiType {
+                    original_type,
+                    ffi_type,
+                    conversion,
+                })
+            }
         }
     }
 
@@ -286,12 +360,43 @@ pub struct QtSlotWrapper {
     pub function_type: CppFunctionPointerType,
 }
 
+/// Virtual method of a subclassed class that can be overridden
+/// by a Rust callback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CppSubclassFunction {
+    /// Original method of the base class
+    pub function: CppFunction,
+    /// Name used in the generated methods of the subclass
+    /// (`set_<name>_callback` and `base_<name>`)
+    pub name: String,
+    /// Arguments of the method.
+    pub arguments: Vec<CppFfiType>,
+    /// Return type of the method.
+    pub return_type: CppFfiType,
+    /// The function pointer type accepted by the callback setter
+    pub callback_type: CppFunctionPointerType,
+}
+
+/// Information about a generated subclass that forwards
+/// calls of virtual methods to Rust callbacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CppSubclass {
+    pub base_class: CppPath,
+    /// Generated name of the subclass
+    pub class_path: CppPath,
+    /// Constructors of the base class available in the subclass
+    pub constructors: Vec<CppFunction>,
+    /// Virtual methods that can be overridden
+    pub functions: Vec<CppSubclassFunction>,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CppFfiItem {
     Function(CppFfiFunction),
     QtSlotWrapper(QtSlotWrapper),
     QtSignalWrapper(QtSignalWrapper),
+    Subclass(CppSubclass),
 }
 
 impl CppFfiItem {
@@ -323,6 +428,14 @@ impl CppFfiItem {
         }
     }
 
+    pub fn as_subclass_ref(&self) -> Option<&CppSubclass> {
+        if let CppFfiItem::Subclass(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+
     pub fn is_slot_wrapper(&self) -> bool {
         matches!(self, CppFfiItem::QtSlotWrapper(_))
     }
@@ -331,6 +444,10 @@ impl CppFfiItem {
         matches!(self, CppFfiItem::QtSignalWrapper(_))
     }
 
+    pub fn is_subclass(&self) -> bool {
+        matches!(self, CppFfiItem::Subclass(_))
+    }
+
     pub fn short_text(&self) -> String {
         match self {
             CppFfiItem::Function(function) => function.path.to_cpp_pseudo_code(),
@@ -350,6 +467,9 @@ impl CppFfiItem {
                     .map(CppType::to_cpp_pseudo_code)
                     .join(", ")
             ),
+            CppFfiItem::Subclass(subclass) => {
+                format!("subclass of {}", subclass.base_class.to_cpp_pseudo_code())
+            }
         }
     }
 
@@ -376,6 +496,13 @@ impl CppFfiItem {
                     false
                 }
             }
+            CppFfiItem::Subclass(subclass) => {
+                if let CppFfiItem::Subclass(other_subclass) = other {
+                    subclass.base_class == other_subclass.base_class
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -384,13 +511,16 @@ impl CppFfiItem {
             CppFfiItem::Function(f) => &f.path,
             CppFfiItem::QtSlotWrapper(s) => &s.class_path,
             CppFfiItem::QtSignalWrapper(s) => &s.class_path,
+            CppFfiItem::Subclass(s) => &s.class_path,
         }
     }
 
     pub fn is_source_item(&self) -> bool {
         match self {
             CppFfiItem::Function(_) => false,
-            CppFfiItem::QtSlotWrapper(_) | CppFfiItem::QtSignalWrapper(_) => true,
+            CppFfiItem::QtSlotWrapper(_)
+            | CppFfiItem::QtSignalWrapper(_)
+            | CppFfiItem::Subclass(_) => true,
         }
     }
 
@@ -403,6 +533,7 @@ impl CppFfiItem {
             CppFfiItem::QtSignalWrapper(signal_wrapper) => {
                 cpp_code_generator::qt_signal_wrapper(db, signal_wrapper)
             }
+            CppFfiItem::Subclass(subclass) => cpp_code_generator::subclass(db, subclass),
         }
     }
 }
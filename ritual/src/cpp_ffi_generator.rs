@@ -9,15 +9,16 @@ use crate::cpp_ffi_data::{CppFfiFunction, CppFfiFunctionKind, CppFieldAccessorTy
 use crate::cpp_ffi_data::{CppFfiFunctionArgument, CppFfiItem};
 use crate::cpp_function::ReturnValueAllocationPlace;
 use crate::cpp_function::{CppFunction, CppFunctionArgument, CppFunctionKind};
-use crate::cpp_type::CppPointerLikeTypeKind;
 use crate::cpp_type::CppType;
 use crate::cpp_type::CppTypeRole;
 use crate::cpp_type::{is_qflags, CppFunctionPointerType};
+use crate::cpp_type::{CppBuiltInNumericType, CppPointerLikeTypeKind};
 use crate::database::DbItem;
 use crate::processor::ProcessorData;
 use itertools::Itertools;
 use log::{debug, trace};
 use ritual_common::errors::{bail, Result, ResultExt};
+use ritual_common::utils::MapIfOk;
 use std::collections::HashSet;
 
 /// Converts this C++ type to its adaptation for FFI interface,
@@ -29,6 +30,12 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
         if original_type.is_or_contains_template_parameter() {
             bail!("template parameters cannot be expressed in FFI");
         }
+        if let Some(function_type) = original_type.as_std_function() {
+            if role == CppTypeRole::ReturnType {
+                bail!("returning std::function is not supported");
+            }
+            return std_function_ffi_type(original_type, function_type);
+        }
         let conversion = match original_type {
             CppType::FunctionPointer(CppFunctionPointerType {
                 return_type,
@@ -75,6 +82,7 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
                 kind,
                 is_const,
                 target,
+                ..
             } => {
                 match *kind {
                     CppPointerLikeTypeKind::Pointer => CppToFfiTypeConversion::NoChange,
@@ -88,10 +96,25 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
                         }
                     }
                     CppPointerLikeTypeKind::RValueReference => {
-                        bail!("rvalue references are not supported");
+                        if role == CppTypeRole::ReturnType {
+                            bail!("returning rvalue references is not supported");
+                        }
+                        if *is_const {
+                            bail!("const rvalue references are not supported");
+                        }
+                        match &**target {
+                            // the value is moved from an object owned by the caller
+                            CppType::Class(path) if !is_qflags(path) => {
+                                CppToFfiTypeConversion::RValueReferenceToPointer
+                            }
+                            _ => bail!("rvalue references to non-class types are not supported"),
+                        }
                     }
                 }
             }
+            CppType::TemplateArgumentValue(_) => {
+                bail!("template argument values are not types");
+            }
             _ => CppToFfiTypeConversion::NoChange,
         };
         CppFfiType::new(original_type.clone(), conversion)
@@ -99,6 +122,41 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
     Ok(inner().with_context(|_| format!("Can't express type to FFI: {:?}", original_type))?)
 }
 
+/// Converts a `std::function` argument type to a callback
+/// function pointer type with user data.
+fn std_function_ffi_type(
+    original_type: &CppType,
+    function_type: &CppFunctionPointerType,
+) -> Result<CppFfiType> {
+    if function_type.allows_variadic_arguments {
+        bail!("std::function with variadic arguments is not supported");
+    }
+    let arguments = function_type
+        .arguments
+        .iter()
+        .map_if_ok(|arg| ffi_type(arg, CppTypeRole::NotReturnType))?;
+    if arguments
+        .iter()
+        .any(|arg| arg.original_type().as_std_function().is_some())
+    {
+        bail!("nested std::function types are not supported");
+    }
+    let return_type = ffi_type(&function_type.return_type, CppTypeRole::ReturnType)?;
+    if return_type.conversion() != &CppToFfiTypeConversion::NoChange {
+        bail!(
+            "unsupported return type of std::function: {}",
+            function_type.return_type.to_cpp_pseudo_code()
+        );
+    }
+    CppFfiType::new(
+        original_type.clone(),
+        CppToFfiTypeConversion::StdFunctionToCallback {
+            arguments,
+            return_type: Box::new(return_type),
+        },
+    )
+}
+
 pub struct FfiNameProvider {
     names: HashSet<String>,
     prefix: String,
@@ -153,6 +211,11 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     let all_cpp_item_ids = data.db.cpp_item_ids().collect_vec();
 
     for cpp_item_id in all_cpp_item_ids {
+        if data.db.has_ffi_items(&cpp_item_id) {
+            // already processed by a previous run; the C++ parser deletes
+            // FFI items of C++ items that use changed types
+            continue;
+        }
         let item = data.db.cpp_item(&cpp_item_id)?;
 
         let source_ffi_item = data.db.source_ffi_item(&cpp_item_id)?;
@@ -170,9 +233,17 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 generate_field_accessors(field, &movable_types, &mut name_provider)
                     .map(|v| v.into_iter().collect_vec())
             }
+            CppItem::GlobalVariable(variable) => generate_field_accessors(
+                &variable.to_static_field(),
+                &movable_types,
+                &mut name_provider,
+            )
+            .map(|v| v.into_iter().collect_vec()),
             CppItem::ClassBase(_)
             | CppItem::Type(_)
             | CppItem::EnumValue(_)
+            | CppItem::MacroConstant(_)
+            | CppItem::TypeAlias(_)
             | CppItem::Namespace(_) => {
                 // no FFI methods for these items
                 continue;
@@ -186,7 +257,18 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                     item.id, item.item, error
                 );
             }
-            Ok(r) => {
+            Ok(mut r) => {
+                let is_destructor = item
+                    .item
+                    .as_function_ref()
+                    .map_or(false, |f| f.is_destructor());
+                if data.config.catch_exceptions() && !is_destructor {
+                    for new_item in &mut r {
+                        if let CppFfiItem::Function(function) = new_item {
+                            add_exception_argument(function)?;
+                        }
+                    }
+                }
                 let source_id = item.id;
                 for new_item in r {
                     data.db.add_ffi_item(Some(source_id.clone()), new_item)?;
@@ -197,6 +279,24 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     Ok(())
 }
 
+/// Adds an argument receiving the message of a C++ exception thrown by
+/// the function, unless the function is known to never throw.
+pub fn add_exception_argument(function: &mut CppFfiFunction) -> Result<()> {
+    if function.is_noexcept {
+        return Ok(());
+    }
+    let message_type = CppType::new_pointer(
+        false,
+        CppType::new_pointer(false, CppType::BuiltInNumeric(CppBuiltInNumericType::Char)),
+    );
+    function.arguments.push(CppFfiFunctionArgument {
+        name: "ritual_exception".to_string(),
+        argument_type: CppFfiType::new(message_type, CppToFfiTypeConversion::NoChange)?,
+        meaning: CppFfiArgumentMeaning::Exception,
+    });
+    Ok(())
+}
+
 fn generate_ffi_methods_for_method(
     method: &CppFunction,
     movable_types: &[CppPath],
@@ -253,6 +353,19 @@ pub fn to_ffi_method(
         return_type: CppFfiType::void(),
         path: name_provider.create_path(&ascii_caption),
         allocation_place: ReturnValueAllocationPlace::NotApplicable,
+        is_noexcept: match &kind {
+            NewFfiFunctionKind::Function { cpp_function, .. } => cpp_function.is_noexcept,
+            // copying or assigning a class value may throw
+            NewFfiFunctionKind::FieldAccessor {
+                field,
+                accessor_type,
+            } => match *accessor_type {
+                CppFieldAccessorType::ConstRefGetter | CppFieldAccessorType::MutRefGetter => true,
+                CppFieldAccessorType::CopyGetter | CppFieldAccessorType::Setter => {
+                    !field.field_type.is_class()
+                }
+            },
+        },
         kind: match kind {
             NewFfiFunctionKind::Function { .. } => CppFfiFunctionKind::Function,
             NewFfiFunctionKind::FieldAccessor { accessor_type, .. } => {
@@ -265,7 +378,12 @@ pub fn to_ffi_method(
         NewFfiFunctionKind::Function { cpp_function, .. } => match &cpp_function.member {
             Some(info) if !info.is_static && info.kind != CppFunctionKind::Constructor => {
                 let class_type = CppType::Class(cpp_function.class_path().unwrap());
-                Some(CppType::new_pointer(info.is_const, class_type))
+                let this_type = CppType::new_pointer(info.is_const, class_type);
+                if info.is_volatile {
+                    Some(this_type.with_volatile_target()?)
+                } else {
+                    Some(this_type)
+                }
             }
             _ => None,
         },
@@ -296,10 +414,6 @@ pub fn to_ffi_method(
 
     let normal_args = match &kind {
         NewFfiFunctionKind::Function { cpp_function, .. } => {
-            if cpp_function.allows_variadic_arguments {
-                bail!("Variable arguments are not supported");
-            }
-
             if cpp_function.is_destructor() {
                 // destructor doesn't have a return type that needs special handling,
                 // but its `allocation_place` must match `allocation_place` of the type's constructor
@@ -321,6 +435,8 @@ pub fn to_ffi_method(
                     name: "value".to_string(),
                     argument_type: field.field_type.clone(),
                     has_default_value: false,
+                    default_value: None,
+                    type_alias: None,
                 };
                 vec![arg]
             } else {
@@ -329,13 +445,69 @@ pub fn to_ffi_method(
         }
     };
 
+    let mut has_callback = false;
     for (index, arg) in normal_args.iter().enumerate() {
         let c_type = ffi_type(&arg.argument_type, CppTypeRole::NotReturnType)?;
+        let is_callback = matches!(
+            c_type.conversion(),
+            CppToFfiTypeConversion::StdFunctionToCallback { .. }
+        );
         r.arguments.push(CppFfiFunctionArgument {
             name: arg.name.clone(),
             argument_type: c_type,
             meaning: CppFfiArgumentMeaning::Argument(index),
         });
+        if is_callback {
+            if has_callback {
+                bail!("only one std::function argument is supported");
+            }
+            has_callback = true;
+            let void_ptr = CppType::new_pointer(false, CppType::Void);
+            let deleter_type = CppType::FunctionPointer(CppFunctionPointerType {
+                return_type: Box::new(CppType::Void),
+                arguments: vec![void_ptr.clone()],
+                allows_variadic_arguments: false,
+            });
+            r.arguments.push(CppFfiFunctionArgument {
+                name: format!("{}_deleter", arg.name),
+                argument_type: CppFfiType::new(deleter_type, CppToFfiTypeConversion::NoChange)?,
+                meaning: CppFfiArgumentMeaning::CallbackContext(index),
+            });
+            r.arguments.push(CppFfiFunctionArgument {
+                name: format!("{}_data", arg.name),
+                argument_type: CppFfiType::new(void_ptr, CppToFfiTypeConversion::NoChange)?,
+                meaning: CppFfiArgumentMeaning::CallbackContext(index),
+            });
+        }
+    }
+
+    if let NewFfiFunctionKind::Function { cpp_function } = &kind {
+        if cpp_function.allows_variadic_arguments {
+            if cpp_function.is_operator() {
+                bail!("variadic operators are not supported");
+            }
+            if has_callback {
+                bail!("std::function arguments of variadic functions are not supported");
+            }
+            // values are passed as an array of `ritual::VarArg`
+            // and converted back by `ritual::call_var_args`
+            r.arguments.push(CppFfiFunctionArgument {
+                name: "var_args".to_string(),
+                argument_type: CppFfiType::new(
+                    CppType::new_pointer(true, CppType::Void),
+                    CppToFfiTypeConversion::NoChange,
+                )?,
+                meaning: CppFfiArgumentMeaning::VarArgs,
+            });
+            r.arguments.push(CppFfiFunctionArgument {
+                name: "var_arg_count".to_string(),
+                argument_type: CppFfiType::new(
+                    CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+                    CppToFfiTypeConversion::NoChange,
+                )?,
+                meaning: CppFfiArgumentMeaning::VarArgCount,
+            });
+        }
     }
 
     let real_return_type = match &kind {
@@ -348,16 +520,22 @@ pub fn to_ffi_method(
         NewFfiFunctionKind::FieldAccessor {
             field,
             accessor_type,
-        } => match *accessor_type {
-            CppFieldAccessorType::CopyGetter => field.field_type.clone(),
-            CppFieldAccessorType::ConstRefGetter => {
-                CppType::new_reference(true, field.field_type.clone())
-            }
-            CppFieldAccessorType::MutRefGetter => {
-                CppType::new_reference(false, field.field_type.clone())
+        } => {
+            let ref_type = |is_const| {
+                let ref_type = CppType::new_reference(is_const, field.field_type.clone());
+                if field.is_volatile {
+                    ref_type.with_volatile_target()
+                } else {
+                    Ok(ref_type)
+                }
+            };
+            match *accessor_type {
+                CppFieldAccessorType::CopyGetter => field.field_type.clone(),
+                CppFieldAccessorType::ConstRefGetter => ref_type(true)?,
+                CppFieldAccessorType::MutRefGetter => ref_type(false)?,
+                CppFieldAccessorType::Setter => CppType::Void,
             }
-            CppFieldAccessorType::Setter => CppType::Void,
-        },
+        }
     };
     let real_return_type_ffi = ffi_type(&real_return_type, CppTypeRole::ReturnType)?;
     match &real_return_type {
@@ -373,6 +551,8 @@ pub fn to_ffi_method(
             } else {
                 r.return_type = real_return_type_ffi;
                 r.allocation_place = ReturnValueAllocationPlace::Heap;
+                // `new` may throw `std::bad_alloc`
+                r.is_noexcept = false;
             }
         }
         _ => {
@@ -383,7 +563,8 @@ pub fn to_ffi_method(
     Ok(r)
 }
 
-/// Adds fictional getter and setter methods for each known public field of each class.
+/// Adds fictional getter and setter methods for each known public or protected field
+/// of each class.
 fn generate_field_accessors(
     field: &CppClassField,
     movable_types: &[CppPath],
@@ -399,16 +580,33 @@ fn generate_field_accessors(
         Ok(CppFfiItem::Function(ffi_function))
     };
 
-    if field.visibility == CppVisibility::Public {
+    if field.visibility != CppVisibility::Private {
+        if field.array_size.is_some() {
+            // Arrays can't be copied or assigned, so only reference getters are available.
+            // Items of class type are not supported because their size is unknown in Rust.
+            if !field.field_type.is_class() {
+                new_methods.push(create_method(CppFieldAccessorType::ConstRefGetter)?);
+                if !field.is_const {
+                    new_methods.push(create_method(CppFieldAccessorType::MutRefGetter)?);
+                }
+            }
+            return Ok(new_methods);
+        }
         // Classes may be non-copyable, so copy getters may not be possible for them,
-        // so we generate reference getters instead.
-        if field.field_type.is_class() {
+        // so we generate reference getters instead. Bit-fields can't be referenced,
+        // but they are never of class type. Volatile fields are also accessed
+        // through references, so that each read and write is explicit in Rust.
+        if (field.field_type.is_class() || field.is_volatile) && field.bit_width.is_none() {
             new_methods.push(create_method(CppFieldAccessorType::ConstRefGetter)?);
-            new_methods.push(create_method(CppFieldAccessorType::MutRefGetter)?);
+            if !field.is_const {
+                new_methods.push(create_method(CppFieldAccessorType::MutRefGetter)?);
+            }
         } else {
             new_methods.push(create_method(CppFieldAccessorType::CopyGetter)?);
         }
-        new_methods.push(create_method(CppFieldAccessorType::Setter)?);
+        if !field.is_const {
+            new_methods.push(create_method(CppFieldAccessorType::Setter)?);
+        }
     }
 
     Ok(new_methods)
@@ -417,12 +615,22 @@ fn generate_field_accessors(
 fn check_preconditions(item: &CppItem, source_ffi_item: Option<DbItem<&CppFfiItem>>) -> Result<()> {
     match item {
         CppItem::Function(function) => {
+            if function.is_deleted {
+                bail!("function is deleted");
+            }
             if let Some(membership) = &function.member {
                 if membership.visibility == CppVisibility::Private {
                     bail!("function is private");
                 }
+                // protected methods are accessed through a publicist class
+                // generated by `cpp_code_generator`
                 if membership.visibility == CppVisibility::Protected {
-                    bail!("function is protected");
+                    if function.is_constructor() || function.is_destructor() {
+                        bail!("protected constructors and destructors are not supported");
+                    }
+                    if function.is_operator() {
+                        bail!("protected operators are not supported");
+                    }
                 }
                 if membership.is_signal {
                     if source_ffi_item.map_or(false, |item| item.item.is_signal_wrapper()) {
@@ -445,8 +653,10 @@ fn check_preconditions(item: &CppItem, source_ffi_item: Option<DbItem<&CppFfiIte
             if field.visibility == CppVisibility::Private {
                 bail!("field is private");
             }
-            if field.visibility == CppVisibility::Protected {
-                bail!("field is protected");
+            // address of a bit-field can't be taken, so it can't be accessed
+            // through a pointer to member
+            if field.visibility == CppVisibility::Protected && field.bit_width.is_some() {
+                bail!("protected bit-fields are not supported");
             }
         }
         _ => {}
@@ -1,6 +1,6 @@
 //! Types for handling information about C++ methods.
 
-use crate::cpp_data::{CppPath, CppPathItem, CppVisibility};
+use crate::cpp_data::{CppDeprecation, CppPath, CppPathItem, CppVisibility};
 use crate::cpp_ffi_data::CppCast;
 pub use crate::cpp_operator::{CppOperator, CppOperatorInfo};
 use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
@@ -23,6 +23,11 @@ pub struct CppFunctionArgument {
     /// Flag indicating that the argument has default value and
     /// therefore can be omitted when calling the method
     pub has_default_value: bool,
+    /// C++ code of the default value expression, if the argument has
+    /// a default value and it could be extracted from the declaration
+    pub default_value: Option<String>,
+    /// Type alias used in the declaration of the argument's type, if any
+    pub type_alias: Option<CppPath>,
 }
 
 impl CppFunctionArgument {
@@ -63,6 +68,9 @@ pub struct CppFunctionMemberData {
     /// True if this is a const method, i.e. "this" pointer receives by
     /// this method has const type
     pub is_const: bool,
+    /// True if this is a volatile method, i.e. "this" pointer received by
+    /// this method has volatile type
+    pub is_volatile: bool,
     /// True if this is a static method, i.e. it doesn't receive "this" pointer at all.
     pub is_static: bool,
     /// Method visibility
@@ -71,12 +79,17 @@ pub struct CppFunctionMemberData {
     pub is_signal: bool,
     /// True if the method is a Qt slot
     pub is_slot: bool,
+    /// True if the method is marked as `final`
+    pub is_final: bool,
+    /// True if the method is marked as `override`
+    pub is_override: bool,
 }
 
 impl CppFunctionMemberData {
     fn is_same(&self, other: &CppFunctionMemberData) -> bool {
         self.kind == other.kind
             && self.is_const == other.is_const
+            && self.is_volatile == other.is_volatile
             && self.is_static == other.is_static
     }
 }
@@ -121,6 +134,14 @@ pub struct CppFunction {
     /// C++ code of the method's declaration.
     /// None if the method was not explicitly declared.
     pub declaration_code: Option<String>,
+    /// True if the function is deleted (`= delete`)
+    pub is_deleted: bool,
+    /// True if the function is declared as non-throwing (`noexcept` or `throw()`)
+    pub is_noexcept: bool,
+    /// Set if the function is deprecated
+    pub deprecation: Option<CppDeprecation>,
+    /// Type alias used in the declaration of the return type, if any
+    pub return_type_alias: Option<CppPath>,
 }
 
 /// Chosen type allocation place for the method
@@ -246,6 +267,23 @@ impl CppFunction {
             if info.is_const {
                 write!(s, " const").unwrap();
             }
+            if info.is_volatile {
+                write!(s, " volatile").unwrap();
+            }
+        }
+        if self.is_noexcept {
+            write!(s, " noexcept").unwrap();
+        }
+        if let Some(info) = &self.member {
+            if info.is_override {
+                write!(s, " override").unwrap();
+            }
+            if info.is_final {
+                write!(s, " final").unwrap();
+            }
+        }
+        if self.is_deleted {
+            write!(s, " = delete").unwrap();
         }
         s.trim().to_string()
     }
@@ -298,7 +336,7 @@ impl CppFunction {
                     arg.argument_type.to_cpp_pseudo_code(),
                     arg.name,
                     if arg.has_default_value {
-                        " = …".to_string()
+                        format!(" = {}", arg.default_value.as_deref().unwrap_or("…"))
                     } else {
                         String::new()
                     }
@@ -309,6 +347,23 @@ impl CppFunction {
             if info.is_const {
                 s = format!("{} const", s);
             }
+            if info.is_volatile {
+                s = format!("{} volatile", s);
+            }
+        }
+        if self.is_noexcept {
+            s = format!("{} noexcept", s);
+        }
+        if let Some(info) = &self.member {
+            if info.is_override {
+                s = format!("{} override", s);
+            }
+            if info.is_final {
+                s = format!("{} final", s);
+            }
+        }
+        if self.is_deleted {
+            s = format!("{} = delete", s);
         }
         s.trim().to_string()
     }
@@ -355,6 +410,27 @@ impl CppFunction {
         }
     }
 
+    /// Returns true if this method is marked as `final`.
+    pub fn is_final(&self) -> bool {
+        match &self.member {
+            Some(info) => info.is_final,
+            None => false,
+        }
+    }
+
+    /// Returns true if this method is marked as `override`.
+    pub fn is_override(&self) -> bool {
+        match &self.member {
+            Some(info) => info.is_override,
+            None => false,
+        }
+    }
+
+    /// Returns true if this method is virtual and may be overridden in a derived class.
+    pub fn can_be_overridden(&self) -> bool {
+        self.is_virtual() && !self.is_final()
+    }
+
     pub fn is_private(&self) -> bool {
         match &self.member {
             Some(info) => info.visibility == CppVisibility::Private,
@@ -362,6 +438,13 @@ impl CppFunction {
         }
     }
 
+    pub fn is_protected(&self) -> bool {
+        match &self.member {
+            Some(info) => info.visibility == CppVisibility::Protected,
+            None => false,
+        }
+    }
+
     pub fn is_signal(&self) -> bool {
         match &self.member {
             Some(info) => info.is_signal,
@@ -435,6 +518,7 @@ impl CppFunction {
         if let Some(class_membership) = &self.member {
             result.push(CppType::PointerLike {
                 is_const: class_membership.is_const,
+                is_volatile: class_membership.is_volatile,
                 kind: CppPointerLikeTypeKind::Pointer,
                 target: Box::new(CppType::Class(self.class_path().unwrap())),
             });
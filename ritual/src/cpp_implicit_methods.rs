@@ -33,11 +33,14 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                     is_virtual: false, // the destructor can actually be virtual but we don't care about it here
                     is_pure_virtual: false,
                     is_const: false,
+                    is_volatile: false,
                     is_static: false,
                     visibility: CppVisibility::Public,
                     is_signal: false,
                     is_slot: false,
                     kind: CppFunctionKind::Destructor,
+                    is_final: false,
+                    is_override: false,
                 }),
                 operator: None,
                 return_type: CppType::Void,
@@ -45,6 +48,11 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 allows_variadic_arguments: false,
                 cast: None,
                 declaration_code: None,
+                is_deleted: false,
+                // destructors are implicitly `noexcept`
+                is_noexcept: true,
+                deprecation: None,
+                return_type_alias: None,
             };
             methods.push(ItemWithSource::new(&type1.id, destructor));
 
@@ -57,11 +65,14 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                     is_virtual: false,
                     is_pure_virtual: false,
                     is_const: false,
+                    is_volatile: false,
                     is_static: false,
                     visibility: CppVisibility::Public,
                     is_signal: false,
                     is_slot: false,
                     kind: CppFunctionKind::Constructor,
+                    is_final: false,
+                    is_override: false,
                 }),
                 operator: None,
                 return_type: CppType::Void,
@@ -69,6 +80,10 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 allows_variadic_arguments: false,
                 declaration_code: None,
                 cast: None,
+                is_deleted: false,
+                is_noexcept: false,
+                deprecation: None,
+                return_type_alias: None,
             };
             methods.push(ItemWithSource::new(&type1.id, default_constructor));
 
@@ -76,6 +91,8 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 argument_type: CppType::new_reference(true, CppType::Class(class_path.clone())),
                 name: "other".to_string(),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             };
 
             let copy_constructor = CppFunction {
@@ -87,11 +104,14 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                     is_virtual: false,
                     is_pure_virtual: false,
                     is_const: false,
+                    is_volatile: false,
                     is_static: false,
                     visibility: CppVisibility::Public,
                     is_signal: false,
                     is_slot: false,
                     kind: CppFunctionKind::Constructor,
+                    is_final: false,
+                    is_override: false,
                 }),
                 operator: None,
                 return_type: CppType::Void,
@@ -99,6 +119,10 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 allows_variadic_arguments: false,
                 cast: None,
                 declaration_code: None,
+                is_deleted: false,
+                is_noexcept: false,
+                deprecation: None,
+                return_type_alias: None,
             };
             methods.push(ItemWithSource::new(&type1.id, copy_constructor));
 
@@ -111,11 +135,14 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                     is_virtual: false,
                     is_pure_virtual: false,
                     is_const: false,
+                    is_volatile: false,
                     is_static: false,
                     visibility: CppVisibility::Public,
                     is_signal: false,
                     is_slot: false,
                     kind: CppFunctionKind::Regular,
+                    is_final: false,
+                    is_override: false,
                 }),
                 operator: Some(CppOperator::Assignment),
                 return_type: CppType::new_reference(false, CppType::Class(class_path.clone())),
@@ -123,6 +150,10 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 allows_variadic_arguments: false,
                 cast: None,
                 declaration_code: None,
+                is_deleted: false,
+                is_noexcept: false,
+                deprecation: None,
+                return_type_alias: None,
             };
             methods.push(ItemWithSource::new(&type1.id, assignment_operator));
         }
@@ -1,33 +1,49 @@
 use crate::config::Config;
-use crate::cpp_code_generator::{all_include_directives, write_include_directives};
+use crate::cpp_code_generator::{
+    all_include_directives, anonymous_type_aliases, write_include_directives,
+};
 use crate::cpp_data::{
-    CppBaseSpecifier, CppClassField, CppEnumValue, CppItem, CppNamespace, CppOriginLocation,
-    CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
+    CppBaseSpecifier, CppClassField, CppConstantValue, CppDeprecation, CppEnumValue,
+    CppGlobalVariable, CppItem, CppMacroConstant, CppNamespace, CppOriginLocation, CppPath,
+    CppPathItem, CppTypeAlias, CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
 };
 use crate::cpp_function::{
     CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
 };
 use crate::cpp_operator::CppOperator;
+use crate::cpp_parser_report::{
+    CppParserDiagnostic, CppParserReport, CppParserSkipCategory, CppParserSkipReason,
+    CppSkippedDeclaration,
+};
 use crate::cpp_type::{
     CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind, CppSpecificNumericType,
-    CppSpecificNumericTypeKind, CppTemplateParameter, CppType,
+    CppSpecificNumericTypeKind, CppTemplateArgumentValue, CppTemplateParameter, CppType,
+};
+use crate::database::{
+    CppDocComment, CppDocCommentParam, CppParsedHeader, CppParserState, DatabaseClient, DocItem,
+    ItemId,
 };
-use crate::database::ItemId;
 use crate::processor::ProcessorData;
 use clang::diagnostic::{Diagnostic, Severity};
+use clang::documentation::{CommentChild, InlineCommandStyle};
 use clang::*;
 use itertools::Itertools;
-use log::{debug, trace, warn};
+use log::{debug, info, trace, warn};
+use once_cell::sync::Lazy;
 use regex::Regex;
 use ritual_common::env_var_names;
-use ritual_common::errors::{bail, err_msg, format_err, print_trace, Result, ResultExt};
+use ritual_common::errors::{bail, err_msg, format_err, print_trace, Error, Result, ResultExt};
 use ritual_common::file_utils::{
     canonicalize, copy_recursively, create_file, open_file, os_str_to_str, path_to_str,
     remove_dir_all, remove_file,
 };
 use ritual_common::target::{current_env, current_target, Env, LibraryTarget};
 use ritual_common::utils::MapIfOk;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs;
 use std::io::Write;
+use std::iter::once;
 use std::mem;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -77,6 +93,17 @@ struct CppParser<'b, 'a> {
     current_target_paths: Vec<PathBuf>,
     source_id: Option<ItemId>,
     output: CppParserOutput,
+    /// Namespace aliases and inline namespaces found in the headers
+    /// mapped to the paths they resolve to
+    namespace_aliases: HashMap<String, String>,
+    /// Declarations skipped because of errors and clang diagnostics
+    report: CppParserReport,
+    /// Headers that didn't change since the previous run and don't need to be parsed again
+    unchanged_headers: HashSet<PathBuf>,
+    /// Items produced by the parser, grouped by the header they were declared in
+    header_items: HashMap<PathBuf, Vec<ItemId>>,
+    /// Namespaces found in the parsed headers
+    namespaces: HashSet<CppPath>,
 }
 
 /// Print representation of `entity` and its children to the log.
@@ -109,6 +136,166 @@ fn get_origin_location(entity: Entity<'_>) -> Result<CppOriginLocation> {
     }
 }
 
+/// Returns information about deprecation of `entity`
+/// or `None` if it's not deprecated.
+fn get_deprecation(entity: Entity<'_>) -> Option<CppDeprecation> {
+    if entity.get_availability() != Availability::Deprecated {
+        return None;
+    }
+    Some(CppDeprecation {
+        message: get_deprecation_message(entity),
+    })
+}
+
+/// Attempts to find the message of the deprecation attribute of `entity`
+/// in its source code. `clang` doesn't include attributes in the entity's range,
+/// so the previous line and the rest of the last line are also examined
+/// up to the nearest declaration boundary.
+fn get_deprecation_message(entity: Entity<'_>) -> Option<String> {
+    let range = entity.get_range()?;
+    let start = range.get_start().get_file_location();
+    let end = range.get_end().get_file_location();
+    let file = start.file?;
+    let extended_range = SourceRange::new(
+        file.get_location(start.line.saturating_sub(1).max(1), 1),
+        file.get_location(end.line + 1, 1),
+    );
+    let mut before = Vec::new();
+    let mut tokens = Vec::new();
+    let mut is_after = false;
+    for token in extended_range.tokenize() {
+        let offset = token.get_location().get_file_location().offset;
+        let spelling = token.get_spelling();
+        if offset < start.offset {
+            before.push(spelling);
+        } else if offset <= end.offset {
+            tokens.push(spelling);
+        } else if !is_after {
+            if is_declaration_boundary(&spelling) {
+                is_after = true;
+            } else {
+                tokens.push(spelling);
+            }
+        }
+    }
+    let before_start = before
+        .iter()
+        .rposition(|s| is_declaration_boundary(s))
+        .map_or(0, |index| index + 1);
+    before.drain(..before_start);
+    before.extend(tokens);
+    parse_deprecation_message(&before)
+}
+
+fn is_declaration_boundary(token: &str) -> bool {
+    matches!(token, ";" | "{" | "}" | "," | ":")
+}
+
+/// Extracts the message from a deprecation attribute,
+/// e.g. `[[deprecated("message")]]` or `Q_DECL_DEPRECATED_X("message")`.
+fn parse_deprecation_message(tokens: &[String]) -> Option<String> {
+    tokens.iter().enumerate().find_map(|(index, token)| {
+        if !token.to_lowercase().contains("deprecated")
+            || tokens.get(index + 1).map(String::as_str) != Some("(")
+        {
+            return None;
+        }
+        let literals = tokens[index + 2..]
+            .iter()
+            .take_while(|t| t.len() >= 2 && t.starts_with('"') && t.ends_with('"'))
+            .map(|t| {
+                t[1..t.len() - 1]
+                    .replace("\\\"", "\"")
+                    .replace("\\\\", "\\")
+            })
+            .collect_vec();
+        if literals.is_empty() {
+            None
+        } else {
+            Some(literals.join(""))
+        }
+    })
+}
+
+#[test]
+fn parse_deprecation_message_works() {
+    fn check(code: &str, message: Option<&str>) {
+        let tokens = code.split(' ').map(|s| s.to_string()).collect_vec();
+        assert_eq!(
+            parse_deprecation_message(&tokens),
+            message.map(|s| s.to_string())
+        );
+    }
+    check("[ [ deprecated ( \"use_g\" ) ] ] void f ( )", Some("use_g"));
+    check("void f ( ) Q_DECL_DEPRECATED_X ( \"a\" \"b\" )", Some("ab"));
+    check("Q_DECL_DEPRECATED void f ( )", None);
+    check("[ [ deprecated ] ] void f ( )", None);
+}
+
+/// Extracts the default value expression from `tokens` following
+/// the `=` sign in an argument declaration.
+fn parse_default_value(tokens: &[String]) -> Option<String> {
+    let mut depth = 0;
+    let mut expression = Vec::new();
+    for token in tokens {
+        match token.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" if depth == 0 => break,
+            ")" | "]" | "}" => depth -= 1,
+            "," | ";" if depth == 0 => break,
+            _ => {}
+        }
+        expression.push(token.as_str());
+    }
+    if expression.is_empty() {
+        return None;
+    }
+    let mut code = String::new();
+    let mut previous: Option<&str> = None;
+    for token in expression {
+        let space = match previous {
+            None => false,
+            Some(previous) => {
+                let is_unary = matches!(token, "-" | "+") && previous == "=";
+                !matches!(
+                    token,
+                    ")" | "]" | "," | "." | "::" | "(" | "[" | "->" | "<" | ">"
+                ) && !matches!(
+                    previous,
+                    "(" | "[" | "." | "::" | "->" | "<" | "-" | "+" | "!" | "~" | "&"
+                ) && !is_unary
+            }
+        };
+        if space {
+            code.push(' ');
+        }
+        code.push_str(token);
+        previous = Some(token);
+    }
+    Some(code)
+}
+
+#[test]
+fn parse_default_value_works() {
+    fn check(code: &str, expected: Option<&str>) {
+        let tokens = code.split(' ').map(|s| s.to_string()).collect_vec();
+        assert_eq!(
+            parse_default_value(&tokens),
+            expected.map(|s| s.to_string())
+        );
+    }
+    check("42 )", Some("42"));
+    check("- 1 , int y )", Some("-1"));
+    check("Qt :: WindowFlags ( ) )", Some("Qt::WindowFlags()"));
+    check("QString ( \"a, b\" ) ) ;", Some("QString(\"a, b\")"));
+    check(
+        "std :: vector < int > { 1 , 2 } )",
+        Some("std::vector<int>{1, 2}"),
+    );
+    check("nullptr", Some("nullptr"));
+    check(")", None);
+}
+
 /// Extract template argument declarations from a class or method definition `entity`.
 fn get_template_arguments(entity: Entity<'_>) -> Option<Vec<CppType>> {
     let mut nested_level = 0;
@@ -129,7 +316,10 @@ fn get_template_arguments(entity: Entity<'_>) -> Option<Vec<CppType>> {
     let args = entity
         .get_children()
         .into_iter()
-        .filter(|c| c.get_kind() == EntityKind::TemplateTypeParameter)
+        .filter(|c| {
+            c.get_kind() == EntityKind::TemplateTypeParameter
+                || c.get_kind() == EntityKind::NonTypeTemplateParameter
+        })
         .enumerate()
         .map(|(i, c)| {
             CppType::TemplateParameter(CppTemplateParameter {
@@ -160,8 +350,205 @@ fn get_context_template_args(entity: Entity<'_>) -> Vec<CppType> {
     args
 }
 
+/// Returns true if `entity` is a type declaration without a name
+/// (e.g. `enum { A, B };` or `union { int i; float f; } value;`).
+fn is_anonymous(entity: Entity<'_>) -> bool {
+    match entity.get_kind() {
+        EntityKind::StructDecl
+        | EntityKind::ClassDecl
+        | EntityKind::UnionDecl
+        | EntityKind::EnumDecl => {}
+        _ => return false,
+    }
+    // newer libclang versions report names like `(unnamed struct at file.h:1:1)`
+    entity.get_name().map_or(true, |name| name.starts_with('('))
+}
+
+/// Returns the name given to an anonymous type by a typedef
+/// (e.g. `Point` in `typedef struct { int x; } Point;`).
+fn anonymous_typedef_name(entity: Entity<'_>) -> Option<String> {
+    // clang uses the typedef name when printing such a type
+    let display_name = entity.get_type()?.get_display_name();
+    let name = display_name.rsplit("::").next()?;
+    let name = ["struct ", "class ", "union ", "enum "]
+        .iter()
+        .fold(name, |name, keyword| name.trim_start_matches(keyword));
+    let is_identifier = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+/// Returns true if `entity` is a class, a struct or a union.
+fn is_class_declaration(entity: Entity<'_>) -> bool {
+    matches!(
+        entity.get_kind(),
+        EntityKind::ClassDecl
+            | EntityKind::ClassTemplate
+            | EntityKind::StructDecl
+            | EntityKind::UnionDecl
+            | EntityKind::ClassTemplatePartialSpecialization
+    )
+}
+
+/// Returns the named class member declared with the anonymous struct or union type `entity`
+/// (e.g. `u` in `struct S { union { int i; float f; } u; };`).
+fn anonymous_type_member(entity: Entity<'_>) -> Option<Entity<'_>> {
+    if !is_anonymous(entity)
+        || entity.get_kind() == EntityKind::EnumDecl
+        || anonymous_typedef_name(entity).is_some()
+    {
+        return None;
+    }
+    let parent = entity.get_semantic_parent()?;
+    if !is_class_declaration(parent) {
+        return None;
+    }
+    parent.get_children().into_iter().find(|child| {
+        (child.get_kind() == EntityKind::FieldDecl || child.get_kind() == EntityKind::VarDecl)
+            && child.get_name().is_some()
+            && child
+                .get_type()
+                .and_then(|t| t.get_canonical_type().get_declaration())
+                == Some(entity)
+    })
+}
+
+/// Returns path of the name given to the anonymous type of the class member `member`.
+/// The type is referred to by a typedef generated by ritual in the namespace
+/// enclosing the class. Its name consists of the names of the enclosing classes
+/// and the member (e.g. `ns::Outer_S_u` for the member `u` of `ns::Outer::S`).
+fn anonymous_member_type_path(member: Entity<'_>) -> Result<CppPath> {
+    let member_name = member
+        .get_name()
+        .ok_or_else(|| err_msg("failed to get member name"))?;
+    let class = member
+        .get_semantic_parent()
+        .ok_or_else(|| err_msg("failed to get parent of member"))?;
+    if !get_context_template_args(class).is_empty() {
+        return Err(skip_error(
+            CppParserSkipCategory::Anonymous,
+            "anonymous types in templates are not supported",
+        ));
+    }
+    let mut class_count = 1;
+    let mut current_class = class;
+    // path of an anonymous member type doesn't contain its enclosing classes
+    while anonymous_type_member(current_class).is_none() {
+        match current_class.get_semantic_parent() {
+            Some(parent) if is_class_declaration(parent) => {
+                current_class = parent;
+                class_count += 1;
+            }
+            _ => break,
+        }
+    }
+    let mut items = get_path(class)?.into_items();
+    let class_items = items.split_off(items.len() - class_count);
+    if class_items
+        .iter()
+        .any(|item| item.template_arguments.is_some())
+    {
+        return Err(skip_error(
+            CppParserSkipCategory::Anonymous,
+            "anonymous types in templates are not supported",
+        ));
+    }
+    let name = class_items
+        .iter()
+        .map(|item| item.name.as_str())
+        .chain(once(member_name.as_str()))
+        .join("_");
+    items.push(CppPathItem::from_good_str(&name));
+    Ok(CppPath::from_items(items))
+}
+
+/// Returns C++ code referring to the class member `member` in an unevaluated context
+/// (e.g. `S::u` or `S::u.inner` if `u` has an anonymous type).
+fn anonymous_member_expression(member: Entity<'_>) -> Result<String> {
+    let member_name = member
+        .get_name()
+        .ok_or_else(|| err_msg("failed to get member name"))?;
+    let class = member
+        .get_semantic_parent()
+        .ok_or_else(|| err_msg("failed to get parent of member"))?;
+    if let Some(class_member) = anonymous_type_member(class) {
+        Ok(format!(
+            "{}.{}",
+            anonymous_member_expression(class_member)?,
+            member_name
+        ))
+    } else {
+        Ok(format!(
+            "{}::{}",
+            get_path(class)?.to_cpp_code()?,
+            member_name
+        ))
+    }
+}
+
+/// Returns the name of an anonymous type `entity`. The name is given by a typedef
+/// (see `anonymous_typedef_name`) or generated for a type of a class member
+/// (see `anonymous_member_type_path`).
+fn anonymous_type_name(entity: Entity<'_>) -> Option<String> {
+    if let Some(name) = anonymous_typedef_name(entity) {
+        return Some(name);
+    }
+    let member = anonymous_type_member(entity)?;
+    let path = anonymous_member_type_path(member).ok()?;
+    Some(path.last().name.clone())
+}
+
+/// Returns true if `entity` is an anonymous struct or union member
+/// without a declarator (e.g. `union { int i; float f; };`).
+/// Members of such a type are accessible as members of the enclosing class.
+fn is_anonymous_member(entity: Entity<'_>) -> bool {
+    is_anonymous(entity)
+        && entity.get_kind() != EntityKind::EnumDecl
+        && anonymous_typedef_name(entity).is_none()
+        && anonymous_type_member(entity).is_none()
+}
+
+/// Returns the path of the type alias used to spell `type1`, ignoring
+/// pointers, references and `const` (e.g. `QWidgetList` for `const QWidgetList&`).
+fn get_type_alias_path(type1: Type<'_>) -> Option<CppPath> {
+    let mut type1 = type1;
+    loop {
+        type1 = match type1.get_kind() {
+            TypeKind::Pointer | TypeKind::LValueReference | TypeKind::RValueReference => {
+                type1.get_pointee_type()?
+            }
+            TypeKind::Elaborated => type1.get_elaborated_type()?,
+            TypeKind::Typedef => break,
+            _ => return None,
+        };
+    }
+    let declaration = type1.get_declaration()?;
+    if !get_context_template_args(declaration).is_empty() {
+        return None;
+    }
+    get_path(declaration).ok()
+}
+
+/// Creates an error that makes the parser skip the declaration
+/// and puts it in `category` of the parser report.
+fn skip_error(category: CppParserSkipCategory, message: impl Into<String>) -> Error {
+    CppParserSkipReason::new(category, message).into()
+}
+
 fn get_path_item(entity: Entity<'_>) -> Result<CppPathItem> {
-    let name = entity.get_name().ok_or_else(|| err_msg("Anonymous type"))?;
+    let name = if is_anonymous(entity) {
+        anonymous_type_name(entity)
+            .ok_or_else(|| skip_error(CppParserSkipCategory::Anonymous, "Anonymous type"))?
+    } else {
+        entity
+            .get_name()
+            .ok_or_else(|| skip_error(CppParserSkipCategory::Anonymous, "Anonymous type"))?
+    };
     let template_arguments = get_template_arguments(entity);
     Ok(CppPathItem {
         name,
@@ -171,6 +558,9 @@ fn get_path_item(entity: Entity<'_>) -> Result<CppPathItem> {
 
 /// Returns fully qualified name of `entity`.
 fn get_path(entity: Entity<'_>) -> Result<CppPath> {
+    if let Some(member) = anonymous_type_member(entity) {
+        return anonymous_member_type_path(member);
+    }
     let mut current_entity = entity;
     let mut parts = vec![get_path_item(entity)?];
     loop {
@@ -180,12 +570,25 @@ fn get_path(entity: Entity<'_>) -> Result<CppPath> {
 
         match p.get_kind() {
             EntityKind::TranslationUnit => break,
+            EntityKind::Namespace if is_inline_namespace(p) => {
+                // members of inline namespaces are accessible through the enclosing namespace,
+                // so the inline namespace is omitted to keep the path independent of
+                // the library's version
+                current_entity = p;
+            }
             EntityKind::ClassDecl
             | EntityKind::ClassTemplate
             | EntityKind::StructDecl
+            | EntityKind::UnionDecl
             | EntityKind::Namespace
             | EntityKind::EnumDecl
             | EntityKind::ClassTemplatePartialSpecialization => {
+                if anonymous_type_member(p).is_some() {
+                    // the path of the anonymous type doesn't contain its enclosing classes
+                    let mut path_items = get_path(p)?.into_items();
+                    path_items.extend(parts);
+                    return Ok(CppPath::from_items(path_items));
+                }
                 parts.insert(0, get_path_item(p)?);
                 current_entity = p;
             }
@@ -195,13 +598,82 @@ fn get_path(entity: Entity<'_>) -> Result<CppPath> {
             _ => bail!("get_full_name: unexpected parent kind: {:?}", p),
         }
     }
-    if parts.len() > 1 && parts[0].name == "std" && parts[1].name == "__cxx11" {
-        // this is an inline namespace (not portable)
-        parts.remove(1);
-    }
     Ok(CppPath::from_items(parts))
 }
 
+/// Returns true if `entity` is an inline namespace (e.g. `inline namespace v1 {}`).
+/// Only the first declaration of a namespace has to be marked `inline`,
+/// so the canonical declaration is examined.
+fn is_inline_namespace(entity: Entity<'_>) -> bool {
+    if entity.get_kind() != EntityKind::Namespace {
+        return false;
+    }
+    let range = match entity.get_canonical_entity().get_range() {
+        Some(range) => range,
+        None => return false,
+    };
+    // the spelling location is used because the declaration may come from
+    // a macro expansion (e.g. `_LIBCPP_BEGIN_NAMESPACE_STD`)
+    let start = range.get_start().get_spelling_location();
+    let file = match start.file {
+        Some(file) => file,
+        None => return false,
+    };
+    let first_line = SourceRange::new(
+        file.get_location(start.line, start.column),
+        file.get_location(start.line + 1, 1),
+    );
+    first_line
+        .tokenize()
+        .first()
+        .map_or(false, |token| token.get_spelling() == "inline")
+}
+
+/// Matches the qualifying part of a name, e.g. `std::filesystem::` in `std::filesystem::path`.
+static QUALIFIER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:[A-Za-z_]\w*::)+").unwrap());
+
+/// Replaces namespace aliases and inline namespaces in qualified names within `name`
+/// according to `aliases`, e.g. `fs::path` becomes `std::filesystem::path`
+/// if `namespace fs = std::filesystem;` is declared.
+fn resolve_namespace_aliases(name: &str, aliases: &HashMap<String, String>) -> String {
+    if aliases.is_empty() {
+        return name.to_string();
+    }
+    QUALIFIER_REGEX
+        .replace_all(name, |captures: &regex::Captures<'_>| {
+            let parts = captures[0].trim_end_matches("::").split("::").collect_vec();
+            for len in (1..=parts.len()).rev() {
+                if let Some(target) = aliases.get(&parts[..len].join("::")) {
+                    return target
+                        .split("::")
+                        .filter(|part| !part.is_empty())
+                        .chain(parts[len..].iter().copied())
+                        .map(|part| format!("{}::", part))
+                        .join("");
+                }
+            }
+            captures[0].to_string()
+        })
+        .into_owned()
+}
+
+#[test]
+fn resolve_namespace_aliases_works() {
+    let mut aliases = HashMap::new();
+    aliases.insert("fs".to_string(), "std::filesystem".to_string());
+    aliases.insert("lib::v2".to_string(), "lib".to_string());
+    aliases.insert("v1".to_string(), String::new());
+    let resolve = |name: &str| resolve_namespace_aliases(name, &aliases);
+    assert_eq!(resolve("fs::path"), "std::filesystem::path");
+    assert_eq!(resolve("QList<fs::path>"), "QList<std::filesystem::path>");
+    assert_eq!(resolve("lib::v2::Box<T>"), "lib::Box<T>");
+    assert_eq!(resolve("lib::v2::a::B"), "lib::a::B");
+    assert_eq!(resolve("v1::Item"), "Item");
+    assert_eq!(resolve("myfs::path"), "myfs::path");
+    assert_eq!(resolve("lib::v3::Box"), "lib::v3::Box");
+    assert_eq!(resolve("int"), "int");
+}
+
 fn get_full_name_display(entity: Entity<'_>) -> String {
     match get_path(entity) {
         Ok(name) => name.to_cpp_pseudo_code(),
@@ -209,6 +681,211 @@ fn get_full_name_display(entity: Entity<'_>) -> String {
     }
 }
 
+/// Converts inline content of a documentation comment to Markdown.
+fn doc_comment_inline_text(children: &[CommentChild]) -> String {
+    let mut text = String::new();
+    for child in children {
+        match child {
+            CommentChild::Text(value) => text.push_str(value),
+            CommentChild::InlineCommand(command) => {
+                let arguments = command.arguments.join(" ");
+                text += &match command.style {
+                    Some(InlineCommandStyle::Bold) => format!(" **{}**", arguments),
+                    Some(InlineCommandStyle::Emphasized) => format!(" *{}*", arguments),
+                    Some(InlineCommandStyle::Monospace) => format!(" `{}`", arguments),
+                    None => format!(" {}", arguments),
+                };
+            }
+            CommentChild::HtmlStartTag(tag) => {
+                text += &format!("<{}", tag.name);
+                for (name, value) in &tag.attributes {
+                    text += &format!(" {}=\"{}\"", name, value);
+                }
+                text.push_str(if tag.closing { "/>" } else { ">" });
+            }
+            CommentChild::HtmlEndTag(name) => text += &format!("</{}>", name),
+            CommentChild::Paragraph(children) => {
+                text += &format!(" {}", doc_comment_inline_text(children));
+            }
+            _ => {}
+        }
+    }
+    text.split_whitespace().join(" ")
+}
+
+/// Returns the heading used for a Doxygen block command `command`
+/// that doesn't have a dedicated place in `CppDocComment`.
+fn doc_comment_block_caption(command: &str) -> String {
+    match command {
+        "see" | "sa" => "See also".into(),
+        "pre" => "Precondition".into(),
+        "post" => "Postcondition".into(),
+        "throw" | "throws" | "exception" => "Throws".into(),
+        _ => {
+            let mut chars = command.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+/// Converts a code block of a documentation comment to Markdown.
+fn doc_comment_code_block(lines: &[String]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let code = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .join("\n");
+    format!("```cpp\n{}\n```", code.trim_matches('\n'))
+}
+
+/// Converts a documentation comment (e.g. a Doxygen comment) parsed by `clang`
+/// to `CppDocComment`. Returns `None` if the comment has no content.
+fn parse_doc_comment(children: &[CommentChild]) -> Option<CppDocComment> {
+    let mut comment = CppDocComment {
+        brief: None,
+        paragraphs: Vec::new(),
+        params: Vec::new(),
+        returns: None,
+    };
+    for child in children {
+        match child {
+            CommentChild::Paragraph(children) => {
+                let text = doc_comment_inline_text(children);
+                if !text.is_empty() {
+                    comment.paragraphs.push(text);
+                }
+            }
+            CommentChild::BlockCommand(command) => {
+                let text = doc_comment_inline_text(&command.children);
+                if text.is_empty() {
+                    continue;
+                }
+                match command.command.as_str() {
+                    "brief" | "short" => comment.brief = Some(text),
+                    "return" | "returns" | "result" => comment.returns = Some(text),
+                    other => comment.paragraphs.push(format!(
+                        "**{}:** {}",
+                        doc_comment_block_caption(other),
+                        text
+                    )),
+                }
+            }
+            CommentChild::ParamCommand(command) => {
+                comment.params.push(CppDocCommentParam {
+                    name: command.parameter.clone(),
+                    text: doc_comment_inline_text(&command.children),
+                });
+            }
+            CommentChild::TParamCommand(command) => {
+                comment.paragraphs.push(format!(
+                    "**Template parameter** `{}`: {}",
+                    command.parameter,
+                    doc_comment_inline_text(&command.children)
+                ));
+            }
+            CommentChild::VerbatimCommand(lines) => {
+                comment.paragraphs.push(doc_comment_code_block(lines));
+            }
+            _ => {}
+        }
+    }
+    if comment.brief.is_none()
+        && comment.paragraphs.is_empty()
+        && comment.params.is_empty()
+        && comment.returns.is_none()
+    {
+        return None;
+    }
+    Some(comment)
+}
+
+#[test]
+fn parse_doc_comment_works() {
+    fn text(value: &str) -> CommentChild {
+        CommentChild::Text(value.to_string())
+    }
+    fn paragraph(children: Vec<CommentChild>) -> CommentChild {
+        CommentChild::Paragraph(children)
+    }
+
+    assert_eq!(parse_doc_comment(&[]), None);
+    assert_eq!(parse_doc_comment(&[paragraph(vec![text(" ")])]), None);
+
+    let comment = parse_doc_comment(&[
+        CommentChild::BlockCommand(clang::documentation::BlockCommand {
+            command: "brief".into(),
+            arguments: Vec::new(),
+            children: vec![paragraph(vec![text(" Resizes the"), text(" buffer.")])],
+        }),
+        paragraph(vec![
+            text(" Use"),
+            CommentChild::InlineCommand(clang::documentation::InlineCommand {
+                command: "c".into(),
+                arguments: vec!["capacity()".into()],
+                style: Some(InlineCommandStyle::Monospace),
+            }),
+            text(" to check the "),
+            CommentChild::HtmlStartTag(clang::documentation::HtmlStartTag {
+                name: "b".into(),
+                attributes: Vec::new(),
+                closing: false,
+            }),
+            text("current"),
+            CommentChild::HtmlEndTag("b".into()),
+            text(" size."),
+        ]),
+        CommentChild::ParamCommand(clang::documentation::ParamCommand {
+            index: Some(0),
+            parameter: "size".into(),
+            direction: None,
+            children: vec![paragraph(vec![text(" New size.")])],
+        }),
+        CommentChild::BlockCommand(clang::documentation::BlockCommand {
+            command: "returns".into(),
+            arguments: Vec::new(),
+            children: vec![paragraph(vec![text(" True on success.")])],
+        }),
+        CommentChild::BlockCommand(clang::documentation::BlockCommand {
+            command: "sa".into(),
+            arguments: Vec::new(),
+            children: vec![paragraph(vec![text(" reserve()")])],
+        }),
+        CommentChild::VerbatimCommand(vec![
+            "  buffer.resize(4);".into(),
+            "  if (ok) {".into(),
+            "    return;".into(),
+            "  }".into(),
+        ]),
+    ])
+    .unwrap();
+
+    assert_eq!(comment.brief.as_deref(), Some("Resizes the buffer."));
+    assert_eq!(
+        comment.paragraphs,
+        vec![
+            "Use `capacity()` to check the <b>current</b> size.".to_string(),
+            "**See also:** reserve()".to_string(),
+            "```cpp\nbuffer.resize(4);\nif (ok) {\n  return;\n}\n```".to_string(),
+        ]
+    );
+    assert_eq!(
+        comment.params,
+        vec![CppDocCommentParam {
+            name: "size".into(),
+            text: "New size.".into(),
+        }]
+    );
+    assert_eq!(comment.returns.as_deref(), Some("True on success."));
+}
+
 #[cfg(test)]
 fn init_clang() -> Result<Clang> {
     for _ in 0..12000 {
@@ -335,9 +1012,266 @@ fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
     result
 }
 
-/// Runs the parser on specified data.
+/// 64-bit FNV-1a hash. Unlike `DefaultHasher`, its output doesn't depend
+/// on the Rust version, so it can be stored in the database.
+#[derive(Debug, Clone, Copy)]
+struct StableHash(u64);
+
+impl StableHash {
+    fn new() -> Self {
+        StableHash(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn add_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Adds `text` followed by a separator, so that sequences
+    /// of strings are hashed unambiguously.
+    fn add_str(&mut self, text: &str) {
+        self.add_bytes(text.as_bytes());
+        self.add_bytes(&[0xff]);
+    }
+
+    fn add_path(&mut self, path: &Path) {
+        self.add_str(&path.to_string_lossy());
+    }
+
+    fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+#[test]
+fn stable_hash_works() {
+    let hash = |texts: &[&str]| {
+        let mut hash = StableHash::new();
+        for text in texts {
+            hash.add_str(text);
+        }
+        hash.finish()
+    };
+    assert_eq!(StableHash::new().finish(), 0xcbf2_9ce4_8422_2325);
+    let mut hash_a = StableHash::new();
+    hash_a.add_bytes(b"a");
+    assert_eq!(hash_a.finish(), 0xaf63_dc4c_8601_ec8c);
+    assert_eq!(hash(&["ab", "c"]), hash(&["ab", "c"]));
+    assert_ne!(hash(&["ab", "c"]), hash(&["a", "bc"]));
+}
+
+/// Computes a hash of the parser configuration. Results of the previous run
+/// are discarded if any of the hashed values has changed.
+fn config_hash(config: &Config) -> Result<u64> {
+    let mut hash = StableHash::new();
+    hash.add_str(env!("CARGO_PKG_VERSION"));
+    hash.add_str(&get_version());
+    // macros defined in the global header are visible in the library's headers
+    hash.add_str(include_str!("../templates/c_lib/global.h"));
+    for directive in all_include_directives(config)? {
+        hash.add_path(&directive);
+    }
+    for path in config.target_include_paths() {
+        hash.add_path(path);
+    }
+    // defines are passed to clang as arguments
+    for argument in config.cpp_parser_arguments() {
+        hash.add_str(argument);
+    }
+    hash.add_str(config.cpp_lib_version().unwrap_or_default());
+    hash.add_str(&config.parse_doc_comments().to_string());
+    let mut cpp_build_paths = config.cpp_build_paths().clone();
+    cpp_build_paths.apply_env();
+    for path in cpp_build_paths.include_paths() {
+        hash.add_path(path);
+    }
+    for path in cpp_build_paths.framework_paths() {
+        hash.add_path(path);
+    }
+    hash.add_str(&::std::env::var(env_var_names::CLANG_SYSTEM_INCLUDE_PATH).unwrap_or_default());
+    Ok(hash.finish())
+}
+
+/// Returns files directly included by each file of the translation unit.
+/// Relies on the detailed preprocessing record enabled in `run_clang`.
+fn direct_includes(translation_unit: Entity<'_>) -> HashMap<PathBuf, BTreeSet<PathBuf>> {
+    let mut includes = HashMap::<_, BTreeSet<_>>::new();
+    for entity in translation_unit.get_children() {
+        if entity.get_kind() != EntityKind::InclusionDirective {
+            continue;
+        }
+        let included_file = entity
+            .get_file()
+            .and_then(|file| canonicalize(file.get_path()).ok());
+        let including_file = entity
+            .get_location()
+            .and_then(|location| location.get_file_location().file)
+            .and_then(|file| canonicalize(file.get_path()).ok());
+        if let (Some(including_file), Some(included_file)) = (including_file, included_file) {
+            includes
+                .entry(including_file)
+                .or_default()
+                .insert(included_file);
+        }
+    }
+    includes
+}
+
+/// Returns all files included by `header`, directly or indirectly.
+fn header_dependencies(
+    header: &Path,
+    includes: &HashMap<PathBuf, BTreeSet<PathBuf>>,
+) -> Vec<PathBuf> {
+    let mut dependencies = BTreeSet::new();
+    let mut queue = vec![header.to_path_buf()];
+    while let Some(path) = queue.pop() {
+        for included_file in includes.get(&path).into_iter().flatten() {
+            if included_file != header && dependencies.insert(included_file.clone()) {
+                queue.push(included_file.clone());
+            }
+        }
+    }
+    dependencies.into_iter().collect()
+}
+
+/// Computes hashes of header contents, reading each file only once.
+#[derive(Debug, Default)]
+struct HeaderHasher {
+    file_hashes: HashMap<PathBuf, u64>,
+}
+
+impl HeaderHasher {
+    fn file_hash(&mut self, path: &Path) -> Result<u64> {
+        if let Some(hash) = self.file_hashes.get(path) {
+            return Ok(*hash);
+        }
+        let mut hash = StableHash::new();
+        hash.add_bytes(
+            &fs::read(path).with_context(|_| format!("failed to read {}", path.display()))?,
+        );
+        let hash = hash.finish();
+        self.file_hashes.insert(path.to_path_buf(), hash);
+        Ok(hash)
+    }
+
+    /// Computes a hash of the content of `header` and the files it includes.
+    fn header_hash(&mut self, header: &Path, dependencies: &[PathBuf]) -> Result<u64> {
+        let mut hash = StableHash::new();
+        for path in once(header).chain(dependencies.iter().map(PathBuf::as_path)) {
+            hash.add_path(path);
+            hash.add_bytes(&self.file_hash(path)?.to_le_bytes());
+        }
+        Ok(hash.finish())
+    }
+}
+
+/// Compares the headers recorded in `state` with the current content of the headers
+/// and the files they include. Outdated headers are removed from `state`.
+/// Returns the set of unchanged headers and IDs of the items that were declared
+/// in the outdated headers.
+fn check_parsed_headers(
+    state: &mut CppParserState,
+    hasher: &mut HeaderHasher,
+) -> (HashSet<PathBuf>, HashSet<ItemId>) {
+    let mut unchanged_headers = HashSet::new();
+    let mut outdated_items = HashSet::new();
+    for (path, header) in mem::take(&mut state.headers) {
+        if hasher.header_hash(&path, &header.dependencies).ok() == Some(header.content_hash) {
+            unchanged_headers.insert(path.clone());
+            state.headers.insert(path, header);
+        } else {
+            debug!("header changed: {}", path.display());
+            outdated_items.extend(header.items);
+        }
+    }
+    // an item may be declared in multiple headers
+    for header in state.headers.values() {
+        for id in &header.items {
+            outdated_items.remove(id);
+        }
+    }
+    (unchanged_headers, outdated_items)
+}
+
+/// Deletes `outdated_items` together with everything generated from C++ items
+/// that refer to their types. Such C++ items themselves are kept if they come
+/// from unchanged headers, so FFI and Rust items are generated for them again.
+fn delete_outdated_items(db: &mut DatabaseClient, outdated_items: &HashSet<ItemId>) {
+    let outdated_paths = db
+        .cpp_items()
+        .filter(|item| outdated_items.contains(&item.id))
+        .filter_map(|item| item.item.path().cloned())
+        .collect::<HashSet<_>>();
+    db.delete_items(|item| outdated_items.contains(&item.id));
+
+    let mut affected_items = HashSet::new();
+    let mut derived_items = HashSet::new();
+    for item in db.cpp_items() {
+        if item
+            .item
+            .all_involved_types()
+            .iter()
+            .any(|t| t.refers_to_any(&outdated_paths))
+        {
+            if item.source_id.is_some() {
+                derived_items.insert(item.id.clone());
+            }
+            affected_items.insert(item.id);
+        }
+    }
+    if affected_items.is_empty() {
+        return;
+    }
+    debug!(
+        "invalidating items generated from {} C++ items",
+        affected_items.len()
+    );
+    // items derived from other items (e.g. template instantiations) are deleted,
+    // parsed items only lose their FFI and Rust items
+    db.delete_items(|item| {
+        derived_items.contains(&item.id)
+            || (item.item.is_ffi_item() || item.item.is_rust_item())
+                && item
+                    .source_id
+                    .as_ref()
+                    .map_or(false, |id| affected_items.contains(id))
+    });
+}
+
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     debug!("clang version: {}", get_version());
+
+    let config_hash = config_hash(&data.config)?;
+    let mut state = data.db.cpp_parser_state().clone();
+    if state.config_hash != config_hash {
+        if !state.headers.is_empty() {
+            info!("C++ parser configuration changed, parsing all headers");
+        }
+        let outdated_items = state
+            .headers
+            .values()
+            .flat_map(|header| header.items.iter().cloned())
+            .collect::<HashSet<_>>();
+        delete_outdated_items(data.db, &outdated_items);
+        state = CppParserState {
+            config_hash,
+            headers: BTreeMap::new(),
+        };
+    }
+    let mut hasher = HeaderHasher::default();
+    let (unchanged_headers, outdated_items) = check_parsed_headers(&mut state, &mut hasher);
+    if !outdated_items.is_empty() {
+        delete_outdated_items(data.db, &outdated_items);
+    }
+    if !unchanged_headers.is_empty() {
+        info!(
+            "Skipping {} headers that didn't change since the previous run",
+            unchanged_headers.len()
+        );
+    }
+
     debug!("Initializing clang");
     let mut parser = CppParser {
         current_target_paths: data
@@ -348,25 +1282,121 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
         source_id: None,
         data,
         output: Default::default(),
+        namespace_aliases: HashMap::new(),
+        report: Default::default(),
+        unchanged_headers,
+        header_items: HashMap::new(),
+        namespaces: HashSet::new(),
     };
     parser
         .current_target_paths
         .push(canonicalize(parser.data.workspace.tmp_path())?.join("extra"));
-    run_clang(
+    let includes = run_clang(
         &parser.data.config,
         &parser.data.workspace.tmp_path(),
         None,
-        |translation_unit| parser.parse(translation_unit),
+        |translation_unit| {
+            parser.parse(translation_unit)?;
+            Ok(direct_includes(translation_unit))
+        },
     )?;
 
+    // namespaces are visited even in unchanged headers, so a namespace
+    // that wasn't found anymore has no declarations left
+    let namespaces = mem::take(&mut parser.namespaces);
+    parser.data.db.delete_items(|item| {
+        item.source_id.is_none()
+            && match item.item.as_cpp_item() {
+                Some(CppItem::Namespace(namespace)) => !namespaces.contains(&namespace.path),
+                _ => false,
+            }
+    });
+
+    let mut header_skipped_declarations = HashMap::<PathBuf, Vec<CppSkippedDeclaration>>::new();
+    for declaration in &parser.report.skipped_declarations {
+        if let Some(path) = declaration
+            .location
+            .as_ref()
+            .and_then(|location| canonicalize(&location.include_file_path).ok())
+        {
+            header_skipped_declarations
+                .entry(path)
+                .or_default()
+                .push(declaration.clone());
+        }
+    }
+    // only unchanged headers are in the state at this point
+    for header in state.headers.values() {
+        parser
+            .report
+            .skipped_declarations
+            .extend(header.skipped_declarations.iter().cloned());
+    }
+
+    let mut header_items = mem::take(&mut parser.header_items);
+    let paths = header_items
+        .keys()
+        .chain(header_skipped_declarations.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    for path in paths {
+        let dependencies = header_dependencies(&path, &includes);
+        let content_hash = hasher.header_hash(&path, &dependencies)?;
+        let items = header_items.remove(&path).unwrap_or_default();
+        let skipped_declarations = header_skipped_declarations
+            .remove(&path)
+            .unwrap_or_default();
+        state.headers.insert(
+            path,
+            CppParsedHeader {
+                content_hash,
+                dependencies,
+                items,
+                skipped_declarations,
+            },
+        );
+    }
+    parser.data.db.set_cpp_parser_state(state);
+
+    let report_name = format!(
+        "cpp_parser_report_{}",
+        parser.data.config.crate_properties().name()
+    );
+    parser
+        .report
+        .save(&parser.data.workspace.log_path(), &report_name)?;
+    info!(
+        "C++ parser skipped {} declarations (see {}.md in the log directory)",
+        parser.report.skipped_declarations.len(),
+        report_name
+    );
     Ok(())
 }
 
+/// Converts a clang diagnostic to its report representation.
+fn parser_diagnostic(diagnostic: &Diagnostic<'_>) -> CppParserDiagnostic {
+    let (include_file_path, line, column) = diagnostic.get_location().get_presumed_location();
+    CppParserDiagnostic {
+        severity: format!("{:?}", diagnostic.get_severity()).to_lowercase(),
+        message: diagnostic.get_text(),
+        location: if include_file_path.is_empty() {
+            None
+        } else {
+            Some(CppOriginLocation {
+                include_file_path,
+                line,
+                column,
+            })
+        },
+    }
+}
+
 pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
     let current_target = LibraryTarget {
         cpp_library_version: data.config.cpp_lib_version().map(ToString::to_string),
         target: current_target(),
     };
+    let type_aliases = anonymous_type_aliases(data.db)?;
     for ffi_item_id in data.db.ffi_item_ids().collect_vec() {
         let ffi_item = data.db.ffi_item(&ffi_item_id)?;
         if !ffi_item.item.is_source_item() {
@@ -379,12 +1409,21 @@ pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
         {
             continue;
         }
-        let code = ffi_item.item.source_item_cpp_code(data.db)?;
+        let code = format!(
+            "{}{}",
+            type_aliases,
+            ffi_item.item.source_item_cpp_code(data.db)?
+        );
         let mut parser = CppParser {
             current_target_paths: vec![canonicalize(data.workspace.tmp_path())?.join("1.cpp")],
             source_id: Some(ffi_item_id),
             data,
             output: Default::default(),
+            namespace_aliases: HashMap::new(),
+            report: Default::default(),
+            unchanged_headers: HashSet::new(),
+            header_items: HashMap::new(),
+            namespaces: HashSet::new(),
         };
         run_clang(
             &parser.data.config,
@@ -400,17 +1439,63 @@ pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
 }
 
 impl CppParser<'_, '_> {
+    /// Adds `item` parsed from `entity` to the database.
+    /// The documentation comment of `entity` is added as well, if any.
     fn add_output(
         &mut self,
         include_file: String,
-        origin_location: CppOriginLocation,
+        entity: Entity<'_>,
         item: CppItem,
     ) -> Result<()> {
-        if let Some(id) = self.data.add_cpp_item(self.source_id.clone(), item)? {
-            self.output.0.push(CppParserOutputItem {
-                id,
-                include_file,
-                origin_location,
+        let origin_location = get_origin_location(entity)?;
+        if let CppItem::Namespace(namespace) = &item {
+            self.namespaces.insert(namespace.path.clone());
+        }
+        // namespaces can be declared in multiple headers, so they are never
+        // deleted when a header changes; items parsed from generated code are not tracked
+        let header_path = if matches!(item, CppItem::Namespace(_)) || self.source_id.is_some() {
+            None
+        } else {
+            self.entity_include_path(entity).ok()
+        };
+        let added_id = self
+            .data
+            .add_cpp_item(self.source_id.clone(), item.clone())?;
+        if let Some(header_path) = header_path {
+            let id = added_id.clone().or_else(|| {
+                self.data
+                    .db
+                    .find_same_cpp_item(&item)
+                    .filter(|i| i.source_id.is_none())
+                    .map(|i| i.id)
+            });
+            if let Some(id) = id {
+                self.header_items.entry(header_path).or_default().push(id);
+            }
+        }
+        if let Some(id) = added_id {
+            if self.data.config.parse_doc_comments() {
+                if let Some(comment) = entity
+                    .get_parsed_comment()
+                    .and_then(|comment| parse_doc_comment(&comment.get_children()))
+                {
+                    self.data.db.add_doc_item(
+                        id.clone(),
+                        DocItem {
+                            anchor: None,
+                            html: String::new(),
+                            mismatched_declaration: None,
+                            url: None,
+                            cross_references: Vec::new(),
+                            comment: Some(comment),
+                        },
+                    );
+                }
+            }
+            self.output.0.push(CppParserOutputItem {
+                id,
+                include_file,
+                origin_location,
             });
         }
         Ok(())
@@ -429,6 +1514,55 @@ impl CppParser<'_, '_> {
             .find(|i| f(i))
     }
 
+    /// Parses text of a non-type template argument, e.g. `4` in `std::array<int, 4>`.
+    /// Integer literals, enumerators (optionally in the `(Enum)1` form produced by `clang`)
+    /// and template parameters of the surrounding context are supported.
+    fn parse_template_argument_value(
+        &self,
+        text: &str,
+        context_template_args: &[CppType],
+    ) -> Result<CppType> {
+        let text = text.trim();
+        if let Some(arg) = context_template_args
+            .iter()
+            .find(|t| t.to_cpp_pseudo_code() == text)
+        {
+            return Ok(arg.clone());
+        }
+        if let Some(value) = parse_integer_literal(text) {
+            return Ok(CppType::TemplateArgumentValue(
+                CppTemplateArgumentValue::Integer(value),
+            ));
+        }
+        let enum_value_path = if let Some(matches) = ENUM_VALUE_CAST_REGEX.captures(text) {
+            let enum_path = CppPath::from_str(&matches[1])?;
+            let value = parse_integer_literal(&matches[2])
+                .ok_or_else(|| format_err!("invalid enum value: {}", text))?;
+            self.data
+                .db
+                .enum_values(&enum_path)
+                .find(|v| v.item.value == value)
+                .map(|v| v.item.path.clone())
+        } else {
+            CppPath::from_str(text).ok().and_then(|path| {
+                self.data
+                    .db
+                    .find_enum_values(&path)
+                    .next()
+                    .map(|v| v.item.path.clone())
+            })
+        };
+        let enum_value_path = enum_value_path.ok_or_else(|| {
+            skip_error(
+                CppParserSkipCategory::TemplateArgument,
+                format!("unsupported template argument value: {}", text),
+            )
+        })?;
+        Ok(CppType::TemplateArgumentValue(
+            CppTemplateArgumentValue::EnumValue(enum_value_path),
+        ))
+    }
+
     /// Attempts to parse an unexposed type, i.e. a type the used `clang` API
     /// is not able to describe. Either `type1` or `string` must be specified,
     /// and both may be specified at the same time.
@@ -465,34 +1599,42 @@ impl CppParser<'_, '_> {
                         .unwrap_or(Accessibility::Public)
                         != Accessibility::Public
                     {
-                        bail!(
-                            "Type uses private class ({})",
-                            get_full_name_display(declaration)
-                        );
+                        return Err(skip_error(
+                            CppParserSkipCategory::PrivateType,
+                            format!(
+                                "Type uses private class ({})",
+                                get_full_name_display(declaration)
+                            ),
+                        ));
                     }
                     if let Some((_class_name, args)) = parse_template_args(&name) {
                         let mut arg_types = Vec::new();
                         for arg in args {
-                            match self.parse_unexposed_type(
-                                None,
-                                Some(arg.trim().to_string()),
-                                context_template_args,
-                            ) {
-                                Ok(arg_type) => arg_types.push(arg_type),
-                                Err(msg) => {
-                                    bail!(
-                                        "Template argument of unexposed type is not parsed: {}: {}",
-                                        arg,
-                                        msg
-                                    );
-                                }
-                            }
+                            let arg_type = self
+                                .parse_unexposed_type(
+                                    None,
+                                    Some(arg.trim().to_string()),
+                                    context_template_args,
+                                )
+                                .with_context(|_| {
+                                    CppParserSkipReason::new(
+                                        CppParserSkipCategory::TemplateArgument,
+                                        format!(
+                                            "Template argument of unexposed type is not parsed: {}",
+                                            arg
+                                        ),
+                                    )
+                                })?;
+                            arg_types.push(arg_type);
                         }
                         let mut name = get_path(declaration)?;
                         name.last_mut().template_arguments = Some(arg_types);
                         return Ok(CppType::Class(name));
                     } else {
-                        bail!("Can't parse declaration of an unexposed type: {}", name);
+                        return Err(skip_error(
+                            CppParserSkipCategory::UnexposedType,
+                            format!("Can't parse declaration of an unexposed type: {}", name),
+                        ));
                     }
                 }
             }
@@ -506,6 +1648,7 @@ impl CppParser<'_, '_> {
         } else {
             bail!("parse_unexposed_type: either type or string must be present");
         };
+        let name = resolve_namespace_aliases(&name, &self.namespace_aliases);
         let re = Regex::new(r"^type-parameter-(\d+)-(\d+)$")?;
         if let Some(matches) = re.captures(name.as_ref()) {
             if matches.len() < 3 {
@@ -529,6 +1672,13 @@ impl CppParser<'_, '_> {
             return Ok(arg.clone());
         }
 
+        if let Some(value) = parse_integer_literal(&name) {
+            // value of a non-type template argument
+            return Ok(CppType::TemplateArgumentValue(
+                CppTemplateArgumentValue::Integer(value),
+            ));
+        }
+
         if name.ends_with(" *") {
             let remaining_name = name[0..name.len() - " *".len()].trim();
             let subtype = self.parse_unexposed_type(
@@ -542,6 +1692,7 @@ impl CppParser<'_, '_> {
                 return Ok(CppType::PointerLike {
                     kind: CppPointerLikeTypeKind::Pointer,
                     is_const,
+                    is_volatile: false,
                     target: Box::new(subtype),
                 });
             }
@@ -557,6 +1708,7 @@ impl CppParser<'_, '_> {
             return Ok(CppType::PointerLike {
                 kind: CppPointerLikeTypeKind::Reference,
                 is_const,
+                is_volatile: false,
                 target: Box::new(subtype),
             });
         }
@@ -579,14 +1731,17 @@ impl CppParser<'_, '_> {
             let path = CppPath::from_str(&name)?;
             if let Some(hook) = self.data.config.cpp_parser_path_hook() {
                 if !hook(&path)? {
-                    bail!("blacklisted path: {}", path.to_cpp_pseudo_code());
+                    return Err(skip_error(
+                        CppParserSkipCategory::BlacklistedPath,
+                        format!("blacklisted path: {}", path.to_cpp_pseudo_code()),
+                    ));
                 }
             }
             match type_data.kind {
                 CppTypeDeclarationKind::Enum { .. } => {
                     return Ok(CppType::Enum { path });
                 }
-                CppTypeDeclarationKind::Class { .. } => {
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => {
                     return Ok(CppType::Class(path));
                 }
             }
@@ -599,45 +1754,55 @@ impl CppParser<'_, '_> {
             {
                 let mut arg_types = Vec::new();
                 for arg in args {
-                    match self.parse_unexposed_type(
-                        None,
-                        Some(arg.trim().to_string()),
-                        context_template_args,
-                    ) {
-                        Ok(arg_type) => arg_types.push(arg_type),
-                        Err(msg) => {
-                            bail!(
-                                "Template argument of unexposed type is not parsed: {}: {}",
-                                arg,
-                                msg
-                            );
-                        }
-                    }
+                    let arg_type = self
+                        .parse_unexposed_type(
+                            None,
+                            Some(arg.trim().to_string()),
+                            context_template_args,
+                        )
+                        .with_context(|_| {
+                            CppParserSkipReason::new(
+                                CppParserSkipCategory::TemplateArgument,
+                                format!(
+                                    "Template argument of unexposed type is not parsed: {}",
+                                    arg
+                                ),
+                            )
+                        })?;
+                    arg_types.push(arg_type);
                 }
                 let mut class_name = CppPath::from_str(&class_text)?;
                 class_name.last_mut().template_arguments = Some(arg_types);
                 return Ok(CppType::Class(class_name));
             }
+        } else if let Ok(value) = self.parse_template_argument_value(&name, context_template_args) {
+            return Ok(value);
         } else {
-            bail!("Can't parse declaration of an unexposed type: {}", name);
+            return Err(skip_error(
+                CppParserSkipCategory::UnexposedType,
+                format!("Can't parse declaration of an unexposed type: {}", name),
+            ));
         }
 
-        bail!("Unrecognized unexposed type: {}", name);
+        Err(skip_error(
+            CppParserSkipCategory::UnexposedType,
+            format!("Unrecognized unexposed type: {}", name),
+        ))
     }
 
     /// Parses type `type1`.
     /// Surrounding class and/or
     /// method may be specified in `context_class` and `context_method`.
     fn parse_type(&self, type1: Type<'_>, context_template_args: &[CppType]) -> Result<CppType> {
-        if type1.is_volatile_qualified() {
-            bail!("Volatile type");
-        }
         let display_name = type1.get_display_name();
         if display_name == "std::list<T>" {
-            bail!(
-                "Type blacklisted because it causes crash on Windows: {}",
-                display_name
-            );
+            return Err(skip_error(
+                CppParserSkipCategory::BlacklistedPath,
+                format!(
+                    "Type blacklisted because it causes crash on Windows: {}",
+                    display_name
+                ),
+            ));
         }
         if display_name == "std::__cxx11::basic_string::const_reference"
             || display_name == "std::vector::const_reference"
@@ -700,10 +1865,21 @@ impl CppParser<'_, '_> {
             }
             TypeKind::Enum => {
                 if let Some(declaration) = type1.get_declaration() {
+                    if is_anonymous(declaration) && anonymous_typedef_name(declaration).is_none() {
+                        // values of anonymous enums are converted to constants,
+                        // so the underlying type is used instead
+                        let underlying_type = declaration
+                            .get_enum_underlying_type()
+                            .ok_or_else(|| err_msg("failed to get enum underlying type"))?;
+                        return self.parse_type(underlying_type, context_template_args);
+                    }
                     let path = get_path(declaration)?;
                     if let Some(hook) = self.data.config.cpp_parser_path_hook() {
                         if !hook(&path)? {
-                            bail!("blacklisted path: {}", path.to_cpp_pseudo_code());
+                            return Err(skip_error(
+                                CppParserSkipCategory::BlacklistedPath,
+                                format!("blacklisted path: {}", path.to_cpp_pseudo_code()),
+                            ));
                         }
                     }
                     Ok(CppType::Enum { path })
@@ -718,18 +1894,24 @@ impl CppParser<'_, '_> {
                         .unwrap_or(Accessibility::Public)
                         != Accessibility::Public
                     {
-                        bail!(
-                            "Type uses private class ({})",
-                            get_full_name_display(declaration)
-                        );
+                        return Err(skip_error(
+                            CppParserSkipCategory::PrivateType,
+                            format!(
+                                "Type uses private class ({})",
+                                get_full_name_display(declaration)
+                            ),
+                        ));
                     }
                     let mut declaration_name = get_path(declaration)?;
                     if let Some(hook) = self.data.config.cpp_parser_path_hook() {
                         if !hook(&declaration_name)? {
-                            bail!(
-                                "blacklisted path: {}",
-                                declaration_name.to_cpp_pseudo_code()
-                            );
+                            return Err(skip_error(
+                                CppParserSkipCategory::BlacklistedPath,
+                                format!(
+                                    "blacklisted path: {}",
+                                    declaration_name.to_cpp_pseudo_code()
+                                ),
+                            ));
                         }
                     }
                     let template_arguments = match type1.get_template_argument_types() {
@@ -739,20 +1921,67 @@ impl CppParser<'_, '_> {
                             if arg_types.is_empty() {
                                 bail!("arg_types is empty");
                             }
-                            for arg_type in arg_types {
+                            // clang doesn't expose values of non-type template arguments,
+                            // so they are parsed from the type's display name
+                            let arg_texts = if arg_types.iter().any(Option::is_none) {
+                                let display_name = type1.get_display_name();
+                                let display_name =
+                                    display_name.trim_start_matches("const ").to_string();
+                                let (_, texts) =
+                                    parse_template_args(&display_name).ok_or_else(|| {
+                                        skip_error(
+                                            CppParserSkipCategory::TemplateArgument,
+                                            format!(
+                                                "failed to parse template arguments of {}",
+                                                display_name
+                                            ),
+                                        )
+                                    })?;
+                                if texts.len() != arg_types.len() {
+                                    return Err(skip_error(
+                                        CppParserSkipCategory::TemplateArgument,
+                                        format!(
+                                            "template arguments count mismatch: {}",
+                                            display_name
+                                        ),
+                                    ));
+                                }
+                                texts
+                            } else {
+                                Vec::new()
+                            };
+                            for (index, arg_type) in arg_types.into_iter().enumerate() {
                                 match arg_type {
-                                    None => bail!("Template argument is None"),
+                                    None => {
+                                        let value = self
+                                            .parse_template_argument_value(
+                                                &arg_texts[index],
+                                                context_template_args,
+                                            )
+                                            .with_context(|_| {
+                                                CppParserSkipReason::new(
+                                                    CppParserSkipCategory::TemplateArgument,
+                                                    format!(
+                                                        "Invalid template argument: {}",
+                                                        arg_texts[index]
+                                                    ),
+                                                )
+                                            })?;
+                                        r.push(value);
+                                    }
                                     Some(arg_type) => {
-                                        match self.parse_type(arg_type, context_template_args) {
-                                            Ok(parsed_type) => r.push(parsed_type),
-                                            Err(msg) => {
-                                                bail!(
-                                                    "Invalid template argument: {:?}: {}",
-                                                    arg_type,
-                                                    msg
-                                                );
-                                            }
-                                        }
+                                        let parsed_type = self
+                                            .parse_type(arg_type, context_template_args)
+                                            .with_context(|_| {
+                                                CppParserSkipReason::new(
+                                                    CppParserSkipCategory::TemplateArgument,
+                                                    format!(
+                                                        "Invalid template argument: {:?}",
+                                                        arg_type
+                                                    ),
+                                                )
+                                            })?;
+                                        r.push(parsed_type);
                                     }
                                 }
                             }
@@ -770,39 +1999,49 @@ impl CppParser<'_, '_> {
                 let mut arguments = Vec::new();
                 if let Some(argument_types) = type1.get_argument_types() {
                     for arg_type in argument_types {
-                        match self.parse_type(arg_type, context_template_args) {
-                            Ok(t) => arguments.push(t),
-                            Err(msg) => {
-                                bail!(
-                                    "Failed to parse function type's argument type: {:?}: {}",
-                                    arg_type,
-                                    msg
-                                );
-                            }
-                        }
+                        let parsed_type = self
+                            .parse_type(arg_type, context_template_args)
+                            .with_context(|_| {
+                                CppParserSkipReason::new(
+                                    CppParserSkipCategory::FunctionType,
+                                    format!(
+                                        "Failed to parse function type's argument type: {:?}",
+                                        arg_type
+                                    ),
+                                )
+                            })?;
+                        arguments.push(parsed_type);
                     }
                 } else {
-                    bail!(
-                        "Failed to parse get argument types from function type: {:?}",
-                        type1
-                    );
+                    return Err(skip_error(
+                        CppParserSkipCategory::FunctionType,
+                        format!(
+                            "Failed to parse get argument types from function type: {:?}",
+                            type1
+                        ),
+                    ));
                 }
                 let return_type = if let Some(result_type) = type1.get_result_type() {
-                    match self.parse_type(result_type, context_template_args) {
-                        Ok(t) => Box::new(t),
-                        Err(msg) => {
-                            bail!(
-                                "Failed to parse function type's argument type: {:?}: {}",
-                                result_type,
-                                msg
-                            );
-                        }
-                    }
+                    let parsed_type = self
+                        .parse_type(result_type, context_template_args)
+                        .with_context(|_| {
+                            CppParserSkipReason::new(
+                                CppParserSkipCategory::FunctionType,
+                                format!(
+                                    "Failed to parse function type's return type: {:?}",
+                                    result_type
+                                ),
+                            )
+                        })?;
+                    Box::new(parsed_type)
                 } else {
-                    bail!(
-                        "Failed to parse get result type from function type: {:?}",
-                        type1
-                    );
+                    return Err(skip_error(
+                        CppParserSkipCategory::FunctionType,
+                        format!(
+                            "Failed to parse get result type from function type: {:?}",
+                            type1
+                        ),
+                    ));
                 };
                 Ok(CppType::FunctionPointer(CppFunctionPointerType {
                     return_type,
@@ -831,6 +2070,7 @@ impl CppParser<'_, '_> {
                                 Ok(CppType::PointerLike {
                                     kind: original_type_indirection,
                                     is_const: pointee.is_const_qualified(),
+                                    is_volatile: pointee.is_volatile_qualified(),
                                     target: Box::new(subtype),
                                 })
                             }
@@ -876,7 +2116,10 @@ impl CppParser<'_, '_> {
                     parsed_canonical
                 }
             }
-            _ => bail!("Unsupported kind of type: {:?}", type1.get_kind()),
+            _ => Err(skip_error(
+                CppParserSkipCategory::UnsupportedType,
+                format!("Unsupported kind of type: {:?}", type1.get_kind()),
+            )),
         }
     }
 
@@ -964,46 +2207,38 @@ impl CppParser<'_, '_> {
     fn parse_function(&mut self, entity: Entity<'_>) -> Result<()> {
         let class_name = match entity.get_semantic_parent() {
             Some(p) => match p.get_kind() {
-                EntityKind::ClassDecl | EntityKind::ClassTemplate | EntityKind::StructDecl => {
-                    match get_path(p) {
-                        Ok(class_name) => Some(class_name),
-                        Err(msg) => {
-                            bail!(
-                                "function parent is a class but it doesn't have a name: {}",
-                                msg
-                            );
-                        }
-                    }
-                }
-                EntityKind::ClassTemplatePartialSpecialization => {
-                    bail!("this function is part of a template partial specialization");
+                EntityKind::ClassDecl
+                | EntityKind::ClassTemplate
+                | EntityKind::StructDecl
+                | EntityKind::ClassTemplatePartialSpecialization => {
+                    let class_name = self.class_path(p).with_context(|_| {
+                        CppParserSkipReason::new(
+                            CppParserSkipCategory::Anonymous,
+                            "function parent is a class but it doesn't have a name",
+                        )
+                    })?;
+                    Some(class_name)
                 }
                 _ => None,
             },
             None => None,
         };
 
-        let return_type = if let Some(x) = entity.get_type() {
-            if let Some(y) = x.get_result_type() {
-                y
-            } else {
-                bail!("failed to get function return type: {:?}", entity);
-            }
-        } else {
-            bail!("failed to get function type: {:?}", entity);
-        };
+        let function_type = entity
+            .get_type()
+            .ok_or_else(|| format_err!("failed to get function type: {:?}", entity))?;
+        let return_type = function_type
+            .get_result_type()
+            .ok_or_else(|| format_err!("failed to get function return type: {:?}", entity))?;
         let context_template_args = get_context_template_args(entity);
-        let return_type_parsed = match self.parse_type(return_type, &context_template_args) {
-            Ok(x) => x,
-            Err(msg) => {
-                trace!("return type: {:?}", return_type);
-                bail!(
-                    "Can't parse return type: {}: {}",
-                    return_type.get_display_name(),
-                    msg
-                );
-            }
-        };
+        let return_type_parsed = self
+            .parse_type(return_type, &context_template_args)
+            .with_context(|_| {
+                format!(
+                    "Can't parse return type: {}",
+                    return_type.get_display_name()
+                )
+            })?;
         let mut arguments = Vec::new();
         let argument_entities = if entity.get_kind() == EntityKind::FunctionTemplate {
             entity
@@ -1041,8 +2276,7 @@ impl CppParser<'_, '_> {
                         clang_type.get_display_name()
                     )
                 })?;
-            let mut has_default_value = false;
-            for token in argument_entity
+            let tokens = argument_entity
                 .get_range()
                 .ok_or_else(|| {
                     format_err!(
@@ -1051,25 +2285,31 @@ impl CppParser<'_, '_> {
                     )
                 })?
                 .tokenize()
-            {
-                let spelling = token.get_spelling();
-                if spelling == "=" {
-                    has_default_value = true;
-                    break;
-                }
-                if spelling == "{" {
-                    // clang sometimes reports incorrect range for arguments
-                    break;
-                }
-            }
+                .into_iter()
+                .map(|token| token.get_spelling())
+                .collect_vec();
+            // clang sometimes reports incorrect range for arguments,
+            // so tokens after `{` are ignored
+            let equals_sign_index = tokens
+                .iter()
+                .take_while(|spelling| *spelling != "{")
+                .position(|spelling| spelling == "=");
+            let has_default_value = equals_sign_index.is_some();
+            let default_value =
+                equals_sign_index.and_then(|index| parse_default_value(&tokens[index + 1..]));
             arguments.push(CppFunctionArgument {
                 name,
                 argument_type,
                 has_default_value,
+                default_value,
+                type_alias: get_type_alias_path(clang_type),
             });
         }
 
-        let mut name_with_namespace = get_path(entity)?;
+        let mut name_with_namespace = match &class_name {
+            Some(class_name) => class_name.join(get_path_item(entity)?),
+            None => get_path(entity)?,
+        };
 
         let mut name = entity
             .get_name()
@@ -1087,16 +2327,7 @@ impl CppParser<'_, '_> {
         }
 
         let template_arguments = match entity.get_kind() {
-            EntityKind::FunctionTemplate => {
-                if entity
-                    .get_children()
-                    .into_iter()
-                    .any(|c| c.get_kind() == EntityKind::NonTypeTemplateParameter)
-                {
-                    bail!("Non-type template parameter is not supported");
-                }
-                get_template_arguments(entity)
-            }
+            EntityKind::FunctionTemplate => get_template_arguments(entity),
             _ => None,
         };
 
@@ -1196,10 +2427,6 @@ impl CppParser<'_, '_> {
                 "[DebugParser] The code extracted directly from header: {:?}",
                 result
             );
-            if result.contains("volatile") {
-                trace!("[DebugParser] Warning: volatile method is detected based on source code");
-                bail!("Probably a volatile method.");
-            }
             Some(result)
         } else {
             let mut token_strings = Vec::new();
@@ -1208,9 +2435,6 @@ impl CppParser<'_, '_> {
                 if text == "{" || text == ";" {
                     break;
                 }
-                if text == "volatile" {
-                    bail!("A volatile method.");
-                }
                 token_strings.push(text);
             }
             Some(token_strings.join(" "))
@@ -1229,6 +2453,7 @@ impl CppParser<'_, '_> {
                     is_virtual: entity.is_virtual_method(),
                     is_pure_virtual: entity.is_pure_virtual_method(),
                     is_const: entity.is_const_method(),
+                    is_volatile: is_volatile_function_type(&function_type.get_display_name())?,
                     is_static: entity.is_static_method(),
                     visibility: match entity.get_accessibility().unwrap_or(Accessibility::Public) {
                         Accessibility::Public => CppVisibility::Public,
@@ -1238,6 +2463,14 @@ impl CppParser<'_, '_> {
                     // not all signals are detected here! see CppData::detect_signals_and_slots
                     is_signal,
                     is_slot: false,
+                    is_final: entity
+                        .get_children()
+                        .iter()
+                        .any(|c| c.get_kind() == EntityKind::FinalAttr),
+                    is_override: entity
+                        .get_children()
+                        .iter()
+                        .any(|c| c.get_kind() == EntityKind::OverrideAttr),
                 })
             } else {
                 None
@@ -1247,11 +2480,16 @@ impl CppParser<'_, '_> {
             return_type: return_type_parsed,
             cast: None,
             declaration_code,
+            // deleted functions are reported as unavailable
+            is_deleted: entity.get_availability() == Availability::Unavailable,
+            is_noexcept: is_noexcept_function_type(&function_type.get_display_name())?,
+            deprecation: get_deprecation(entity),
+            return_type_alias: get_type_alias_path(return_type),
         };
 
         self.add_output(
             self.entity_include_file(entity)?,
-            get_origin_location(entity)?,
+            entity,
             CppItem::Function(function),
         )?;
 
@@ -1261,19 +2499,27 @@ impl CppParser<'_, '_> {
     /// Parses an enum `entity`.
     fn parse_enum(&mut self, entity: Entity<'_>) -> Result<()> {
         let include_file = self.entity_include_file(entity).with_context(|_| {
-            format!(
-                "Origin of type is unknown: {}; entity: {:?}",
-                get_full_name_display(entity),
-                entity
+            CppParserSkipReason::new(
+                CppParserSkipCategory::UnknownOrigin,
+                format!(
+                    "Origin of type is unknown: {}; entity: {:?}",
+                    get_full_name_display(entity),
+                    entity
+                ),
             )
         })?;
         let enum_name = get_path(entity)?;
+        let underlying_type = self.enum_underlying_type(entity)?;
         self.add_output(
             include_file.clone(),
-            get_origin_location(entity)?,
+            entity,
             CppItem::Type(CppTypeDeclaration {
-                kind: CppTypeDeclarationKind::Enum,
+                kind: CppTypeDeclarationKind::Enum {
+                    underlying_type: underlying_type.clone(),
+                },
                 path: enum_name.clone(),
+                deprecation: get_deprecation(entity),
+                anonymous_member: None,
             }),
         )?;
         for child in entity.get_children() {
@@ -1281,16 +2527,23 @@ impl CppParser<'_, '_> {
                 let val = child
                     .get_enum_constant_value()
                     .ok_or_else(|| err_msg("failed to get value of enum variant"))?;
+                let value = if underlying_type.is_unsigned_integer() {
+                    // the signed value is sign-extended from the underlying type's width
+                    val.1 as i64
+                } else {
+                    val.0
+                };
 
                 let value_name = child
                     .get_name()
                     .ok_or_else(|| err_msg("failed to get name of enum variant"))?;
                 self.add_output(
                     include_file.clone(),
-                    get_origin_location(child)?,
+                    child,
                     CppItem::EnumValue(CppEnumValue {
                         path: enum_name.join(CppPathItem::from_good_str(&value_name)),
-                        value: val.0,
+                        value,
+                        deprecation: get_deprecation(child),
                     }),
                 )?;
             }
@@ -1298,23 +2551,208 @@ impl CppParser<'_, '_> {
         Ok(())
     }
 
+    /// Returns the integer type used to represent values of enum `entity`.
+    /// It's the underlying type if it's fixed in the declaration
+    /// (e.g. `enum class Flags : uint8_t`) or doesn't have the size of `int`.
+    /// Otherwise `int` is used, regardless of the signedness chosen by the compiler.
+    fn enum_underlying_type(&self, entity: Entity<'_>) -> Result<CppType> {
+        let clang_type = entity
+            .get_enum_underlying_type()
+            .ok_or_else(|| err_msg("failed to get enum underlying type"))?;
+        let underlying_type = self
+            .parse_type(clang_type, &get_context_template_args(entity))
+            .with_context(|_| err_msg("failed to parse enum underlying type"))?;
+        let is_fixed = entity.get_range().map_or(false, |range| {
+            range
+                .tokenize()
+                .into_iter()
+                .map(|token| token.get_spelling())
+                .take_while(|token| token != "{")
+                .any(|token| token == ":")
+        });
+        let int_type = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+        let uint_type = CppType::BuiltInNumeric(CppBuiltInNumericType::UInt);
+        if is_fixed || (underlying_type != int_type && underlying_type != uint_type) {
+            Ok(underlying_type)
+        } else {
+            Ok(int_type)
+        }
+    }
+
+    /// Parses an anonymous enum `entity`. Its values are accessible in the enclosing
+    /// scope, so they are added as constants of the enclosing class or namespace.
+    fn parse_anonymous_enum(&mut self, entity: Entity<'_>) -> Result<()> {
+        let include_file = self.entity_include_file(entity).with_context(|_| {
+            CppParserSkipReason::new(
+                CppParserSkipCategory::UnknownOrigin,
+                "Origin of anonymous enum is unknown",
+            )
+        })?;
+        if !get_context_template_args(entity).is_empty() {
+            return Err(skip_error(
+                CppParserSkipCategory::Anonymous,
+                "anonymous enums in templates are not supported",
+            ));
+        }
+        let parent = entity
+            .get_semantic_parent()
+            .ok_or_else(|| err_msg("failed to get parent of anonymous enum"))?;
+        let parent_path = match parent.get_kind() {
+            EntityKind::TranslationUnit => None,
+            _ => Some(get_path(parent)?),
+        };
+        let is_class_member = matches!(
+            parent.get_kind(),
+            EntityKind::ClassDecl | EntityKind::StructDecl | EntityKind::UnionDecl
+        );
+        let underlying_type = entity
+            .get_enum_underlying_type()
+            .ok_or_else(|| err_msg("failed to get enum underlying type"))?;
+        let value_type = self.parse_type(underlying_type, &[])?;
+        for child in entity.get_children() {
+            if child.get_kind() != EntityKind::EnumConstantDecl {
+                continue;
+            }
+            let val = child
+                .get_enum_constant_value()
+                .ok_or_else(|| err_msg("failed to get value of enum variant"))?;
+            let value_name = child
+                .get_name()
+                .ok_or_else(|| err_msg("failed to get name of enum variant"))?;
+            let value_item = CppPathItem::from_good_str(&value_name);
+            let path = match &parent_path {
+                Some(parent_path) => parent_path.join(value_item),
+                None => CppPath::from_item(value_item),
+            };
+            if let Some(hook) = self.data.config.cpp_parser_path_hook() {
+                if !hook(&path)? {
+                    continue;
+                }
+            }
+            let item = if is_class_member {
+                CppItem::ClassField(CppClassField {
+                    path,
+                    field_type: value_type.clone(),
+                    visibility: match entity.get_accessibility().unwrap_or(Accessibility::Public) {
+                        Accessibility::Public => CppVisibility::Public,
+                        Accessibility::Protected => CppVisibility::Protected,
+                        Accessibility::Private => CppVisibility::Private,
+                    },
+                    is_static: true,
+                    is_const: true,
+                    is_volatile: false,
+                    value: Some(CppConstantValue::Integer(val.0)),
+                    bit_width: None,
+                    array_size: None,
+                })
+            } else {
+                CppItem::GlobalVariable(CppGlobalVariable {
+                    path,
+                    variable_type: value_type.clone(),
+                    is_const: true,
+                    is_volatile: false,
+                    value: Some(CppConstantValue::Integer(val.0)),
+                })
+            };
+            self.add_output(include_file.clone(), child, item)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a `typedef` or `using` declaration `entity`.
+    fn parse_type_alias(&mut self, entity: Entity<'_>) -> Result<()> {
+        let include_file = self.entity_include_file(entity).with_context(|_| {
+            CppParserSkipReason::new(
+                CppParserSkipCategory::UnknownOrigin,
+                "Origin of type alias is unknown",
+            )
+        })?;
+        if !get_context_template_args(entity).is_empty() {
+            bail!("type aliases in templates are not supported");
+        }
+        let target_clang_type = entity
+            .get_typedef_underlying_type()
+            .ok_or_else(|| err_msg("failed to get underlying type of type alias"))?;
+        if let Some(declaration) = target_clang_type.get_canonical_type().get_declaration() {
+            if is_anonymous(declaration) {
+                // the alias is used as the name of the anonymous type itself
+                return Ok(());
+            }
+        }
+        let path = get_path(entity)?;
+        if self
+            .parse_special_typedef(&path.to_cpp_pseudo_code())
+            .is_some()
+        {
+            // the typedef is used in place of its target type
+            return Ok(());
+        }
+        let target_type = self
+            .parse_type(target_clang_type, &[])
+            .with_context(|_| err_msg("failed to parse type alias target"))?;
+        match &target_type {
+            CppType::Class(target_path) | CppType::Enum { path: target_path }
+                if target_path == &path =>
+            {
+                // `typedef struct A A;` doesn't introduce a new name
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.add_output(
+            include_file,
+            entity,
+            CppItem::TypeAlias(CppTypeAlias { path, target_type }),
+        )?;
+        Ok(())
+    }
+
     /// Parses a class field `entity`.
     fn parse_class_field(&mut self, entity: Entity<'_>, class_type: &CppPath) -> Result<()> {
-        let include_file = self
-            .entity_include_file(entity)
-            .with_context(|_| err_msg("Origin of class field is unknown"))?;
+        let include_file = self.entity_include_file(entity).with_context(|_| {
+            CppParserSkipReason::new(
+                CppParserSkipCategory::UnknownOrigin,
+                "Origin of class field is unknown",
+            )
+        })?;
         let field_name = entity
             .get_name()
             .ok_or_else(|| err_msg("failed to get field name"))?;
-        let field_clang_type = entity
+        let mut field_clang_type = entity
             .get_type()
             .ok_or_else(|| err_msg("failed to get field type"))?;
+        let mut array_size = None;
+        let canonical_type = field_clang_type.get_canonical_type();
+        if canonical_type.get_kind() == TypeKind::ConstantArray {
+            // the item type is stored, and the array size is stored separately
+            array_size = Some(
+                canonical_type
+                    .get_size()
+                    .ok_or_else(|| err_msg("failed to get array size"))?,
+            );
+            field_clang_type = canonical_type
+                .get_element_type()
+                .ok_or_else(|| err_msg("failed to get array item type"))?;
+        }
+        let context_template_args = get_context_template_args(entity);
         let field_type = self
-            .parse_type(field_clang_type, &get_context_template_args(entity))
+            .parse_type(field_clang_type, &context_template_args)
             .with_context(|_| err_msg("failed to parse field type"))?;
+        let is_static = entity.get_kind() == EntityKind::VarDecl;
+        let is_const = field_clang_type.is_const_qualified();
+        let is_volatile = field_clang_type.is_volatile_qualified();
+        let bit_width = if entity.is_bit_field() {
+            Some(
+                entity
+                    .get_bit_field_width()
+                    .ok_or_else(|| err_msg("failed to get bit-field width"))?,
+            )
+        } else {
+            None
+        };
         self.add_output(
             include_file,
-            get_origin_location(entity)?,
+            entity,
             CppItem::ClassField(CppClassField {
                 path: class_type.join(CppPathItem::from_good_str(&field_name)),
                 field_type,
@@ -1323,13 +2761,60 @@ impl CppParser<'_, '_> {
                     Accessibility::Protected => CppVisibility::Protected,
                     Accessibility::Private => CppVisibility::Private,
                 },
-                is_static: entity.get_kind() == EntityKind::VarDecl,
+                is_static,
+                is_const,
+                is_volatile,
+                value: if is_static
+                    && is_const
+                    && array_size.is_none()
+                    && context_template_args.is_empty()
+                {
+                    evaluate_constant(entity)
+                } else {
+                    None
+                },
+                bit_width,
+                array_size,
             }),
         )?;
 
         Ok(())
     }
 
+    /// Parses a namespace-scope variable `entity`.
+    fn parse_global_variable(&mut self, entity: Entity<'_>) -> Result<()> {
+        let include_file = self.entity_include_file(entity).with_context(|_| {
+            CppParserSkipReason::new(
+                CppParserSkipCategory::UnknownOrigin,
+                "Origin of variable is unknown",
+            )
+        })?;
+        let variable_clang_type = entity
+            .get_type()
+            .ok_or_else(|| err_msg("failed to get variable type"))?;
+        let variable_type = self
+            .parse_type(variable_clang_type, &[])
+            .with_context(|_| err_msg("failed to parse variable type"))?;
+        let is_const = variable_clang_type.is_const_qualified();
+        let is_volatile = variable_clang_type.is_volatile_qualified();
+        self.add_output(
+            include_file,
+            entity,
+            CppItem::GlobalVariable(CppGlobalVariable {
+                path: get_path(entity)?,
+                variable_type,
+                is_const,
+                is_volatile,
+                value: if is_const {
+                    evaluate_constant(entity)
+                } else {
+                    None
+                },
+            }),
+        )?;
+        Ok(())
+    }
+
     // we pass parent manually because both lexical and semantic parent are missing for these
     // entities for some reason
     fn parse_class_base(
@@ -1347,7 +2832,7 @@ impl CppParser<'_, '_> {
         if let CppType::Class(base_type) = &base_type {
             self.add_output(
                 self.entity_include_file(entity)?,
-                get_origin_location(entity).unwrap(),
+                entity,
                 CppItem::ClassBase(CppBaseSpecifier {
                     base_class_type: base_type.clone(),
                     is_virtual: entity.is_virtual_base(),
@@ -1357,7 +2842,7 @@ impl CppParser<'_, '_> {
                         Accessibility::Private => CppVisibility::Private,
                     },
                     base_index,
-                    derived_class_type: get_path(parent)?,
+                    derived_class_type: self.class_path(parent)?,
                 }),
             )?;
         } else {
@@ -1366,71 +2851,116 @@ impl CppParser<'_, '_> {
         Ok(())
     }
 
+    /// Returns fully qualified name of a class `entity`. For template partial
+    /// specializations, the last path item contains the specialization pattern
+    /// (e.g. `Traits<T*>`) instead of the list of template parameters.
+    fn class_path(&self, entity: Entity<'_>) -> Result<CppPath> {
+        let mut path = get_path(entity)?;
+        if entity.get_kind() != EntityKind::ClassTemplatePartialSpecialization {
+            return Ok(path);
+        }
+        let display_name = entity
+            .get_display_name()
+            .ok_or_else(|| err_msg("failed to get display name"))?;
+        let (_, args) = parse_template_args(&display_name).ok_or_else(|| {
+            format_err!(
+                "failed to parse template partial specialization pattern: {}",
+                display_name
+            )
+        })?;
+        let context_template_args = get_context_template_args(entity);
+        let args = args.iter().map_if_ok(|arg| {
+            self.parse_unexposed_type(None, Some(arg.trim().to_string()), &context_template_args)
+        })?;
+        path.last_mut().template_arguments = Some(args);
+        Ok(path)
+    }
+
     /// Parses a class or a struct `entity`.
     fn parse_class(&mut self, entity: Entity<'_>) -> Result<()> {
         let include_file = self.entity_include_file(entity).with_context(|_| {
-            format!(
-                "Origin of type is unknown: {}; entity: {:?}",
-                get_full_name_display(entity),
-                entity
+            CppParserSkipReason::new(
+                CppParserSkipCategory::UnknownOrigin,
+                format!(
+                    "Origin of type is unknown: {}; entity: {:?}",
+                    get_full_name_display(entity),
+                    entity
+                ),
             )
         })?;
-        let full_name = get_path(entity)?;
+        let full_name = self.class_path(entity)?;
         let template_arguments = get_template_arguments(entity);
-        if entity.get_kind() == EntityKind::ClassTemplate {
-            if entity
-                .get_children()
-                .into_iter()
-                .any(|c| c.get_kind() == EntityKind::NonTypeTemplateParameter)
-            {
-                bail!("Non-type template parameter is not supported");
-            }
-
+        if entity.get_kind() == EntityKind::ClassTemplate
+            || entity.get_kind() == EntityKind::ClassTemplatePartialSpecialization
+        {
             if template_arguments.is_none() {
                 dump_entity(entity, 0);
-                bail!("missing template arguments");
+                return Err(skip_error(
+                    CppParserSkipCategory::TemplateArgument,
+                    "missing template arguments",
+                ));
             }
         } else if template_arguments.is_some() {
-            bail!("unexpected template arguments");
+            return Err(skip_error(
+                CppParserSkipCategory::TemplateArgument,
+                "unexpected template arguments",
+            ));
         }
         let mut current_base_index = 0;
         for child in entity.get_children() {
             if child.get_kind() == EntityKind::FieldDecl || child.get_kind() == EntityKind::VarDecl
             {
                 if let Err(err) = self.parse_class_field(child, &full_name) {
-                    debug!(
-                        "failed to parse class field: {}: {}",
-                        get_full_name_display(child),
-                        err
-                    );
-                    trace!("entity: {:?}", entity);
+                    self.report_skipped_entity(child, "class field", &err);
                 }
             }
+            if is_anonymous_member(child) {
+                self.parse_anonymous_member_fields(child, &full_name);
+            }
             if child.get_kind() == EntityKind::BaseSpecifier {
                 if let Err(err) = self.parse_class_base(child, current_base_index, entity) {
-                    debug!(
-                        "failed to parse class base: {}: {}",
-                        get_full_name_display(entity),
-                        err
-                    );
+                    self.report_skipped_entity(entity, "class base", &err);
                 }
                 current_base_index += 1;
             }
-            if child.get_kind() == EntityKind::NonTypeTemplateParameter {
-                bail!("Non-type template parameter is not supported");
-            }
         }
         self.add_output(
             include_file,
-            get_origin_location(entity).unwrap(),
+            entity,
             CppItem::Type(CppTypeDeclaration {
-                kind: CppTypeDeclarationKind::Class,
+                kind: if entity.get_kind() == EntityKind::UnionDecl {
+                    CppTypeDeclarationKind::Union
+                } else {
+                    CppTypeDeclarationKind::Class
+                },
                 path: full_name,
+                deprecation: get_deprecation(entity),
+                anonymous_member: anonymous_type_member(entity)
+                    .map(anonymous_member_expression)
+                    .transpose()?,
             }),
         )?;
         Ok(())
     }
 
+    /// Parses fields of an anonymous struct or union member `entity`
+    /// as fields of the enclosing class `class_type`.
+    fn parse_anonymous_member_fields(&mut self, entity: Entity<'_>, class_type: &CppPath) {
+        if entity.get_accessibility().unwrap_or(Accessibility::Public) != Accessibility::Public {
+            return;
+        }
+        for child in entity.get_children() {
+            if child.get_kind() == EntityKind::FieldDecl {
+                if let Err(err) = self.parse_class_field(child, class_type) {
+                    self.report_skipped_entity(child, "anonymous member field", &err);
+                }
+            }
+            if is_anonymous_member(child) {
+                self.parse_anonymous_member_fields(child, class_type);
+            }
+        }
+    }
+
     /// Determines file path of the include file this `entity` is located in.
     fn entity_include_path(&self, entity: Entity<'_>) -> Result<PathBuf> {
         if let Some(location) = entity.get_location() {
@@ -1454,24 +2984,51 @@ impl CppParser<'_, '_> {
         Ok(os_str_to_str(file_name)?.to_string())
     }
 
-    /// Returns false if this `entity` was blacklisted in some way.
-    fn should_process_entity(&self, entity: Entity<'_>) -> Result<bool> {
-        if entity.get_kind() == EntityKind::TranslationUnit {
-            return Ok(true);
-        }
+    /// Returns true if `entity` is located in one of the target include paths.
+    fn is_in_target_paths(&self, entity: Entity<'_>) -> Result<bool> {
         if let Ok(file_path) = self.entity_include_path(entity) {
             let file_path = canonicalize(Path::new(&file_path))?;
-            if !self.current_target_paths.is_empty()
-                && !self
+            Ok(self.current_target_paths.is_empty()
+                || self
                     .current_target_paths
                     .iter()
-                    .any(|x| file_path.starts_with(x))
-            {
-                return Ok(false);
-            }
+                    .any(|x| file_path.starts_with(x)))
         } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns true if `entity` is located in a header that was parsed
+    /// by the previous run and didn't change since then.
+    fn is_in_unchanged_header(&self, entity: Entity<'_>) -> bool {
+        !self.unchanged_headers.is_empty()
+            && self
+                .entity_include_path(entity)
+                .map_or(false, |path| self.unchanged_headers.contains(&path))
+    }
+
+    /// Returns false if this `entity` was blacklisted in some way.
+    fn should_process_entity(&self, entity: Entity<'_>) -> Result<bool> {
+        if entity.get_kind() == EntityKind::TranslationUnit {
+            return Ok(true);
+        }
+        if !self.is_in_target_paths(entity)? {
             return Ok(false);
         }
+        // namespaces are still visited because they may contain declarations
+        // from other headers, and aliases are needed to resolve types
+        if self.is_in_unchanged_header(entity)
+            && !matches!(
+                entity.get_kind(),
+                EntityKind::Namespace | EntityKind::NamespaceAlias
+            )
+        {
+            return Ok(false);
+        }
+        if is_anonymous(entity) && anonymous_type_name(entity).is_none() {
+            // anonymous types are filtered by their enclosing declaration
+            return Ok(true);
+        }
         if let Ok(full_name) = get_path(entity) {
             if let Some(hook) = self.data.config.cpp_parser_path_hook() {
                 if !hook(&full_name)? {
@@ -1484,11 +3041,61 @@ impl CppParser<'_, '_> {
         Ok(true)
     }
 
+    /// Logs the error that caused `entity` to be skipped
+    /// and adds the entity to the parser report.
+    fn report_skipped_entity(&mut self, entity: Entity<'_>, kind: &str, error: &Error) {
+        let name = get_full_name_display(entity);
+        debug!("failed to parse {}: {}: {}", kind, name, error);
+        print_trace(error, Some(log::Level::Trace));
+        trace!("entity: {:?}", entity);
+
+        let location = get_origin_location(entity).ok();
+        let lines = entity.get_range().map(|range| {
+            (
+                range.get_start().get_presumed_location(),
+                range.get_end().get_presumed_location(),
+            )
+        });
+        let diagnostics = self
+            .report
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| match (&diagnostic.location, &lines) {
+                (Some(location), Some((start, end))) => {
+                    location.include_file_path == start.0
+                        && location.line >= start.1
+                        && location.line <= end.1
+                }
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        self.report
+            .skipped_declarations
+            .push(CppSkippedDeclaration {
+                kind: kind.to_string(),
+                name,
+                location,
+                category: CppParserSkipCategory::from_error(error),
+                message: error.iter_chain().join(": "),
+                diagnostics,
+            });
+    }
+
     fn parse(&mut self, entity: Entity<'_>) -> Result<()> {
+        self.report.diagnostics = entity
+            .get_translation_unit()
+            .get_diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.get_severity() == Severity::Warning)
+            .map(parser_diagnostic)
+            .collect();
         debug!("Parsing types");
         self.parse_types(entity)?;
         debug!("Parsing functions");
         self.parse_functions(entity)?;
+        debug!("Parsing macros");
+        self.parse_macros(entity)?;
         for hook in self.data.config.after_cpp_parser_hooks() {
             hook(self.data, &self.output)?;
         }
@@ -1506,45 +3113,90 @@ impl CppParser<'_, '_> {
                 if entity.get_accessibility() == Some(Accessibility::Private) {
                     return Ok(()); // skipping private stuff
                 }
-                if entity.get_name().is_some() && entity.is_definition() {
-                    if let Err(error) = self.parse_enum(entity) {
-                        debug!(
-                            "failed to parse enum: {}: {}",
-                            get_full_name_display(entity),
-                            error
-                        );
-                        trace!("entity: {:?}", entity);
+                if !entity.is_definition() {
+                    return Ok(());
+                }
+                if is_anonymous(entity) && anonymous_typedef_name(entity).is_none() {
+                    if let Err(error) = self.parse_anonymous_enum(entity) {
+                        self.report_skipped_entity(entity, "anonymous enum", &error);
                     }
+                } else if let Err(error) = self.parse_enum(entity) {
+                    self.report_skipped_entity(entity, "enum", &error);
                 }
             }
-            EntityKind::ClassDecl | EntityKind::ClassTemplate | EntityKind::StructDecl => {
+            EntityKind::ClassDecl
+            | EntityKind::ClassTemplate
+            | EntityKind::StructDecl
+            | EntityKind::UnionDecl => {
                 if entity.get_accessibility() == Some(Accessibility::Private) {
                     return Ok(()); // skipping private stuff
                 }
-                let ok = entity.get_name().is_some() && // not an anonymous struct
+                let ok = get_path_item(entity).is_ok() && // not an unnamed anonymous type
                     entity.is_definition() && // not a forward declaration
                     entity.get_template().is_none(); // not a template specialization
                 if ok {
                     if let Err(error) = self.parse_class(entity) {
-                        debug!(
-                            "failed to parse class: {}: {}",
-                            get_full_name_display(entity),
-                            error
+                        self.report_skipped_entity(entity, "class", &error);
+                    }
+                }
+            }
+            EntityKind::ClassTemplatePartialSpecialization => {
+                if entity.get_accessibility() == Some(Accessibility::Private) {
+                    return Ok(()); // skipping private stuff
+                }
+                if entity.is_definition() {
+                    if let Err(error) = self.parse_class(entity) {
+                        self.report_skipped_entity(
+                            entity,
+                            "template partial specialization",
+                            &error,
                         );
-                        trace!("entity: {:?}", entity);
                     }
                 }
             }
+            EntityKind::TypedefDecl | EntityKind::TypeAliasDecl => {
+                if entity.get_accessibility() == Some(Accessibility::Private) {
+                    return Ok(()); // skipping private stuff
+                }
+                if let Err(error) = self.parse_type_alias(entity) {
+                    self.report_skipped_entity(entity, "type alias", &error);
+                }
+            }
             EntityKind::Namespace => match get_path(entity) {
                 Ok(path) => {
-                    self.add_output(
-                        self.entity_include_file(entity)?,
-                        get_origin_location(entity).unwrap(),
-                        CppItem::Namespace(CppNamespace { path }),
-                    )?;
+                    if is_inline_namespace(entity) {
+                        // the inline namespace's members are added to the enclosing namespace
+                        let parent = path.parent().map(|p| p.to_cpp_pseudo_code());
+                        self.namespace_aliases
+                            .insert(path.to_cpp_pseudo_code(), parent.unwrap_or_default());
+                    } else {
+                        self.add_output(
+                            self.entity_include_file(entity)?,
+                            entity,
+                            CppItem::Namespace(CppNamespace { path }),
+                        )?;
+                    }
                 }
                 Err(error) => debug!("failed to get namespace name: {}", error),
             },
+            EntityKind::NamespaceAlias => {
+                let target = entity
+                    .get_children()
+                    .into_iter()
+                    .filter_map(|child| child.get_reference())
+                    .filter(|target| target.get_kind() == EntityKind::Namespace)
+                    .last();
+                match (get_path(entity), target.map(get_path)) {
+                    (Ok(alias), Some(Ok(target))) => {
+                        self.namespace_aliases
+                            .insert(alias.to_cpp_pseudo_code(), target.to_cpp_pseudo_code());
+                    }
+                    _ => debug!(
+                        "failed to resolve namespace alias: {}",
+                        get_full_name_display(entity)
+                    ),
+                }
+            }
             _ => {}
         }
         match entity.get_kind() {
@@ -1552,6 +3204,7 @@ impl CppParser<'_, '_> {
             | EntityKind::Namespace
             | EntityKind::StructDecl
             | EntityKind::ClassDecl
+            | EntityKind::UnionDecl
             | EntityKind::UnexposedDecl
             | EntityKind::ClassTemplate => {
                 for c in entity.get_children() {
@@ -1563,6 +3216,67 @@ impl CppParser<'_, '_> {
         Ok(())
     }
 
+    /// Parses object-like macros defined in translation unit `entity`
+    /// and saves the ones that expand to a literal expression.
+    fn parse_macros(&mut self, entity: Entity<'_>) -> Result<()> {
+        // values of previously seen macros, so that macros referring
+        // to other macros can be evaluated
+        let mut known_values = HashMap::new();
+        for child in entity.get_children() {
+            if child.get_kind() != EntityKind::MacroDefinition
+                || child.is_function_like_macro()
+                || child.is_builtin_macro()
+            {
+                continue;
+            }
+            let name = match child.get_name() {
+                Some(name) => name,
+                None => continue,
+            };
+            let range = match child.get_range() {
+                Some(range) => range,
+                None => continue,
+            };
+            let tokens = range
+                .tokenize()
+                .into_iter()
+                .skip(1) // macro name
+                .map(|token| token.get_spelling())
+                .collect_vec();
+            let value = match evaluate_macro_body(&tokens, &known_values) {
+                Some(value) => value,
+                None => {
+                    trace!("skipping macro: {} {}", name, tokens.join(" "));
+                    continue;
+                }
+            };
+            if let CppConstantValue::Integer(value) = value {
+                known_values.insert(name.clone(), value);
+            }
+            if !self.is_in_target_paths(child)? || self.is_in_unchanged_header(child) {
+                continue;
+            }
+            if let Some(hook) = self.data.config.cpp_parser_macro_hook() {
+                if !hook(&name)? {
+                    continue;
+                }
+            }
+            let include_file = match self.entity_include_file(child) {
+                Ok(include_file) => include_file,
+                Err(_) => continue,
+            };
+            self.add_output(
+                include_file,
+                child,
+                CppItem::MacroConstant(CppMacroConstant {
+                    path: CppPath::from_good_str(&name),
+                    value,
+                }),
+            )?;
+        }
+        Ok(())
+    }
+
     /// Parses methods in translation unit `entity`.
     fn parse_functions(&mut self, entity: Entity<'_>) -> Result<()> {
         if !self.should_process_entity(entity)? {
@@ -1576,19 +3290,25 @@ impl CppParser<'_, '_> {
             | EntityKind::ConversionFunction
             | EntityKind::FunctionTemplate => {
                 if let Err(error) = self.parse_function(entity) {
-                    debug!(
-                        "failed to parse function: {}: {}",
-                        get_full_name_display(entity),
-                        error
-                    );
-                    print_trace(&error, Some(log::Level::Trace));
-                    trace!("entity: {:?}", entity);
+                    self.report_skipped_entity(entity, "function", &error);
+                }
+            }
+            EntityKind::VarDecl => {
+                // static class fields are parsed with their class
+                let is_global = entity.get_semantic_parent().map_or(false, |parent| {
+                    parent.get_kind() == EntityKind::Namespace
+                        || parent.get_kind() == EntityKind::TranslationUnit
+                });
+                if is_global {
+                    if let Err(error) = self.parse_global_variable(entity) {
+                        self.report_skipped_entity(entity, "variable", &error);
+                    }
                 }
             }
             EntityKind::StructDecl
             | EntityKind::ClassDecl
-            | EntityKind::ClassTemplate
-            | EntityKind::ClassTemplatePartialSpecialization => {
+            | EntityKind::UnionDecl
+            | EntityKind::ClassTemplate => {
                 if let Some(name) = entity.get_display_name() {
                     if let Ok(CppType::Class(parent_type_path)) = self.parse_unexposed_type(
                         None,
@@ -1603,7 +3323,7 @@ impl CppParser<'_, '_> {
                                 .any(|x| !x.is_template_parameter())
                             {
                                 trace!(
-                                    "skipping template partial specialization: {}",
+                                    "skipping template specialization: {}",
                                     get_full_name_display(entity),
                                 );
                                 trace!("entity: {:?}", entity);
@@ -1620,8 +3340,10 @@ impl CppParser<'_, '_> {
             | EntityKind::Namespace
             | EntityKind::StructDecl
             | EntityKind::ClassDecl
+            | EntityKind::UnionDecl
             | EntityKind::UnexposedDecl
-            | EntityKind::ClassTemplate => {
+            | EntityKind::ClassTemplate
+            | EntityKind::ClassTemplatePartialSpecialization => {
                 for c in entity.get_children() {
                     self.parse_functions(c)?;
                 }
@@ -1696,3 +3418,334 @@ fn should_parse_template_args_works() {
     assert_eq!(parse_template_args("name<arg,arg2"), None);
     assert_eq!(parse_template_args("name<arg<arg3,arg4>,arg2"), None);
 }
+
+/// Matches an enum value in the `(Enum)1` form.
+static ENUM_VALUE_CAST_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\((.+)\)\s*(-?\w+)$").unwrap());
+
+/// Parses a C++ integer or boolean literal, e.g. `4`, `-1`, `0x10u` or `true`.
+fn parse_integer_literal(text: &str) -> Option<i64> {
+    match text {
+        "true" => return Some(1),
+        "false" => return Some(0),
+        _ => {}
+    }
+    let (is_negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text.trim_start()),
+        None => (false, text),
+    };
+    let text = text.trim_end_matches(|c: char| c == 'u' || c == 'U' || c == 'l' || c == 'L');
+    let value = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()?
+    } else if text.len() > 1 && text.starts_with('0') {
+        u64::from_str_radix(&text[1..], 8).ok()?
+    } else if text.starts_with(|c: char| c.is_ascii_digit()) {
+        text.parse::<u64>().ok()?
+    } else {
+        return None;
+    };
+    // values that don't fit in `i64` are not supported
+    let value = i128::from(value);
+    i64::try_from(if is_negative { -value } else { value }).ok()
+}
+
+#[test]
+fn parse_integer_literal_works() {
+    assert_eq!(parse_integer_literal("4"), Some(4));
+    assert_eq!(parse_integer_literal("-1"), Some(-1));
+    assert_eq!(parse_integer_literal("16UL"), Some(16));
+    assert_eq!(parse_integer_literal("0x10"), Some(16));
+    assert_eq!(parse_integer_literal("010"), Some(8));
+    assert_eq!(parse_integer_literal("true"), Some(1));
+    assert_eq!(
+        parse_integer_literal("-9223372036854775808"),
+        Some(i64::MIN)
+    );
+    assert_eq!(parse_integer_literal("0xFFFFFFFFFFFFFFFFull"), None);
+    assert_eq!(parse_integer_literal("T"), None);
+    assert_eq!(parse_integer_literal("N"), None);
+    assert_eq!(parse_integer_literal("QString"), None);
+}
+
+/// Evaluates the initializer of a constant variable `entity`
+/// if it's an integer, floating point or string expression.
+/// Unsigned values above `i64::MAX` are not supported.
+fn evaluate_constant(entity: Entity<'_>) -> Option<CppConstantValue> {
+    match entity.evaluate()? {
+        EvaluationResult::SignedInteger(value) => Some(CppConstantValue::Integer(value)),
+        EvaluationResult::UnsignedInteger(value) => {
+            i64::try_from(value).ok().map(CppConstantValue::Integer)
+        }
+        EvaluationResult::Float(value) if value.is_finite() => {
+            Some(CppConstantValue::Float(format!("{:?}", value)))
+        }
+        EvaluationResult::String(value) => value.into_string().ok().map(CppConstantValue::String),
+        _ => None,
+    }
+}
+
+/// Parses a C++ floating point literal, e.g. `1.5`, `2e-3` or `0.5f`.
+fn parse_float_literal(text: &str) -> Option<f64> {
+    if text.starts_with("0x")
+        || text.starts_with("0X")
+        || !text.contains(|c: char| c == '.' || c == 'e' || c == 'E')
+    {
+        return None;
+    }
+    let text = text.trim_end_matches(|c: char| c == 'f' || c == 'F' || c == 'l' || c == 'L');
+    text.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+/// Parses a plain C++ string literal token (including the quotes)
+/// and resolves simple escape sequences.
+fn parse_string_literal(text: &str) -> Option<String> {
+    let text = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        result.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            c @ '\\' | c @ '"' | c @ '\'' | c @ '?' => c,
+            _ => return None,
+        });
+    }
+    Some(result)
+}
+
+/// Evaluates `code` if it's a single integer, boolean,
+/// floating point or string literal (e.g. a default value of an argument).
+pub(crate) fn evaluate_literal(code: &str) -> Option<CppConstantValue> {
+    if let Some(value) = parse_string_literal(code) {
+        return Some(CppConstantValue::String(value));
+    }
+    if let Some(value) = parse_float_literal(code) {
+        return Some(CppConstantValue::Float(format!("{:?}", value)));
+    }
+    parse_integer_literal(code).map(CppConstantValue::Integer)
+}
+
+#[test]
+fn evaluate_literal_works() {
+    assert_eq!(evaluate_literal("-1"), Some(CppConstantValue::Integer(-1)));
+    assert_eq!(
+        evaluate_literal("0x10u"),
+        Some(CppConstantValue::Integer(16))
+    );
+    assert_eq!(
+        evaluate_literal("false"),
+        Some(CppConstantValue::Integer(0))
+    );
+    assert_eq!(
+        evaluate_literal("-0.5f"),
+        Some(CppConstantValue::Float("-0.5".to_string()))
+    );
+    assert_eq!(
+        evaluate_literal("\"a\\n\""),
+        Some(CppConstantValue::String("a\n".to_string()))
+    );
+    assert_eq!(evaluate_literal("nullptr"), None);
+    assert_eq!(evaluate_literal("QString()"), None);
+    assert_eq!(evaluate_literal("1 + 2"), None);
+}
+
+/// Evaluates the body of an object-like macro if it's a literal expression.
+///
+/// Supported bodies are (possibly concatenated) string literals,
+/// floating point literals and integer expressions consisting of literals,
+/// names of previously evaluated macros (`known_values`) and
+/// arithmetic and bitwise operators.
+fn evaluate_macro_body(
+    tokens: &[String],
+    known_values: &HashMap<String, i64>,
+) -> Option<CppConstantValue> {
+    if tokens.is_empty() {
+        return None;
+    }
+    if tokens.iter().all(|token| token.starts_with('"')) {
+        let parts = tokens
+            .iter()
+            .map(|token| parse_string_literal(token))
+            .collect::<Option<Vec<_>>>()?;
+        return Some(CppConstantValue::String(parts.concat()));
+    }
+
+    let mut inner = tokens;
+    while inner.len() > 2 && inner[0] == "(" && inner[inner.len() - 1] == ")" {
+        inner = &inner[1..inner.len() - 1];
+    }
+    let float_value = match inner {
+        [value] => parse_float_literal(value),
+        [sign, value] if sign == "-" => parse_float_literal(value).map(|value| -value),
+        _ => None,
+    };
+    if let Some(value) = float_value {
+        return Some(CppConstantValue::Float(format!("{:?}", value)));
+    }
+
+    let mut evaluator = MacroExpressionEvaluator {
+        tokens,
+        position: 0,
+        known_values,
+    };
+    let value = evaluator.expression(0)?;
+    if evaluator.position == tokens.len() {
+        Some(CppConstantValue::Integer(value))
+    } else {
+        None
+    }
+}
+
+/// Precedence climbing evaluator for integer expressions in macro bodies.
+struct MacroExpressionEvaluator<'a> {
+    tokens: &'a [String],
+    position: usize,
+    known_values: &'a HashMap<String, i64>,
+}
+
+impl<'a> MacroExpressionEvaluator<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let tokens = self.tokens;
+        let token = tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn binary_operator_precedence(operator: &str) -> Option<u32> {
+        let precedence = match operator {
+            "*" | "/" | "%" => 10,
+            "+" | "-" => 9,
+            "<<" | ">>" => 8,
+            "&" => 5,
+            "^" => 4,
+            "|" => 3,
+            _ => return None,
+        };
+        Some(precedence)
+    }
+
+    fn expression(&mut self, min_precedence: u32) -> Option<i64> {
+        let mut left = self.operand()?;
+        let tokens = self.tokens;
+        while let Some(operator) = tokens.get(self.position) {
+            let precedence = match Self::binary_operator_precedence(operator) {
+                Some(precedence) if precedence >= min_precedence => precedence,
+                _ => break,
+            };
+            self.position += 1;
+            let right = self.expression(precedence + 1)?;
+            left = match operator.as_str() {
+                "*" => left.wrapping_mul(right),
+                "/" => left.checked_div(right)?,
+                "%" => left.checked_rem(right)?,
+                "+" => left.wrapping_add(right),
+                "-" => left.wrapping_sub(right),
+                "<<" => left.checked_shl(u32::try_from(right).ok()?)?,
+                ">>" => left.checked_shr(u32::try_from(right).ok()?)?,
+                "&" => left & right,
+                "^" => left ^ right,
+                "|" => left | right,
+                _ => unreachable!(),
+            };
+        }
+        Some(left)
+    }
+
+    fn operand(&mut self) -> Option<i64> {
+        match self.next()? {
+            "(" => {
+                let value = self.expression(0)?;
+                if self.next()? == ")" {
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+            "-" => self.operand().map(i64::wrapping_neg),
+            "+" => self.operand(),
+            "~" => self.operand().map(|value| !value),
+            "!" => self.operand().map(|value| (value == 0) as i64),
+            token => parse_integer_literal(token).or_else(|| self.known_values.get(token).copied()),
+        }
+    }
+}
+
+#[test]
+fn evaluate_macro_body_works() {
+    fn eval(body: &[&str]) -> Option<CppConstantValue> {
+        let mut known_values = HashMap::new();
+        known_values.insert("BASE".to_string(), 16);
+        let tokens = body.iter().map(|token| token.to_string()).collect_vec();
+        evaluate_macro_body(&tokens, &known_values)
+    }
+    assert_eq!(eval(&["0x0502"]), Some(CppConstantValue::Integer(0x0502)));
+    assert_eq!(
+        eval(&["(", "1", "<<", "3", ")"]),
+        Some(CppConstantValue::Integer(8))
+    );
+    assert_eq!(
+        eval(&["1", "+", "2", "*", "3"]),
+        Some(CppConstantValue::Integer(7))
+    );
+    assert_eq!(
+        eval(&["(", "1", "+", "2", ")", "*", "3"]),
+        Some(CppConstantValue::Integer(9))
+    );
+    assert_eq!(eval(&["-", "1"]), Some(CppConstantValue::Integer(-1)));
+    assert_eq!(eval(&["~", "0"]), Some(CppConstantValue::Integer(-1)));
+    assert_eq!(
+        eval(&["BASE", "|", "1"]),
+        Some(CppConstantValue::Integer(17))
+    );
+    assert_eq!(
+        eval(&["(", "-", "0.5f", ")"]),
+        Some(CppConstantValue::Float("-0.5".to_string()))
+    );
+    assert_eq!(
+        eval(&["\"lib\"", "\"name\\n\""]),
+        Some(CppConstantValue::String("libname\n".to_string()))
+    );
+    assert_eq!(eval(&[]), None);
+    assert_eq!(eval(&["1", "/", "0"]), None);
+    assert_eq!(eval(&["1", "+"]), None);
+    assert_eq!(eval(&["(", "1"]), None);
+    assert_eq!(eval(&["UNKNOWN"]), None);
+    assert_eq!(eval(&["static_cast", "<", "int", ">", "(", "1", ")"]), None);
+}
+
+/// Checks whether a function type's display name (e.g. `void (int) noexcept`)
+/// contains a non-throwing exception specification.
+fn is_noexcept_function_type(text: &str) -> Result<bool> {
+    let re = Regex::new(r"\b(?:noexcept(?:\s*\(\s*true\s*\))?|throw\s*\(\s*\))[^()]*$")?;
+    Ok(re.is_match(text))
+}
+
+/// Returns true if function type `text` reported by clang has the `volatile` qualifier.
+fn is_volatile_function_type(text: &str) -> Result<bool> {
+    let re = Regex::new(r"\bvolatile\b[^()]*$")?;
+    Ok(re.is_match(text))
+}
+
+#[test]
+fn is_volatile_function_type_works() {
+    assert!(is_volatile_function_type("int () volatile").unwrap());
+    assert!(is_volatile_function_type("void (int) const volatile noexcept").unwrap());
+    assert!(!is_volatile_function_type("void (volatile int *)").unwrap());
+    assert!(!is_volatile_function_type("int () const").unwrap());
+}
+
+#[test]
+fn is_noexcept_function_type_works() {
+    assert!(is_noexcept_function_type("void (int) noexcept").unwrap());
+    assert!(is_noexcept_function_type("int () const noexcept(true)").unwrap());
+    assert!(is_noexcept_function_type("void () throw()").unwrap());
+    assert!(!is_noexcept_function_type("void (int)").unwrap());
+    assert!(!is_noexcept_function_type("void () noexcept(false)").unwrap());
+    assert!(!is_noexcept_function_type("void (void (*)() noexcept)").unwrap());
+}
@@ -0,0 +1,239 @@
+//! Report about C++ declarations skipped by the parser.
+
+use crate::cpp_data::CppOriginLocation;
+use ritual_common::errors::{Context, Error, Fail, Result};
+use ritual_common::file_utils::{create_dir_all, create_file, save_json};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+/// Category of the reason why the parser skipped a declaration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CppParserSkipCategory {
+    /// The declaration uses a path rejected by `cpp_parser_path_hook`
+    /// or by the built-in blacklist
+    BlacklistedPath,
+    /// The declaration uses a private class
+    PrivateType,
+    /// A template argument couldn't be parsed
+    TemplateArgument,
+    /// A type not exposed by clang couldn't be parsed from its name
+    UnexposedType,
+    /// An argument or return type of a function pointer type couldn't be parsed
+    FunctionType,
+    /// clang reported a kind of type that is not supported
+    UnsupportedType,
+    /// The declaration or its parent doesn't have a name
+    Anonymous,
+    /// The header the declaration belongs to is unknown
+    UnknownOrigin,
+    /// Any other error
+    Other,
+}
+
+impl CppParserSkipCategory {
+    /// Returns the category of a parser error. If multiple `CppParserSkipReason`s
+    /// are present in the error chain, the innermost one is used because
+    /// it's the closest to the actual cause.
+    pub fn from_error(error: &Error) -> Self {
+        error
+            .iter_chain()
+            .filter_map(|cause| {
+                cause
+                    .downcast_ref::<CppParserSkipReason>()
+                    .or_else(|| {
+                        cause
+                            .downcast_ref::<Context<CppParserSkipReason>>()
+                            .map(Context::get_context)
+                    })
+                    .map(|reason| reason.category)
+            })
+            .last()
+            .unwrap_or(CppParserSkipCategory::Other)
+    }
+
+    /// Returns a human-readable name of the category.
+    pub fn caption(self) -> &'static str {
+        use self::CppParserSkipCategory::*;
+
+        match self {
+            BlacklistedPath => "blacklisted path",
+            PrivateType => "private type",
+            TemplateArgument => "template argument",
+            UnexposedType => "unexposed type",
+            FunctionType => "function pointer type",
+            UnsupportedType => "unsupported type",
+            Anonymous => "anonymous declaration",
+            UnknownOrigin => "unknown origin",
+            Other => "other",
+        }
+    }
+}
+
+/// Parser error that carries the category of the skipped declaration.
+///
+/// It can be returned as an error itself or attached to another error
+/// with `ResultExt::context`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CppParserSkipReason {
+    pub category: CppParserSkipCategory,
+    pub message: String,
+}
+
+impl CppParserSkipReason {
+    pub fn new(category: CppParserSkipCategory, message: impl Into<String>) -> Self {
+        CppParserSkipReason {
+            category,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CppParserSkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Fail for CppParserSkipReason {}
+
+/// Diagnostic message reported by clang
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CppParserDiagnostic {
+    /// Severity of the diagnostic (e.g. `warning`)
+    pub severity: String,
+    pub message: String,
+    pub location: Option<CppOriginLocation>,
+}
+
+/// C++ declaration that the parser failed to process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CppSkippedDeclaration {
+    /// Kind of the declaration (e.g. `function` or `class`)
+    pub kind: String,
+    /// Full name of the declaration
+    pub name: String,
+    pub location: Option<CppOriginLocation>,
+    pub category: CppParserSkipCategory,
+    /// Full error message
+    pub message: String,
+    /// clang diagnostics reported within the declaration
+    pub diagnostics: Vec<CppParserDiagnostic>,
+}
+
+/// Summary of problems encountered by the parser
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CppParserReport {
+    /// Warnings reported by clang
+    pub diagnostics: Vec<CppParserDiagnostic>,
+    /// Declarations that were not added to the database
+    pub skipped_declarations: Vec<CppSkippedDeclaration>,
+}
+
+fn format_location(location: &Option<CppOriginLocation>) -> String {
+    match location {
+        Some(location) => format!(
+            "{}:{}:{}",
+            location.include_file_path, location.line, location.column
+        ),
+        None => "unknown location".to_string(),
+    }
+}
+
+impl CppParserReport {
+    /// Formats the report as a Markdown document.
+    pub fn to_markdown(&self) -> String {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+        for declaration in &self.skipped_declarations {
+            groups
+                .entry(declaration.category)
+                .or_default()
+                .push(declaration);
+        }
+
+        let mut output = "# C++ parser report\n\n".to_string();
+        output += "| Category | Skipped declarations |\n| --- | --- |\n";
+        for (category, declarations) in &groups {
+            output += &format!("| {} | {} |\n", category.caption(), declarations.len());
+        }
+        output += &format!("| **total** | {} |\n", self.skipped_declarations.len());
+
+        for (category, declarations) in &groups {
+            output += &format!("\n## {}\n\n", category.caption());
+            for declaration in declarations {
+                output += &format!(
+                    "- {} `{}` ({}): {}\n",
+                    declaration.kind,
+                    declaration.name,
+                    format_location(&declaration.location),
+                    declaration.message
+                );
+                for diagnostic in &declaration.diagnostics {
+                    output += &format!(
+                        "    - clang {}: {}\n",
+                        diagnostic.severity, diagnostic.message
+                    );
+                }
+            }
+        }
+
+        if !self.diagnostics.is_empty() {
+            output += "\n## clang diagnostics\n\n";
+            for diagnostic in &self.diagnostics {
+                output += &format!(
+                    "- {} ({}): {}\n",
+                    diagnostic.severity,
+                    format_location(&diagnostic.location),
+                    diagnostic.message
+                );
+            }
+        }
+        output
+    }
+
+    /// Writes the report to `<name>.json` and `<name>.md` files in `dir`.
+    pub fn save(&self, dir: &Path, name: &str) -> Result<()> {
+        create_dir_all(dir)?;
+        save_json(dir.join(format!("{}.json", name)), self, None)?;
+        let mut file = create_file(dir.join(format!("{}.md", name)))?;
+        write!(file, "{}", self.to_markdown())?;
+        Ok(())
+    }
+}
+
+#[test]
+fn report_to_markdown() {
+    let location = CppOriginLocation {
+        include_file_path: "lib.h".to_string(),
+        line: 4,
+        column: 2,
+    };
+    let report = CppParserReport {
+        diagnostics: vec![CppParserDiagnostic {
+            severity: "warning".to_string(),
+            message: "unused parameter".to_string(),
+            location: Some(location.clone()),
+        }],
+        skipped_declarations: vec![CppSkippedDeclaration {
+            kind: "function".to_string(),
+            name: "f".to_string(),
+            location: Some(location),
+            category: CppParserSkipCategory::PrivateType,
+            message: "Type uses private class (A::B)".to_string(),
+            diagnostics: Vec::new(),
+        }],
+    };
+    assert_eq!(
+        report.to_markdown(),
+        "# C++ parser report\n\n\
+         | Category | Skipped declarations |\n| --- | --- |\n\
+         | private type | 1 |\n\
+         | **total** | 1 |\n\n\
+         ## private type\n\n\
+         - function `f` (lib.h:4:2): Type uses private class (A::B)\n\n\
+         ## clang diagnostics\n\n\
+         - warning (lib.h:4:2): unused parameter\n"
+    );
+}
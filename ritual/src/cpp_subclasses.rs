@@ -0,0 +1,513 @@
+//! Generates C++ subclasses that allow overriding virtual methods
+//! of the classes selected with `Config::add_subclassable_class`.
+
+use crate::cpp_data::{CppPath, CppTypeDeclarationKind, CppVisibility};
+use crate::cpp_ffi_data::{CppFfiItem, CppSubclass, CppSubclassFunction, CppToFfiTypeConversion};
+use crate::cpp_ffi_generator::{ffi_type, FfiNameProvider};
+use crate::cpp_function::CppFunction;
+use crate::cpp_type::{CppFunctionPointerType, CppPointerLikeTypeKind, CppType, CppTypeRole};
+use crate::processor::ProcessorData;
+use itertools::Itertools;
+use log::{debug, trace};
+use ritual_common::errors::{bail, format_err, print_trace, Result};
+use ritual_common::string_utils::CaseOperations;
+use ritual_common::utils::MapIfOk;
+use std::iter::once;
+
+/// Returns `class` and all its direct and indirect base classes.
+/// Closer bases come first.
+fn class_and_bases(data: &ProcessorData<'_>, class: &CppPath) -> Vec<CppPath> {
+    let mut result = vec![class.clone()];
+    let mut index = 0;
+    while index < result.len() {
+        let bases = data
+            .db
+            .all_cpp_items()
+            .filter_map(|item| item.item.as_base_ref())
+            .filter(|base| base.derived_class_type == result[index])
+            .sorted_by_key(|base| base.base_index)
+            .map(|base| base.base_class_type.clone())
+            .collect_vec();
+        for base in bases {
+            if !result.contains(&base) {
+                result.push(base);
+            }
+        }
+        index += 1;
+    }
+    result
+}
+
+/// Returns true if `function` is a move constructor.
+fn is_move_constructor(function: &CppFunction) -> bool {
+    if !function.is_constructor() || function.arguments.len() != 1 {
+        return false;
+    }
+    match (&function.arguments[0].argument_type, function.class_path()) {
+        (
+            CppType::PointerLike {
+                kind: CppPointerLikeTypeKind::RValueReference,
+                target,
+                ..
+            },
+            Ok(class_path),
+        ) => **target == CppType::Class(class_path),
+        _ => false,
+    }
+}
+
+/// Converts signature of a virtual method to the types used by the callback.
+fn subclass_function(
+    function: &CppFunction,
+    class_path: &CppPath,
+    name: String,
+) -> Result<CppSubclassFunction> {
+    if function.allows_variadic_arguments {
+        bail!("variadic arguments are not supported");
+    }
+    let arguments = function
+        .arguments
+        .iter()
+        .map_if_ok(|arg| ffi_type(&arg.argument_type, CppTypeRole::NotReturnType))?;
+    for arg in &arguments {
+        match arg.conversion() {
+            CppToFfiTypeConversion::NoChange
+            | CppToFfiTypeConversion::ValueToPointer { .. }
+            | CppToFfiTypeConversion::ReferenceToPointer
+            | CppToFfiTypeConversion::QFlagsToInt => {}
+            _ => bail!(
+                "unsupported argument type: {}",
+                arg.original_type().to_cpp_pseudo_code()
+            ),
+        }
+        if let CppType::FunctionPointer(_) = arg.original_type() {
+            bail!("function pointer arguments are not supported");
+        }
+    }
+    let return_type = ffi_type(&function.return_type, CppTypeRole::ReturnType)?;
+    match return_type.conversion() {
+        CppToFfiTypeConversion::NoChange
+        | CppToFfiTypeConversion::ValueToPointer { .. }
+        | CppToFfiTypeConversion::ReferenceToPointer
+        | CppToFfiTypeConversion::QFlagsToInt => {}
+        _ => bail!(
+            "unsupported return type: {}",
+            function.return_type.to_cpp_pseudo_code()
+        ),
+    }
+    if let CppType::FunctionPointer(_) = return_type.original_type() {
+        bail!("function pointer return type is not supported");
+    }
+
+    let is_const = function.member.as_ref().map_or(false, |m| m.is_const);
+    let callback_arguments = once(CppType::new_pointer(false, CppType::Void))
+        .chain(once(CppType::new_pointer(
+            is_const,
+            CppType::Class(class_path.clone()),
+        )))
+        .chain(arguments.iter().map(|arg| arg.ffi_type().clone()))
+        .collect();
+    let callback_type = CppFunctionPointerType {
+        return_type: Box::new(return_type.ffi_type().clone()),
+        arguments: callback_arguments,
+        allows_variadic_arguments: false,
+    };
+
+    Ok(CppSubclassFunction {
+        function: function.clone(),
+        name,
+        arguments,
+        return_type,
+        callback_type,
+    })
+}
+
+/// Generates a subclass of `base_class` with callbacks
+/// for all supported virtual methods.
+fn generate_subclass(
+    data: &ProcessorData<'_>,
+    base_class: &CppPath,
+    name_provider: &mut FfiNameProvider,
+) -> Result<CppSubclass> {
+    let is_class = data
+        .db
+        .all_cpp_items()
+        .filter_map(|item| item.item.as_type_ref())
+        .any(|t| &t.path == base_class && t.kind == CppTypeDeclarationKind::Class);
+    if !is_class {
+        bail!("class not found");
+    }
+    let is_template = base_class.items().iter().any(|item| {
+        item.template_arguments.as_ref().map_or(false, |args| {
+            args.iter().any(|arg| arg.is_template_parameter())
+        })
+    });
+    if is_template {
+        bail!("class templates can't be subclassed");
+    }
+
+    let class_path = name_provider.create_path(&format!("subclass_{}", base_class.ascii_caption()));
+
+    let constructors = data
+        .db
+        .all_cpp_items()
+        .filter_map(|item| item.item.as_function_ref())
+        .filter(|f| {
+            f.is_constructor()
+                && f.class_path().ok().as_ref() == Some(base_class)
+                && !f.is_private()
+                && !f.is_deleted
+                && !f.is_copy_constructor()
+                && !is_move_constructor(f)
+        })
+        .cloned()
+        .collect_vec();
+    if constructors.is_empty() {
+        bail!("no accessible constructors");
+    }
+
+    let mut seen_signatures = Vec::new();
+    let mut names = Vec::new();
+    let mut functions = Vec::new();
+    for class in class_and_bases(data, base_class) {
+        let class_functions = data
+            .db
+            .all_cpp_items()
+            .filter_map(|item| item.item.as_function_ref())
+            .filter(|f| f.is_virtual() && f.class_path().ok().as_ref() == Some(&class))
+            .cloned()
+            .collect_vec();
+        for function in class_functions {
+            let member = function
+                .member
+                .as_ref()
+                .expect("virtual method must be a member");
+            if function.is_destructor() {
+                continue;
+            }
+            let signature = (
+                function.path.last().clone(),
+                function
+                    .arguments
+                    .iter()
+                    .map(|arg| arg.argument_type.clone())
+                    .collect_vec(),
+                member.is_const,
+            );
+            // the method is already overridden in a more derived class
+            if seen_signatures.contains(&signature) {
+                continue;
+            }
+            seen_signatures.push(signature);
+
+            if function.is_final() {
+                continue;
+            }
+            // private methods can be overridden, but the base implementation
+            // can't be called, so they are only used if they are pure virtual
+            if member.visibility == CppVisibility::Private && !member.is_pure_virtual {
+                continue;
+            }
+            let result = if function.is_signal() || function.is_operator() {
+                Err(format_err!("signals and operators are not supported"))
+            } else if function.path.last().template_arguments.is_some() {
+                Err(format_err!("template methods are not supported"))
+            } else if member.is_volatile {
+                Err(format_err!("volatile methods are not supported"))
+            } else {
+                let snake_name = function.path.last().name.to_snake_case();
+                let mut name = snake_name.clone();
+                let mut num = 1;
+                while names.contains(&name) {
+                    num += 1;
+                    name = format!("{}_{}", snake_name, num);
+                }
+                subclass_function(&function, &class_path, name)
+            };
+            match result {
+                Ok(item) => {
+                    names.push(item.name.clone());
+                    functions.push(item);
+                }
+                Err(err) => {
+                    if member.is_pure_virtual {
+                        bail!(
+                            "can't override pure virtual method {}: {}",
+                            function.short_text(),
+                            err
+                        );
+                    }
+                    trace!("skipping virtual method {}: {}", function.short_text(), err);
+                }
+            }
+        }
+    }
+
+    Ok(CppSubclass {
+        base_class: base_class.clone(),
+        class_path,
+        constructors,
+        functions,
+    })
+}
+
+pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    let config = data.config;
+    let mut name_provider = FfiNameProvider::new(data);
+    for base_class in config.subclassable_classes() {
+        let found = data
+            .db
+            .all_ffi_items()
+            .filter_map(|item| item.item.as_subclass_ref())
+            .any(|item| &item.base_class == base_class);
+        if found {
+            trace!(
+                "subclass already exists: {}",
+                base_class.to_cpp_pseudo_code()
+            );
+            continue;
+        }
+        match generate_subclass(data, base_class, &mut name_provider) {
+            Ok(subclass) => {
+                let id = data.db.add_ffi_item(None, CppFfiItem::Subclass(subclass))?;
+                if id.is_some() {
+                    trace!("adding subclass of {}", base_class.to_cpp_pseudo_code());
+                }
+            }
+            Err(err) => {
+                debug!(
+                    "failed to add subclass of {}: {}",
+                    base_class.to_cpp_pseudo_code(),
+                    err
+                );
+                print_trace(&err, Some(log::Level::Trace));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn subclass_function_callback_type() {
+    use crate::cpp_function::{CppFunctionArgument, CppFunctionKind, CppFunctionMemberData};
+    use crate::cpp_type::CppBuiltInNumericType;
+
+    let index_type =
+        CppType::new_reference(true, CppType::Class(CppPath::from_good_str("QModelIndex")));
+    let function = CppFunction {
+        path: CppPath::from_good_str("QAbstractItemModel::rowCount"),
+        member: Some(CppFunctionMemberData {
+            kind: CppFunctionKind::Regular,
+            is_virtual: true,
+            is_pure_virtual: true,
+            is_const: true,
+            is_volatile: false,
+            is_static: false,
+            visibility: CppVisibility::Public,
+            is_signal: false,
+            is_slot: false,
+            is_final: false,
+            is_override: false,
+        }),
+        operator: None,
+        return_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        arguments: vec![CppFunctionArgument {
+            name: "parent".to_string(),
+            argument_type: index_type,
+            has_default_value: true,
+            default_value: None,
+            type_alias: None,
+        }],
+        allows_variadic_arguments: false,
+        cast: None,
+        declaration_code: None,
+        is_deleted: false,
+        is_noexcept: false,
+        deprecation: None,
+        return_type_alias: None,
+    };
+    let class_path = CppPath::from_good_str("Subclass");
+    let item = subclass_function(&function, &class_path, "row_count".to_string()).unwrap();
+    assert_eq!(item.name, "row_count");
+    assert_eq!(
+        item.arguments[0].conversion(),
+        &CppToFfiTypeConversion::ReferenceToPointer
+    );
+    assert_eq!(
+        item.callback_type.arguments,
+        vec![
+            CppType::new_pointer(false, CppType::Void),
+            CppType::new_pointer(true, CppType::Class(class_path)),
+            CppType::new_pointer(true, CppType::Class(CppPath::from_good_str("QModelIndex"))),
+        ]
+    );
+    assert_eq!(
+        *item.callback_type.return_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+    );
+}
+
+#[test]
+fn subclass_code() {
+    use crate::cpp_code_generator;
+    use crate::cpp_data::CppPathItem;
+    use crate::cpp_function::{CppFunctionArgument, CppFunctionKind, CppFunctionMemberData};
+    use crate::cpp_type::CppBuiltInNumericType;
+    use crate::database::{Database, DatabaseClient, IndexedDatabase};
+    use ritual_common::ReadOnly;
+    use std::path::PathBuf;
+
+    let method = |name: &str,
+                  return_type: CppType,
+                  arguments: Vec<CppType>,
+                  is_const: bool,
+                  is_pure_virtual: bool| CppFunction {
+        path: CppPath::from_good_str(&format!("Listener::{}", name)),
+        member: Some(CppFunctionMemberData {
+            kind: if name == "Listener" {
+                CppFunctionKind::Constructor
+            } else {
+                CppFunctionKind::Regular
+            },
+            is_virtual: name != "Listener",
+            is_pure_virtual,
+            is_const,
+            is_volatile: false,
+            is_static: false,
+            visibility: CppVisibility::Public,
+            is_signal: false,
+            is_slot: false,
+            is_final: false,
+            is_override: false,
+        }),
+        operator: None,
+        return_type,
+        arguments: arguments
+            .into_iter()
+            .enumerate()
+            .map(|(index, argument_type)| CppFunctionArgument {
+                name: format!("a{}", index),
+                argument_type,
+                has_default_value: false,
+                default_value: None,
+                type_alias: None,
+            })
+            .collect(),
+        allows_variadic_arguments: false,
+        cast: None,
+        declaration_code: None,
+        is_deleted: false,
+        is_noexcept: false,
+        deprecation: None,
+        return_type_alias: None,
+    };
+
+    let class_path = CppPath::from_good_str("ListenerSubclass");
+    let qflags = CppType::Class(CppPath::from_item(CppPathItem {
+        name: "QFlags".into(),
+        template_arguments: Some(vec![CppType::Enum {
+            path: CppPath::from_good_str("ns::Flag"),
+        }]),
+    }));
+    let functions = vec![
+        method(
+            "count",
+            CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            vec![],
+            true,
+            true,
+        ),
+        method(
+            "notify",
+            CppType::Void,
+            vec![CppType::new_reference(
+                true,
+                CppType::Class(CppPath::from_good_str("QString")),
+            )],
+            false,
+            false,
+        ),
+        method("flags", qflags, vec![], true, false),
+        method(
+            "current",
+            CppType::new_reference(false, CppType::Class(CppPath::from_good_str("Item"))),
+            vec![],
+            false,
+            false,
+        ),
+    ];
+    let subclass = CppSubclass {
+        base_class: CppPath::from_good_str("Listener"),
+        class_path: class_path.clone(),
+        constructors: vec![method("Listener", CppType::Void, vec![], false, false)],
+        functions: functions
+            .iter()
+            .map(|f| subclass_function(f, &class_path, f.path.last().name.clone()).unwrap())
+            .collect(),
+    };
+
+    let db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("subclass_code".into()),
+            PathBuf::from("subclass_code"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let code = cpp_code_generator::subclass(&db, &subclass).unwrap();
+
+    assert!(code.contains("class ListenerSubclass : public Listener {"));
+    assert!(code.contains("    ListenerSubclass() : Listener() {}"));
+    // the destructor deletes the Rust object
+    assert!(code.contains(
+        "    ~ListenerSubclass() {\n        if (m_deleter) {\n            m_deleter(m_data);"
+    ));
+    // the Rust object is never replaced
+    assert!(code.contains(
+        "    bool set_data(void* data, void (*deleter)(void*)) noexcept {\n        \
+         if (m_deleter) {\n            return false;\n        }"
+    ));
+
+    // callback table
+    assert!(
+        code.contains("    int (*m_count_callback)(void *, ListenerSubclass const *) = nullptr;")
+    );
+    assert!(code.contains(
+        "    void (*m_notify_callback)(void *, ListenerSubclass *, QString const *) = nullptr;"
+    ));
+    assert!(code.contains(
+        "    void set_count_callback(int (*callback)(void *, ListenerSubclass const *)) noexcept {\n        \
+         m_count_callback = callback;\n    }"
+    ));
+
+    // pure virtual method has no base implementation to fall back to
+    assert!(code.contains(
+        "    int count() const override {\n        if (m_count_callback) {\n            \
+         return m_count_callback(m_data, this);\n        }\n        std::abort();\n    }"
+    ));
+    assert!(!code.contains("base_count"));
+
+    // other methods fall back to the base implementation
+    assert!(code.contains(
+        "    void notify(QString const & arg0) override {\n        if (m_notify_callback) {\n            \
+         return m_notify_callback(m_data, this, &arg0);\n        }\n        \
+         return Listener::notify(arg0);\n    }"
+    ));
+    assert!(code.contains(
+        "    void base_notify(QString const & arg0) {\n        return Listener::notify(arg0);\n    }"
+    ));
+
+    // const method returning QFlags
+    assert!(code.contains(
+        "    QFlags< ns::Flag > flags() const override {\n        if (m_flags_callback) {\n            \
+         return QFlags< ns::Flag >(m_flags_callback(m_data, this));\n        }\n        \
+         return Listener::flags();\n    }"
+    ));
+    assert!(code.contains("    QFlags< ns::Flag > base_flags() const {"));
+
+    // method returning a reference
+    assert!(code.contains(
+        "    Item & current() override {\n        if (m_current_callback) {\n            \
+         return *m_current_callback(m_data, this);\n        }\n        \
+         return Listener::current();\n    }"
+    ));
+}
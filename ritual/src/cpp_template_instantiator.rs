@@ -1,11 +1,12 @@
 use crate::cpp_data::{CppItem, CppPath, CppPathItem, CppTypeDeclaration};
 use crate::cpp_function::{CppFunction, CppFunctionArgument, CppOperator};
-use crate::cpp_type::CppType;
+use crate::cpp_type::{CppTemplateParameter, CppType};
 use crate::database::{DatabaseClient, ItemWithSource};
 use crate::processor::ProcessorData;
+use itertools::Itertools;
 use log::{debug, trace};
-use ritual_common::errors::{bail, err_msg, Result};
-use ritual_common::utils::MapIfOk;
+use ritual_common::errors::{bail, Result};
+use std::iter::once;
 
 /// Returns true if `type1` is a known template instantiation.
 fn check_template_type(data: &ProcessorData<'_>, type1: &CppType) -> Result<()> {
@@ -48,7 +49,9 @@ pub fn instantiate_function(
         new_method.arguments.push(CppFunctionArgument {
             name: arg.name.clone(),
             has_default_value: arg.has_default_value,
+            default_value: arg.default_value.clone(),
             argument_type: arg.argument_type.instantiate(nested_level, arguments)?,
+            type_alias: arg.type_alias.clone(),
         });
     }
     new_method.return_type = function.return_type.instantiate(nested_level, arguments)?;
@@ -101,63 +104,232 @@ pub fn instantiate_function(
 // TODO: instantiations of QObject::findChild and QObject::findChildren should be available
 
 #[derive(Debug)]
-struct Substitution<'a> {
+struct Substitution {
     nested_level: usize,
-    arguments: &'a [CppType],
+    arguments: Vec<CppType>,
 }
 
-fn find_suitable_template_arguments<'a>(
+/// Returns the first template parameter found within `type1`.
+fn find_template_parameter(type1: &CppType) -> Option<&CppTemplateParameter> {
+    match type1 {
+        CppType::TemplateParameter(param) => Some(param),
+        CppType::PointerLike { target, .. } => find_template_parameter(target),
+        CppType::FunctionPointer(type1) => type1
+            .arguments
+            .iter()
+            .chain(once(&*type1.return_type))
+            .find_map(find_template_parameter),
+        CppType::Class(path) => path
+            .items()
+            .iter()
+            .filter_map(|item| item.template_arguments.as_ref())
+            .flatten()
+            .find_map(find_template_parameter),
+        _ => None,
+    }
+}
+
+/// Returns nested level of template parameters used in template arguments
+/// of the last item of `pattern`.
+fn pattern_nested_level(pattern: &CppPath) -> Option<usize> {
+    pattern
+        .last()
+        .template_arguments
+        .iter()
+        .flatten()
+        .find_map(find_template_parameter)
+        .map(|param| param.nested_level)
+}
+
+/// Attempts to match `type1` against `pattern`, storing types that correspond to
+/// template parameters of `pattern` at `nested_level` in `deduced` (by parameter index).
+/// Returns false if `type1` doesn't match `pattern`.
+fn deduce_template_arguments(
+    pattern: &CppType,
+    type1: &CppType,
+    nested_level: usize,
+    deduced: &mut Vec<Option<CppType>>,
+) -> bool {
+    match (pattern, type1) {
+        (CppType::TemplateParameter(param), _) if param.nested_level == nested_level => {
+            if deduced.len() <= param.index {
+                deduced.resize(param.index + 1, None);
+            }
+            match &deduced[param.index] {
+                Some(value) => value == type1,
+                None => {
+                    deduced[param.index] = Some(type1.clone());
+                    true
+                }
+            }
+        }
+        (
+            CppType::PointerLike {
+                kind,
+                is_const,
+                is_volatile,
+                target,
+            },
+            CppType::PointerLike {
+                kind: kind2,
+                is_const: is_const2,
+                is_volatile: is_volatile2,
+                target: target2,
+            },
+        ) => {
+            kind == kind2
+                && is_const == is_const2
+                && is_volatile == is_volatile2
+                && deduce_template_arguments(target, target2, nested_level, deduced)
+        }
+        (CppType::FunctionPointer(function), CppType::FunctionPointer(function2)) => {
+            function.allows_variadic_arguments == function2.allows_variadic_arguments
+                && function.arguments.len() == function2.arguments.len()
+                && deduce_template_arguments(
+                    &function.return_type,
+                    &function2.return_type,
+                    nested_level,
+                    deduced,
+                )
+                && function
+                    .arguments
+                    .iter()
+                    .zip(&function2.arguments)
+                    .all(|(a, b)| deduce_template_arguments(a, b, nested_level, deduced))
+        }
+        (CppType::Class(path), CppType::Class(path2)) => {
+            path.items().len() == path2.items().len()
+                && path.items().iter().zip(path2.items()).all(|(item, item2)| {
+                    item.name == item2.name
+                        && match (&item.template_arguments, &item2.template_arguments) {
+                            (None, None) => true,
+                            (Some(args), Some(args2)) => {
+                                args.len() == args2.len()
+                                    && args.iter().zip(args2).all(|(a, b)| {
+                                        deduce_template_arguments(a, b, nested_level, deduced)
+                                    })
+                            }
+                            _ => false,
+                        }
+                })
+        }
+        _ => pattern == type1,
+    }
+}
+
+/// Attempts to match `path` against `pattern` (a path of a generic type).
+/// Returns deduced template arguments for the pattern's template parameters at `nested_level`.
+fn match_template_pattern(
+    pattern: &CppPath,
     path: &CppPath,
-    db: &'a DatabaseClient,
-) -> Result<Vec<Substitution<'a>>> {
+    nested_level: usize,
+) -> Option<Vec<CppType>> {
+    let mut deduced = Vec::new();
+    if !deduce_template_arguments(
+        &CppType::Class(pattern.clone()),
+        &CppType::Class(path.clone()),
+        nested_level,
+        &mut deduced,
+    ) {
+        return None;
+    }
+    deduced.into_iter().collect()
+}
+
+/// Returns true if `pattern` is more specialized than `other_pattern`,
+/// i.e. any type matching `pattern` also matches `other_pattern`, but not vice versa.
+fn is_more_specialized(pattern: &CppPath, other_pattern: &CppPath) -> bool {
+    let matches = |pattern: &CppPath, path: &CppPath| {
+        pattern_nested_level(pattern).map_or(false, |nested_level| {
+            match_template_pattern(pattern, path, nested_level).is_some()
+        })
+    };
+    matches(other_pattern, pattern) && !matches(pattern, other_pattern)
+}
+
+/// Returns substitutions that produce existing template instantiations from
+/// the generic type `path` or its parent types.
+///
+/// If `path` is a class template or a template partial specialization,
+/// an instantiation is only used if `path` is the most specialized
+/// of the known patterns matching it.
+fn find_suitable_template_arguments(path: &CppPath, db: &DatabaseClient) -> Vec<Substitution> {
     let mut current_path = path.clone();
     let mut result = Vec::new();
     loop {
         if let Some(template_arguments) = &current_path.last().template_arguments {
             assert!(!template_arguments.is_empty());
-            if template_arguments.iter().all(|t| t.is_template_parameter()) {
-                let items =
-                    db.cpp_items()
-                        .filter_map(|item| item.item.as_type_ref())
-                        .filter(|type1| {
-                            type1.path.parent_parts().ok() == current_path.parent_parts().ok()
-                                && type1.path.last().name == current_path.last().name
-                                && type1.path.last().template_arguments.as_ref().map_or(
-                                    false,
-                                    |args| {
-                                        !args.iter().all(CppType::is_or_contains_template_parameter)
-                                    },
-                                )
-                        })
-                        .map_if_ok(|type1| {
-                            let nested_level =
-                                if let CppType::TemplateParameter(param) = &template_arguments[0] {
-                                    param.nested_level
-                                } else {
-                                    bail!("only template parameters can be here");
-                                };
-
-                            let arguments = type1
-                                .path
-                                .last()
-                                .template_arguments
-                                .as_ref()
-                                .ok_or_else(|| {
-                                    err_msg("template instantiation must have template arguments")
-                                })?;
-
-                            Ok(Substitution {
-                                nested_level,
-                                arguments,
+            let is_pattern = template_arguments.iter().all(|t| t.is_template_parameter())
+                || db
+                    .cpp_items()
+                    .filter_map(|item| item.item.as_type_ref())
+                    .any(|type1| {
+                        type1.path == current_path && type1.is_template_partial_specialization()
+                    });
+            let nested_level = pattern_nested_level(&current_path).filter(|_| is_pattern);
+            if let Some(nested_level) = nested_level {
+                let same_name = |type1: &CppTypeDeclaration| {
+                    type1.path.parent_parts().ok() == current_path.parent_parts().ok()
+                        && type1.path.last().name == current_path.last().name
+                };
+                let same_name_types = db
+                    .cpp_items()
+                    .filter_map(|item| item.item.as_type_ref())
+                    .filter(|type1| same_name(type1))
+                    .collect_vec();
+                let patterns = same_name_types.iter().filter(|type1| {
+                    type1
+                        .path
+                        .last()
+                        .template_arguments
+                        .iter()
+                        .flatten()
+                        .any(CppType::is_or_contains_template_parameter)
+                });
+                let instantiations = same_name_types.iter().filter(|type1| {
+                    !type1
+                        .path
+                        .last()
+                        .template_arguments
+                        .iter()
+                        .flatten()
+                        .all(CppType::is_or_contains_template_parameter)
+                });
+                for type1 in instantiations {
+                    let arguments = if let Some(arguments) =
+                        match_template_pattern(&current_path, &type1.path, nested_level)
+                    {
+                        arguments
+                    } else {
+                        continue;
+                    };
+                    let better_pattern = patterns.clone().find(|pattern| {
+                        pattern.path != current_path
+                            && is_more_specialized(&pattern.path, &current_path)
+                            && pattern_nested_level(&pattern.path).map_or(false, |level| {
+                                match_template_pattern(&pattern.path, &type1.path, level).is_some()
                             })
-                        })?;
-                result.extend(items);
+                    });
+                    if let Some(better_pattern) = better_pattern {
+                        trace!(
+                            "skipping {} for {} because {} is more specialized",
+                            type1.path.to_cpp_pseudo_code(),
+                            current_path.to_cpp_pseudo_code(),
+                            better_pattern.path.to_cpp_pseudo_code()
+                        );
+                        continue;
+                    }
+                    result.push(Substitution {
+                        nested_level,
+                        arguments,
+                    });
+                }
             }
         }
         if let Ok(p) = current_path.parent() {
             current_path = p;
         } else {
-            return Ok(result);
+            return result;
         }
     }
 }
@@ -171,7 +343,7 @@ fn instantiate_types(data: &mut ProcessorData<'_>) -> Result<()> {
             .filter_map(|item| item.filter_map(|item| item.as_type_ref()))
         {
             trace!("class: {}", type1.item.path.to_cpp_pseudo_code());
-            for substitution in find_suitable_template_arguments(&type1.item.path, data.db)? {
+            for substitution in find_suitable_template_arguments(&type1.item.path, data.db) {
                 trace!("found template instantiation: {:?}", substitution);
 
                 let new_type = CppTypeDeclaration {
@@ -179,7 +351,9 @@ fn instantiate_types(data: &mut ProcessorData<'_>) -> Result<()> {
                     path: type1
                         .item
                         .path
-                        .instantiate(substitution.nested_level, substitution.arguments)?,
+                        .instantiate(substitution.nested_level, &substitution.arguments)?,
+                    deprecation: type1.item.deprecation.clone(),
+                    anonymous_member: None,
                 };
 
                 if data
@@ -225,14 +399,14 @@ fn instantiate_functions(data: &mut ProcessorData<'_>) -> Result<()> {
                 _ => continue,
             };
 
-            for substitution in find_suitable_template_arguments(path, data.db)? {
+            for substitution in find_suitable_template_arguments(path, data.db) {
                 trace!("method: {}", function.short_text());
                 trace!("found template instantiation: {:?}", substitution);
 
                 match instantiate_function(
                     function,
                     substitution.nested_level,
-                    substitution.arguments,
+                    &substitution.arguments,
                 ) {
                     Ok(method) => {
                         let mut ok = true;
@@ -320,20 +494,30 @@ pub fn find_template_instantiations(data: &mut ProcessorData<'_>) -> Result<()>
         }
     }
     for item in result {
+        let arguments_count = item
+            .last()
+            .template_arguments
+            .as_ref()
+            .map_or(0, |args| args.len());
+        // type and non-type template parameters are both represented as
+        // `CppType::TemplateParameter` in the original type, so instantiations
+        // like `std::array<int, 4>` are matched by name and arguments count
         let original_type = data
             .db
             .all_cpp_items()
             .filter_map(|x| x.filter_map(|item| item.as_type_ref()))
             .find(|t| {
                 let t = &t.item;
-                t.path.parent_parts().ok() == item.parent_parts().ok()
+                !t.is_template_partial_specialization()
+                    && t.path.parent_parts().ok() == item.parent_parts().ok()
                     && t.path.last().name == item.last().name
                     && t.path
                         .last()
                         .template_arguments
                         .as_ref()
                         .map_or(false, |args| {
-                            args.iter().all(CppType::is_template_parameter)
+                            args.len() == arguments_count
+                                && args.iter().all(CppType::is_template_parameter)
                         })
             });
         if let Some(original_type) = original_type {
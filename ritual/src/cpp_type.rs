@@ -3,6 +3,7 @@
 use crate::cpp_data::CppPath;
 use ritual_common::errors::{bail, Result};
 use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
@@ -58,6 +59,25 @@ pub struct CppFunctionPointerType {
     pub allows_variadic_arguments: bool,
 }
 
+impl CppFunctionPointerType {
+    /// Returns C++ code of the function type (e.g. `int(bool)`)
+    /// as it appears in a `std::function` template argument.
+    pub fn to_cpp_function_type_code(&self) -> Result<String> {
+        if self.allows_variadic_arguments {
+            bail!("function types with variadic arguments are not supported");
+        }
+        let mut arg_texts = Vec::new();
+        for arg in &self.arguments {
+            arg_texts.push(arg.to_cpp_code(None)?);
+        }
+        Ok(format!(
+            "{}({})",
+            self.return_type.to_cpp_code(None)?,
+            arg_texts.join(", ")
+        ))
+    }
+}
+
 /// Information about a numeric C++ type that is
 /// guaranteed to be the same on all platforms,
 /// e.g. `uint32_t`.
@@ -88,6 +108,38 @@ pub struct CppTemplateParameter {
     pub name: String,
 }
 
+/// Value of a non-type template argument, like `4` in `std::array<int, 4>`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub enum CppTemplateArgumentValue {
+    /// Integral or boolean value
+    Integer(i64),
+    /// Enumerator, identified by its full path
+    EnumValue(CppPath),
+}
+
+impl CppTemplateArgumentValue {
+    /// Returns C++ code representing this value.
+    pub fn to_cpp_code(&self) -> Result<String> {
+        match self {
+            CppTemplateArgumentValue::Integer(value) => Ok(value.to_string()),
+            CppTemplateArgumentValue::EnumValue(path) => path.to_cpp_code(),
+        }
+    }
+
+    pub fn ascii_caption(&self) -> String {
+        match self {
+            CppTemplateArgumentValue::Integer(value) => {
+                if *value < 0 {
+                    format!("minus_{}", value.unsigned_abs())
+                } else {
+                    value.to_string()
+                }
+            }
+            CppTemplateArgumentValue::EnumValue(path) => path.ascii_caption(),
+        }
+    }
+}
+
 /// Base C++ type. `CppType` can add indirection
 /// and constness to `CppTypeBase`, but otherwise
 /// this enum lists all supported types.
@@ -113,11 +165,16 @@ pub enum CppType {
     /// Template parameter, like `"T"` anywhere inside
     /// `QVector<T>` declaration
     TemplateParameter(CppTemplateParameter),
+    /// Value of a non-type template parameter, like `4` in `std::array<int, 4>`.
+    /// Only valid as a template argument.
+    TemplateArgumentValue(CppTemplateArgumentValue),
     /// Function pointer type
     FunctionPointer(CppFunctionPointerType),
     PointerLike {
         kind: CppPointerLikeTypeKind,
         is_const: bool,
+        /// True if the target type is `volatile`
+        is_volatile: bool,
         target: Box<CppType>,
     },
 }
@@ -193,6 +250,7 @@ impl CppType {
         CppType::PointerLike {
             kind: CppPointerLikeTypeKind::Pointer,
             is_const,
+            is_volatile: false,
             target: Box::new(target),
         }
     }
@@ -201,10 +259,22 @@ impl CppType {
         CppType::PointerLike {
             kind: CppPointerLikeTypeKind::Reference,
             is_const,
+            is_volatile: false,
             target: Box::new(target),
         }
     }
 
+    /// Returns a copy of this pointer-like type with `volatile` target type.
+    pub fn with_volatile_target(&self) -> Result<Self> {
+        let mut r = self.clone();
+        if let CppType::PointerLike { is_volatile, .. } = &mut r {
+            *is_volatile = true;
+            Ok(r)
+        } else {
+            bail!("not a pointer like type");
+        }
+    }
+
     /// Returns true if this is `void` type.
     pub fn is_void(&self) -> bool {
         matches!(self, CppType::Void)
@@ -217,11 +287,27 @@ impl CppType {
     pub fn is_template_parameter(&self) -> bool {
         matches!(self, CppType::TemplateParameter { .. })
     }
+    /// Returns true if this is a value of a non-type template parameter.
+    pub fn is_template_argument_value(&self) -> bool {
+        matches!(self, CppType::TemplateArgumentValue(..))
+    }
     /// Returns true if this is a function pointer.
     pub fn is_function_pointer(&self) -> bool {
         matches!(self, CppType::FunctionPointer(..))
     }
 
+    /// Returns true if this is an unsigned integer type.
+    pub fn is_unsigned_integer(&self) -> bool {
+        match self {
+            CppType::BuiltInNumeric(t) => t.is_unsigned_integer(),
+            CppType::SpecificNumeric(CppSpecificNumericType { kind, .. }) => {
+                *kind == CppSpecificNumericTypeKind::Integer { is_signed: false }
+            }
+            CppType::PointerSizedInteger { is_signed, .. } => !*is_signed,
+            _ => false,
+        }
+    }
+
     pub fn is_pointer(&self) -> bool {
         match self {
             CppType::PointerLike { kind, .. } => *kind == CppPointerLikeTypeKind::Pointer,
@@ -229,6 +315,46 @@ impl CppType {
         }
     }
 
+    /// Returns true if `value` is a valid value of this integer type
+    /// on all supported platforms. Returns false if this is not an integer type.
+    pub fn can_hold_integer(&self, value: i64) -> bool {
+        use self::CppBuiltInNumericType::*;
+
+        let (bits, is_signed) = match self {
+            CppType::BuiltInNumeric(numeric) => match numeric {
+                Bool => return true,
+                // signedness of `char` depends on the platform
+                Char => (7, false),
+                SChar => (8, true),
+                UChar => (8, false),
+                // `wchar_t` is 16-bit unsigned on Windows and 32-bit signed elsewhere
+                WChar | Char16 | UShort => (16, false),
+                Short => (16, true),
+                Char32 | UInt => (32, false),
+                Int => (32, true),
+                // `long` is 32-bit on Windows
+                Long => (32, true),
+                ULong => (32, false),
+                LongLong | Int128 => (64, true),
+                ULongLong | UInt128 => (64, false),
+                Float | Double | LongDouble => return false,
+            },
+            CppType::SpecificNumeric(CppSpecificNumericType {
+                bits,
+                kind: CppSpecificNumericTypeKind::Integer { is_signed },
+                ..
+            }) => ((*bits).min(64), *is_signed),
+            _ => return false,
+        };
+        let value = i128::from(value);
+        if is_signed {
+            let max = (1_i128 << (bits - 1)) - 1;
+            value >= -max - 1 && value <= max
+        } else {
+            value >= 0 && value < (1_i128 << bits)
+        }
+    }
+
     /// Returns true if this is a template parameter or a type that
     /// contains any template parameters.
     pub fn is_or_contains_template_parameter(&self) -> bool {
@@ -281,6 +407,30 @@ impl CppType {
         }
     }
 
+    /// Returns true if this type or any type it contains (pointer targets,
+    /// template arguments, function pointer arguments) refers to a path from `paths`.
+    pub fn refers_to_any(&self, paths: &HashSet<CppPath>) -> bool {
+        match self {
+            CppType::SpecificNumeric(CppSpecificNumericType { path, .. })
+            | CppType::PointerSizedInteger { path, .. }
+            | CppType::Enum { path } => paths.contains(path),
+            CppType::Class(path) => {
+                paths.contains(path)
+                    || path.items().iter().any(|item| {
+                        item.template_arguments.as_ref().map_or(false, |args| {
+                            args.iter().any(|arg| arg.refers_to_any(paths))
+                        })
+                    })
+            }
+            CppType::PointerLike { target, .. } => target.refers_to_any(paths),
+            CppType::FunctionPointer(type1) => {
+                type1.return_type.refers_to_any(paths)
+                    || type1.arguments.iter().any(|arg| arg.refers_to_any(paths))
+            }
+            _ => false,
+        }
+    }
+
     /// Returns C++ code representing this type.
     pub fn to_cpp_code(&self, function_pointer_inner_text: Option<&str>) -> Result<String> {
         if !self.is_function_pointer() && function_pointer_inner_text.is_some() {
@@ -296,6 +446,7 @@ impl CppType {
             CppType::TemplateParameter { .. } => {
                 bail!("template parameters are not allowed in C++ code generator");
             }
+            CppType::TemplateArgumentValue(value) => value.to_cpp_code(),
             CppType::FunctionPointer(CppFunctionPointerType {
                 return_type,
                 arguments,
@@ -322,11 +473,13 @@ impl CppType {
             CppType::PointerLike {
                 kind,
                 is_const,
+                is_volatile,
                 target,
             } => Ok(format!(
-                "{}{} {}",
+                "{}{}{} {}",
                 target.to_cpp_code(function_pointer_inner_text)?,
                 if *is_const { " const" } else { "" },
+                if *is_volatile { " volatile" } else { "" },
                 match *kind {
                     CppPointerLikeTypeKind::Pointer => "*",
                     CppPointerLikeTypeKind::Reference => "&",
@@ -352,11 +505,13 @@ impl CppType {
             CppType::PointerLike {
                 kind,
                 is_const,
+                is_volatile,
                 target,
             } => {
                 return format!(
-                    "{}{}{}",
+                    "{}{}{}{}",
                     if *is_const { "const " } else { "" },
+                    if *is_volatile { "volatile " } else { "" },
                     target.to_cpp_pseudo_code(),
                     match *kind {
                         CppPointerLikeTypeKind::Pointer => "*",
@@ -380,15 +535,18 @@ impl CppType {
             | CppType::Enum { path }
             | CppType::Class(path) => path.ascii_caption(),
             CppType::TemplateParameter(param) => param.name.to_string(),
+            CppType::TemplateArgumentValue(value) => value.ascii_caption(),
             CppType::FunctionPointer(_) => "fn".into(),
             CppType::PointerLike {
                 kind,
                 is_const,
+                is_volatile,
                 target,
             } => format!(
-                "{}{}{}",
+                "{}{}{}{}",
                 target.ascii_caption(),
                 if *is_const { "_const" } else { "" },
+                if *is_volatile { "_volatile" } else { "" },
                 match *kind {
                     CppPointerLikeTypeKind::Pointer => "_ptr",
                     CppPointerLikeTypeKind::Reference => "_ref",
@@ -414,6 +572,14 @@ impl CppType {
         }
     }
 
+    pub fn pointer_like_is_volatile(&self) -> Result<bool> {
+        if let CppType::PointerLike { is_volatile, .. } = self {
+            Ok(*is_volatile)
+        } else {
+            bail!("not a pointer like type");
+        }
+    }
+
     pub fn as_function_pointer(&self) -> Option<&CppFunctionPointerType> {
         if let CppType::FunctionPointer(t) = self {
             Some(t)
@@ -442,7 +608,38 @@ pub fn is_qflags(path: &CppPath) -> bool {
             .map_or(false, |args| args.len() == 1)
 }
 
+/// Returns the function type of `path` if it's a `std::function` class.
+/// The parser represents the function type as a function pointer type.
+pub fn std_function_type(path: &CppPath) -> Option<&CppFunctionPointerType> {
+    let items = path.items();
+    if items.len() != 2 || items[0].name != "std" || items[1].name != "function" {
+        return None;
+    }
+    match &items[1].template_arguments {
+        Some(args) if args.len() == 1 => args[0].as_function_pointer(),
+        _ => None,
+    }
+}
+
 impl CppType {
+    /// Returns the function type if this is a `std::function` class
+    /// or a const reference to it.
+    pub fn as_std_function(&self) -> Option<&CppFunctionPointerType> {
+        match self {
+            CppType::Class(path) => std_function_type(path),
+            CppType::PointerLike {
+                kind: CppPointerLikeTypeKind::Reference,
+                is_const: true,
+                target,
+                ..
+            } => match &**target {
+                CppType::Class(path) => std_function_type(path),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     pub fn contains_reference(&self) -> bool {
         if let CppType::PointerLike { kind, target, .. } = self {
             match *kind {
@@ -479,10 +676,12 @@ impl CppType {
             CppType::PointerLike {
                 kind,
                 is_const,
+                is_volatile,
                 target,
             } => Ok(CppType::PointerLike {
                 kind: kind.clone(),
                 is_const: *is_const,
+                is_volatile: *is_volatile,
                 target: Box::new(target.instantiate(nested_level, template_arguments1)?),
             }),
             _ => Ok(self.clone()),
@@ -1,7 +1,8 @@
 use crate::config::{CrateDependencyKind, CrateDependencySource};
 use crate::cpp_code_generator;
 use crate::cpp_code_generator::{
-    all_include_directives, generate_cpp_type_size_requester, write_include_directives,
+    all_include_directives, anonymous_type_aliases, extra_sources,
+    generate_cpp_type_size_requester, write_include_directives,
 };
 use crate::database::CRATE_DB_FILE_NAME;
 use crate::processor::ProcessorData;
@@ -215,11 +216,16 @@ fn generate_crate_template(data: &mut ProcessorData<'_>, output_path: &Path) ->
 }
 
 /// Generates main files and directories of the library.
+/// `sources` are names of generated source files of the library.
+/// `type_aliases` is C++ code added to the global header after the include directives.
 fn generate_c_lib_template(
     lib_name: &str,
     lib_path: &Path,
     global_header_name: &str,
+    ffi_header_name: &str,
+    sources: &[String],
     include_directives: &[PathBuf],
+    type_aliases: &str,
 ) -> Result<()> {
     let name_upper = lib_name.to_uppercase();
     let cmakelists_path = lib_path.join("CMakeLists.txt");
@@ -229,7 +235,10 @@ fn generate_c_lib_template(
         cmakelists_file,
         include_str!("../templates/c_lib/CMakeLists.txt"),
         lib_name_lowercase = lib_name,
-        lib_name_uppercase = name_upper
+        lib_name_uppercase = name_upper,
+        global_header_name = global_header_name,
+        ffi_header_name = ffi_header_name,
+        sources = sources.join(" ")
     )?;
 
     let global_header_path = lib_path.join(&global_header_name);
@@ -240,6 +249,7 @@ fn generate_c_lib_template(
         include_str!("../templates/c_lib/global.h"),
     )?;
     write_include_directives(&mut global_header_file, include_directives)?;
+    write!(global_header_file, "{}", type_aliases)?;
     Ok(())
 }
 
@@ -261,18 +271,20 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     }
     let c_lib_name = format!("{}_c", data.config.crate_properties().name());
     let global_header_name = format!("{}_global.h", c_lib_name);
+    let ffi_header_name = format!("{}_ffi.h", crate_name);
+    let mut sources =
+        cpp_code_generator::generate_cpp_files(&data.db, &c_lib_path, &global_header_name)?;
+    sources.extend(extra_sources(data.config)?);
     generate_c_lib_template(
         &c_lib_name,
         &c_lib_path,
         &global_header_name,
+        &ffi_header_name,
+        &sources,
         &all_include_directives(data.config)?,
+        &anonymous_type_aliases(&data.db)?,
     )?;
-
-    cpp_code_generator::generate_cpp_file(
-        &data.db,
-        &c_lib_path.join("file1.cpp"),
-        &global_header_name,
-    )?;
+    cpp_code_generator::generate_c_header(&data.db, &c_lib_path.join(&ffi_header_name))?;
 
     let file = create_file(c_lib_path.join("sized_types.cxx"))?;
     generate_cpp_type_size_requester(data.db, data.config.include_directives(), file)?;
@@ -294,6 +306,7 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     run_command(
         Command::new("rustfmt")
             .arg("src/ffi.in.rs")
+            .arg("src/ffi_runtime.in.rs")
             .current_dir(&output_path),
     )?;
 
@@ -314,3 +327,42 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn c_lib_template_sources() {
+    use ritual_common::file_utils::file_to_string;
+
+    let dir = tempdir::TempDir::new("test_c_lib_template").unwrap();
+    let sources = ["lib.cpp", "ns.cpp", "top_class.cpp", "extra/extra.cpp"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect_vec();
+    generate_c_lib_template(
+        "a_c",
+        dir.path(),
+        "a_c_global.h",
+        "a_ffi.h",
+        &sources,
+        &[PathBuf::from("a.h"), PathBuf::from("extra/extra.h")],
+        "",
+    )
+    .unwrap();
+
+    let cmakelists = file_to_string(dir.path().join("CMakeLists.txt")).unwrap();
+    assert!(cmakelists.contains("set(sources lib.cpp ns.cpp top_class.cpp extra/extra.cpp)\n"));
+    assert!(cmakelists.contains("add_library(a_c ${RITUAL_LIBRARY_TYPE} ${sources})\n"));
+    // the precompiled header is only used if CMake supports it,
+    // otherwise each source includes the global header
+    assert!(cmakelists.contains(
+        "if(NOT(CMAKE_VERSION VERSION_LESS 3.16) AND NOT RITUAL_DISABLE_PCH)\n    \
+         # all sources include the global header, so it's used as a shared precompiled header\n    \
+         target_precompile_headers(a_c PRIVATE a_c_global.h)\n\
+         endif()\n"
+    ));
+    assert_eq!(cmakelists.matches("target_precompile_headers").count(), 1);
+    assert!(cmakelists.contains("cmake_minimum_required(VERSION 3.0.0 FATAL_ERROR)"));
+
+    let global_header = file_to_string(dir.path().join("a_c_global.h")).unwrap();
+    assert!(global_header.contains("#include \"a.h\""));
+    assert!(global_header.contains("#include \"extra/extra.h\""));
+}
@@ -1,6 +1,7 @@
 use crate::cpp_checks::{CppChecks, CppChecksItem};
-use crate::cpp_data::{CppItem, CppPath};
+use crate::cpp_data::{CppEnumValue, CppItem, CppPath};
 use crate::cpp_ffi_data::CppFfiItem;
+use crate::cpp_parser_report::CppSkippedDeclaration;
 use crate::rust_info::RustItem;
 use crate::rust_type::RustPath;
 use log::{debug, error, info, trace, warn};
@@ -11,7 +12,7 @@ use ritual_common::string_utils::ends_with_digit;
 use ritual_common::target::LibraryTarget;
 use ritual_common::ReadOnly;
 use serde_derive::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::iter::once;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -168,6 +169,40 @@ pub struct DocItem {
     pub url: Option<String>,
     /// Absolute documentation URLs encountered in the content
     pub cross_references: Vec<String>,
+    /// Documentation comment parsed from the C++ header.
+    /// If present, it's used instead of `html`.
+    pub comment: Option<CppDocComment>,
+}
+
+/// Documentation comment (e.g. a Doxygen comment) attached to
+/// a C++ declaration. All text is stored in Markdown.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppDocComment {
+    /// Brief description (from `\brief` command or the first paragraph)
+    pub brief: Option<String>,
+    /// Other paragraphs of the description
+    pub paragraphs: Vec<String>,
+    /// Descriptions of function parameters (from `\param` commands)
+    pub params: Vec<CppDocCommentParam>,
+    /// Description of the return value (from `\return` command)
+    pub returns: Option<String>,
+}
+
+/// Description of a function parameter in a documentation comment
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppDocCommentParam {
+    pub name: String,
+    pub text: String,
+}
+
+impl CppDocComment {
+    /// Returns the brief description or the first paragraph if there is no brief description.
+    pub fn summary(&self) -> Option<&str> {
+        self.brief
+            .as_ref()
+            .or_else(|| self.paragraphs.first())
+            .map(|s| s.as_str())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -271,6 +306,31 @@ impl DatabaseItemData {
     }
 }
 
+/// Header processed by the C++ parser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CppParsedHeader {
+    /// Hash of the content of the header and its dependencies at the time it was parsed
+    pub content_hash: u64,
+    /// Files included by the header, directly or indirectly
+    #[serde(default)]
+    pub dependencies: Vec<PathBuf>,
+    /// C++ items added by the parser from declarations in this header
+    pub items: Vec<ItemId>,
+    /// Declarations of this header skipped by the parser
+    #[serde(default)]
+    pub skipped_declarations: Vec<CppSkippedDeclaration>,
+}
+
+/// Information about the previous run of the C++ parser
+/// used to avoid re-parsing unchanged headers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CppParserState {
+    /// Hash of the include directives, include paths and clang arguments
+    pub config_hash: u64,
+    /// Parsed headers, indexed by full path
+    pub headers: BTreeMap<PathBuf, CppParsedHeader>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Database {
     crate_name: Arc<String>,
@@ -278,6 +338,8 @@ pub struct Database {
     items: Vec<DbItem<DatabaseItemData>>,
     targets: Vec<LibraryTarget>,
     next_id: u32,
+    #[serde(default)]
+    cpp_parser_state: CppParserState,
 }
 
 impl Database {
@@ -288,6 +350,7 @@ impl Database {
             items: Vec::new(),
             targets: Vec::new(),
             next_id: 1,
+            cpp_parser_state: CppParserState::default(),
         }
     }
 
@@ -326,6 +389,10 @@ pub struct IndexedDatabase {
     source_id_to_index: HashMap<Option<ItemId>, Vec<usize>>,
     cpp_path_to_index: HashMap<CppPath, Vec<usize>>,
     rust_path_to_index: HashMap<RustPath, usize>,
+    /// Enum values grouped by the path of their enum
+    enum_path_to_values: HashMap<CppPath, Vec<usize>>,
+    /// Enum values grouped by their unscoped paths (see `CppEnumValue::unscoped_path`)
+    unscoped_enum_value_path_to_index: HashMap<CppPath, Vec<usize>>,
 }
 
 impl IndexedDatabase {
@@ -336,6 +403,8 @@ impl IndexedDatabase {
             source_id_to_index: HashMap::new(),
             cpp_path_to_index: HashMap::new(),
             rust_path_to_index: HashMap::new(),
+            enum_path_to_values: HashMap::new(),
+            unscoped_enum_value_path_to_index: HashMap::new(),
         };
         value.refresh();
         value
@@ -349,6 +418,8 @@ impl IndexedDatabase {
         self.source_id_to_index.clear();
         self.cpp_path_to_index.clear();
         self.rust_path_to_index.clear();
+        self.enum_path_to_values.clear();
+        self.unscoped_enum_value_path_to_index.clear();
         for (index, item) in self.db.items.iter().enumerate() {
             self.source_id_to_index
                 .entry(item.source_id.clone())
@@ -363,6 +434,22 @@ impl IndexedDatabase {
                     .or_default()
                     .push(index);
             }
+            if let Some(value) = item
+                .item
+                .as_cpp_item()
+                .and_then(|item| item.as_enum_value_ref())
+            {
+                if let Ok(enum_path) = value.path.parent() {
+                    self.enum_path_to_values
+                        .entry(enum_path)
+                        .or_default()
+                        .push(index);
+                }
+                self.unscoped_enum_value_path_to_index
+                    .entry(value.unscoped_path())
+                    .or_default()
+                    .push(index);
+            }
         }
     }
 
@@ -381,6 +468,22 @@ impl IndexedDatabase {
                 .or_default()
                 .push(index);
         }
+        if let Some(value) = item
+            .item
+            .as_cpp_item()
+            .and_then(|item| item.as_enum_value_ref())
+        {
+            if let Ok(enum_path) = value.path.parent() {
+                self.enum_path_to_values
+                    .entry(enum_path)
+                    .or_default()
+                    .push(index);
+            }
+            self.unscoped_enum_value_path_to_index
+                .entry(value.unscoped_path())
+                .or_default()
+                .push(index);
+        }
         self.db.items.push(item);
     }
 
@@ -394,6 +497,21 @@ impl IndexedDatabase {
             .flat_map(move |ids| ids.iter().map(move |&id| self.db.items[id].as_ref()))
     }
 
+    fn enum_values_by_index<'a>(
+        &'a self,
+        indexes: Option<&'a Vec<usize>>,
+    ) -> impl Iterator<Item = DbItem<&'a CppEnumValue>> {
+        indexes.into_iter().flat_map(move |ids| {
+            ids.iter().map(move |&id| {
+                self.db.items[id].as_ref().map(|item| {
+                    item.as_cpp_item()
+                        .and_then(|item| item.as_enum_value_ref())
+                        .expect("invalid db index")
+                })
+            })
+        })
+    }
+
     fn filter_by_cpp_path(&self, path: &CppPath) -> impl Iterator<Item = DbItem<&CppItem>> {
         self.cpp_path_to_index
             .get(path)
@@ -486,6 +604,18 @@ impl DatabaseClient {
         self.current_database.db.cpp_items_mut()
     }
 
+    /// Returns the item of the current database that `CppItem::is_same` considers
+    /// equal to `item`. Items with a path are looked up by the path index.
+    pub fn find_same_cpp_item(&self, item: &CppItem) -> Option<DbItem<&CppItem>> {
+        if let Some(path) = item.path() {
+            self.current_database
+                .filter_by_cpp_path(path)
+                .find(|other| other.item.is_same(item))
+        } else {
+            self.cpp_items().find(|other| other.item.is_same(item))
+        }
+    }
+
     pub fn ffi_items(&self) -> impl Iterator<Item = DbItem<&CppFfiItem>> {
         self.current_database.db.ffi_items()
     }
@@ -625,7 +755,7 @@ impl DatabaseClient {
         source_id: Option<ItemId>,
         data: CppItem,
     ) -> Result<Option<ItemId>> {
-        if self.cpp_items().any(|item| item.item.is_same(&data)) {
+        if self.find_same_cpp_item(&data).is_some() {
             self.counters.items_ignored += 1;
             return Ok(None);
         }
@@ -646,6 +776,15 @@ impl DatabaseClient {
         Ok(Some(id))
     }
 
+    pub fn cpp_parser_state(&self) -> &CppParserState {
+        &self.current_database.db.cpp_parser_state
+    }
+
+    pub fn set_cpp_parser_state(&mut self, state: CppParserState) {
+        self.is_modified = true;
+        self.current_database.db.cpp_parser_state = state;
+    }
+
     pub fn add_environment(&mut self, env: LibraryTarget) {
         if !self.current_database.db.targets.iter().any(|e| e == &env) {
             self.is_modified = true;
@@ -812,6 +951,29 @@ impl DatabaseClient {
         Some(id)
     }
 
+    /// Returns items generated directly from the item `source_id`.
+    pub fn items_from_source(
+        &self,
+        source_id: &ItemId,
+    ) -> impl Iterator<Item = DbItem<&DatabaseItemData>> {
+        self.current_database
+            .filter_by_source(&Some(source_id.clone()))
+    }
+
+    /// Returns true if any FFI items were generated from the item `source_id`.
+    pub fn has_ffi_items(&self, source_id: &ItemId) -> bool {
+        self.current_database
+            .filter_by_source(&Some(source_id.clone()))
+            .any(|item| item.item.is_ffi_item())
+    }
+
+    /// Returns true if any Rust items were generated from the item `source_id`.
+    pub fn has_rust_items(&self, source_id: &ItemId) -> bool {
+        self.current_database
+            .filter_by_source(&Some(source_id.clone()))
+            .any(|item| item.item.is_rust_item())
+    }
+
     pub fn cpp_checks(&self, source_id: &ItemId) -> Result<CppChecks> {
         let items = self
             .database(&source_id.crate_name)?
@@ -940,6 +1102,29 @@ impl DatabaseClient {
         self.all_databases().flat_map(|d| d.db.cpp_items())
     }
 
+    /// Returns values of the enum `enum_path` from the current database and its dependencies.
+    pub fn enum_values<'a>(
+        &'a self,
+        enum_path: &'a CppPath,
+    ) -> impl Iterator<Item = DbItem<&'a CppEnumValue>> {
+        self.all_databases()
+            .flat_map(move |d| d.enum_values_by_index(d.enum_path_to_values.get(enum_path)))
+    }
+
+    /// Returns enum values from the current database and its dependencies
+    /// that are accessible as `path`, i.e. `path` is their full path or
+    /// their unscoped path.
+    pub fn find_enum_values<'a>(
+        &'a self,
+        path: &'a CppPath,
+    ) -> impl Iterator<Item = DbItem<&'a CppEnumValue>> {
+        self.all_databases().flat_map(move |d| {
+            d.filter_by_cpp_path(path)
+                .filter_map(|item| item.filter_map(|item| item.as_enum_value_ref()))
+                .chain(d.enum_values_by_index(d.unscoped_enum_value_path_to_index.get(path)))
+        })
+    }
+
     pub fn all_ffi_items(&self) -> impl Iterator<Item = DbItem<&CppFfiItem>> {
         self.all_databases().flat_map(|d| d.db.ffi_items())
     }
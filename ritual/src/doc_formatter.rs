@@ -3,13 +3,15 @@
 
 #![allow(dead_code)]
 
+use crate::cpp_data::CppItem;
 use crate::cpp_ffi_data::{CppFfiFunctionKind, CppFfiItem, CppFieldAccessorType};
 use crate::cpp_type::CppType;
-use crate::database::{DatabaseClient, DbItem, DocItem};
+use crate::database::{CppDocComment, DatabaseClient, DbItem, DocItem};
 use crate::rust_code_generator::rust_type_to_code;
 use crate::rust_info::{
-    RustEnumValue, RustFunction, RustFunctionKind, RustModule, RustModuleKind, RustQtReceiverType,
-    RustSpecialModuleKind, RustStruct, RustStructKind, RustWrapperTypeKind,
+    RustConstant, RustEnumValue, RustFunction, RustFunctionKind, RustModule, RustModuleKind,
+    RustQtReceiverType, RustSpecialModuleKind, RustStruct, RustStructKind, RustTypeAlias,
+    RustWrapperTypeKind,
 };
 use itertools::Itertools;
 use ritual_common::errors::{bail, err_msg, Result};
@@ -49,6 +51,13 @@ pub fn module_doc(module: DbItem<&RustModule>, database: &DatabaseClient) -> Res
             RustSpecialModuleKind::Ops => {
                 write!(output, "Functions that provide access to C++ operators")?;
             }
+            RustSpecialModuleKind::Protected => {
+                write!(
+                    output,
+                    "Functions that provide access to protected members of C++ classes.\n\n\
+                     These members are intended to be used only by the class and its subclasses."
+                )?;
+            }
             RustSpecialModuleKind::SizedTypes => {
                 write!(
                     output,
@@ -92,14 +101,32 @@ fn first_phrase(html: &str) -> &str {
     html
 }
 
+/// Returns the short description of the item that is used in the beginning of its doc.
+fn doc_item_summary(doc_item: &DocItem) -> Option<&str> {
+    if let Some(comment) = &doc_item.comment {
+        comment.summary()
+    } else if doc_item.html.is_empty() {
+        None
+    } else {
+        Some(first_phrase(&doc_item.html))
+    }
+}
+
+/// Returns the text of the item's doc suitable for placing inline.
+fn doc_item_inline_text(doc_item: &DocItem) -> &str {
+    if let Some(comment) = &doc_item.comment {
+        comment.summary().unwrap_or("")
+    } else {
+        &doc_item.html
+    }
+}
+
 pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Result<String> {
     let mut output = String::new();
 
     let doc_item = database.find_doc_for(&type1.id)?;
-    if let Some(doc_item) = &doc_item {
-        if !doc_item.item.html.is_empty() {
-            writeln!(output, "{}\n", first_phrase(&doc_item.item.html))?;
-        }
+    if let Some(summary) = doc_item.as_ref().and_then(|i| doc_item_summary(i.item)) {
+        writeln!(output, "{}\n", summary)?;
     }
 
     match &type1.item.kind {
@@ -115,7 +142,7 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                 .to_cpp_pseudo_code();
 
             match kind {
-                RustWrapperTypeKind::EnumWrapper => {
+                RustWrapperTypeKind::EnumWrapper { .. } => {
                     writeln!(
                         output,
                         "C++ enum: {}.\n",
@@ -168,7 +195,9 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                     .item;
 
                 let cpp_signal_arguments = match ffi_item {
-                    CppFfiItem::Function(_) => bail!("invalid source ffi item type"),
+                    CppFfiItem::Function(_) | CppFfiItem::Subclass(_) => {
+                        bail!("invalid source ffi item type")
+                    }
                     CppFfiItem::QtSlotWrapper(w) => &w.signal_arguments,
                     CppFfiItem::QtSignalWrapper(w) => &w.signal_arguments,
                 };
@@ -214,6 +243,26 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                     }
                 }
             }
+
+            if let Some(subclass) = database
+                .source_ffi_item(&cpp_item.id)?
+                .and_then(|item| item.item.as_subclass_ref())
+            {
+                output.clear(); // remove irrelevant C++ type name
+                writeln!(
+                    output,
+                    "Subclass of {} that forwards calls of virtual methods to a Rust object.\n",
+                    wrap_inline_cpp_code(&subclass.base_class.to_cpp_pseudo_code())
+                )?;
+                writeln!(
+                    output,
+                    "Create an object using `new()` and install an implementation \
+                     of the corresponding trait using `set_impl()`. \
+                     Methods not overridden by the implementation call \
+                     the base class implementation. Calls of pure virtual methods \
+                     made before `set_impl()` abort the process.\n"
+                )?;
+            }
         }
         RustStructKind::QtSlotWrapper(_) => {
             bail!("RustStructKind::QtSlotWrapper is deprecated");
@@ -245,7 +294,66 @@ pub fn enum_value_doc(value: DbItem<&RustEnumValue>, database: &DatabaseClient)
         ))
     );
     if let Some(doc_item) = database.find_doc_for(&value.id)? {
-        doc = format!("{} ({})", doc_item.item.html, doc);
+        doc = format!("{} ({})", doc_item_inline_text(doc_item.item), doc);
+    }
+    Ok(doc)
+}
+
+pub fn constant_doc(constant: DbItem<&RustConstant>, database: &DatabaseClient) -> Result<String> {
+    let cpp_item = database
+        .source_cpp_item(&constant.id)?
+        .ok_or_else(|| err_msg("source cpp item not found"))?;
+    if let Some(index) = constant.item.default_value_of_argument {
+        let function = cpp_item
+            .item
+            .as_function_ref()
+            .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+        let arg = function
+            .arguments
+            .get(index)
+            .ok_or_else(|| err_msg("invalid argument index"))?;
+        return Ok(format!(
+            "Default value of argument {} of C++ function: {}",
+            wrap_inline_cpp_code(&arg.name),
+            wrap_inline_cpp_code(&function.short_text())
+        ));
+    }
+    let cpp_path = cpp_item
+        .item
+        .path()
+        .ok_or_else(|| err_msg("cpp item expected to have path"))?;
+
+    let kind_text = if cpp_item.item.as_macro_constant_ref().is_some() {
+        "macro"
+    } else {
+        "constant"
+    };
+    let mut doc = format!(
+        "C++ {}: {}",
+        kind_text,
+        wrap_inline_cpp_code(&cpp_path.to_cpp_pseudo_code())
+    );
+    if let Some(doc_item) = database.find_doc_for(&constant.id)? {
+        doc = format!("{} ({})", doc_item_inline_text(doc_item.item), doc);
+    }
+    Ok(doc)
+}
+
+pub fn type_alias_doc(alias: DbItem<&RustTypeAlias>, database: &DatabaseClient) -> Result<String> {
+    let cpp_item = database
+        .source_cpp_item(&alias.id)?
+        .ok_or_else(|| err_msg("source cpp item not found"))?;
+    let cpp_alias = cpp_item
+        .item
+        .as_type_alias_ref()
+        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+
+    let mut doc = format!(
+        "C++ type alias: {}",
+        wrap_inline_cpp_code(&cpp_alias.short_text())
+    );
+    if let Some(doc_item) = database.find_doc_for(&alias.id)? {
+        doc = format!("{} ({})", doc_item_inline_text(doc_item.item), doc);
     }
     Ok(doc)
 }
@@ -258,7 +366,31 @@ fn format_maybe_link(url: &Option<String>, text: &str) -> String {
     }
 }
 
+/// Renders a documentation comment parsed from a C++ header to Markdown.
+fn format_doc_comment(comment: &CppDocComment) -> String {
+    let mut parts = Vec::new();
+    if let Some(brief) = &comment.brief {
+        parts.push(brief.clone());
+    }
+    parts.extend(comment.paragraphs.iter().cloned());
+    if !comment.params.is_empty() {
+        let list = comment
+            .params
+            .iter()
+            .map(|param| format!("- `{}`: {}", param.name, param.text))
+            .join("\n");
+        parts.push(format!("Parameters:\n\n{}", list));
+    }
+    if let Some(returns) = &comment.returns {
+        parts.push(format!("Returns: {}", returns));
+    }
+    parts.join("\n\n")
+}
+
 fn format_doc_item(cpp_doc: &DocItem) -> String {
+    if let Some(comment) = &cpp_doc.comment {
+        return format!("C++ documentation:\n\n{}", format_doc_comment(comment));
+    }
     let mut output = if let Some(declaration) = &cpp_doc.mismatched_declaration {
         format!(
             "Warning: no exact match found in C++ documentation. \
@@ -352,10 +484,8 @@ pub fn function_doc(function: DbItem<&RustFunction>, database: &DatabaseClient)
     }
 
     let doc_item = database.find_doc_for(&function.id)?;
-    if let Some(doc_item) = &doc_item {
-        if !doc_item.item.html.is_empty() {
-            writeln!(output, "{}\n", first_phrase(&doc_item.item.html))?;
-        }
+    if let Some(summary) = doc_item.as_ref().and_then(|i| doc_item_summary(i.item)) {
+        writeln!(output, "{}\n", summary)?;
     }
 
     match &function.item.kind {
@@ -369,6 +499,7 @@ pub fn function_doc(function: DbItem<&RustFunction>, database: &DatabaseClient)
 
             match &cpp_ffi_function.kind {
                 CppFfiFunctionKind::Function => {
+                    let source_id = cpp_item.source_id.clone();
                     let cpp_item = cpp_item
                         .item
                         .as_function_ref()
@@ -378,41 +509,86 @@ pub fn function_doc(function: DbItem<&RustFunction>, database: &DatabaseClient)
                         "Calls C++ function: {}.\n\n",
                         wrap_inline_cpp_code(&cpp_item.short_text())
                     )?;
+                    if cpp_item.is_override() {
+                        write!(
+                            output,
+                            "This function overrides a virtual base class function. "
+                        )?;
+                    }
+                    if cpp_item.can_be_overridden() {
+                        write!(
+                            output,
+                            "This function can be overridden in a C++ subclass.\n\n"
+                        )?;
+                    } else if cpp_item.is_final() {
+                        write!(
+                            output,
+                            "This function is final and cannot be overridden.\n\n"
+                        )?;
+                    }
 
-                    // TODO: detect omitted arguments using source_id
-                    /*if let Some(arguments_before_omitting) =
-                        &cpp_function.doc.arguments_before_omitting
-                    {
-                        // TODO: handle singular/plural form
-                        doc.push(format!(
-                            "This version of the function omits some arguments ({}).\n\n",
-                            arguments_before_omitting.len() - cpp_function.arguments.len()
-                        ));
-                    }*/
+                    if let Some(source_id) = source_id {
+                        let original_function = database
+                            .item(&source_id)?
+                            .item
+                            .as_cpp_item()
+                            .and_then(|item| item.as_function_ref());
+                        if let Some(original_function) = original_function {
+                            if original_function.path == cpp_item.path
+                                && original_function.arguments.len() > cpp_item.arguments.len()
+                            {
+                                let omitted = original_function.arguments
+                                    [cpp_item.arguments.len()..]
+                                    .iter()
+                                    .map(|arg| {
+                                        wrap_inline_cpp_code(&format!(
+                                            "{} = {}",
+                                            arg.name,
+                                            arg.default_value.as_deref().unwrap_or("…")
+                                        ))
+                                    })
+                                    .join(", ");
+                                write!(
+                                    output,
+                                    "This version of the function omits some arguments \
+                                     and uses their default values: {}.\n\n",
+                                    omitted
+                                )?;
+                            }
+                        }
+                    }
                 }
                 CppFfiFunctionKind::FieldAccessor { accessor_type } => {
-                    let cpp_item = cpp_item
-                        .item
-                        .as_field_ref()
-                        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
-                    let field_text =
-                        wrap_inline_cpp_code(&cpp_item.path.last().to_cpp_pseudo_code());
+                    let (field_text, noun) = match cpp_item.item {
+                        CppItem::ClassField(field) => {
+                            (field.path.last().to_cpp_pseudo_code(), "field")
+                        }
+                        CppItem::GlobalVariable(variable) => {
+                            (variable.path.to_cpp_pseudo_code(), "variable")
+                        }
+                        _ => bail!("invalid source cpp item type"),
+                    };
+                    let field_text = wrap_inline_cpp_code(&field_text);
                     match *accessor_type {
                         CppFieldAccessorType::CopyGetter => {
-                            write!(output, "Returns the value of the {} field.", field_text)?;
+                            write!(output, "Returns the value of the {} {}.", field_text, noun)?;
                         }
                         CppFieldAccessorType::ConstRefGetter => {
-                            write!(output, "Returns a reference to the {} field.", field_text)?;
+                            write!(
+                                output,
+                                "Returns a reference to the {} {}.",
+                                field_text, noun
+                            )?;
                         }
                         CppFieldAccessorType::MutRefGetter => {
                             write!(
                                 output,
-                                "Returns a mutable reference to the {} field.",
-                                field_text
+                                "Returns a mutable reference to the {} {}.",
+                                field_text, noun
                             )?;
                         }
                         CppFieldAccessorType::Setter => {
-                            write!(output, "Sets the value of the {} field.", field_text)?;
+                            write!(output, "Sets the value of the {} {}.", field_text, noun)?;
                         }
                     };
                 }
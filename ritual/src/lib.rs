@@ -24,6 +24,8 @@ mod cpp_inheritance; // TODO: deal with inheritance for subclassing support
 mod cpp_omitting_arguments;
 mod cpp_operator;
 pub mod cpp_parser;
+pub mod cpp_parser_report;
+mod cpp_subclasses;
 pub mod cpp_template_instantiator;
 pub mod cpp_type;
 mod crate_writer;
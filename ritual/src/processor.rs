@@ -5,7 +5,7 @@ use crate::database::{DatabaseClient, ItemId};
 use crate::workspace::Workspace;
 use crate::{
     cpp_casts, cpp_checker, cpp_ffi_generator, cpp_implicit_methods, cpp_omitting_arguments,
-    cpp_parser, cpp_template_instantiator, crate_writer, rust_generator,
+    cpp_parser, cpp_subclasses, cpp_template_instantiator, crate_writer, rust_generator,
 };
 use itertools::Itertools;
 use log::debug;
@@ -114,6 +114,7 @@ impl Default for ProcessingSteps {
                 &format!("cpp_ffi_generator{}", suffix),
                 cpp_ffi_generator::run,
             );
+            s.push(&format!("cpp_subclasses{}", suffix), cpp_subclasses::run);
             s.push(&format!("cpp_checker{}", suffix), |data| {
                 cpp_checker::run(data, false)
             });
@@ -1,16 +1,17 @@
 //! Types and functions used for Rust code generation.
 
 use crate::cpp_checks::Condition;
+use crate::cpp_data::{CppConstantValue, CppDeprecation};
 use crate::cpp_ffi_data::{CppFfiArgumentMeaning, CppFfiItem};
 use crate::cpp_function::CppFunction;
 use crate::database::{DatabaseClient, DbItem, ItemId};
 use crate::doc_formatter;
 use crate::rust_generator::qt_core_path;
 use crate::rust_info::{
-    RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData, RustFunction,
-    RustFunctionArgument, RustFunctionKind, RustItem, RustModule, RustModuleKind,
-    RustQtReceiverType, RustSpecialModuleKind, RustStruct, RustStructKind, RustTraitImpl,
-    RustWrapperTypeKind,
+    RustConstant, RustConstantValue, RustEnumValue, RustExtraImpl, RustExtraImplKind,
+    RustFfiWrapperData, RustFunction, RustFunctionArgument, RustFunctionKind, RustItem, RustModule,
+    RustModuleKind, RustQtReceiverType, RustSpecialModuleKind, RustStruct, RustStructKind,
+    RustSubclassImpl, RustTraitImpl, RustTypeAlias, RustWrapperTypeKind,
 };
 use crate::rust_type::{
     RustClosureToCallbackConversion, RustCommonType, RustFinalType, RustPath,
@@ -24,6 +25,7 @@ use ritual_common::utils::MapIfOk;
 use std::fmt::Write as _;
 use std::fs;
 use std::io::{self, BufWriter, Write};
+use std::iter::once;
 use std::path::{Path, PathBuf};
 
 fn wrap_unsafe(in_unsafe_context: bool, content: &str) -> String {
@@ -110,6 +112,13 @@ pub fn rust_type_to_code(rust_type: &RustType, current_crate: Option<&str>) -> S
             "impl {}",
             rust_common_type_to_code(trait_type, current_crate)
         ),
+        RustType::Array { item_type, size } => {
+            format!(
+                "[{}; {}]",
+                rust_type_to_code(item_type, current_crate),
+                size
+            )
+        }
     }
 }
 
@@ -118,6 +127,12 @@ struct Generator<'a> {
     crate_template_src_path: Option<PathBuf>,
     destination: Vec<File<BufWriter<fs::File>>>,
     current_database: &'a DatabaseClient,
+    /// If true, FFI functions are generated as wrappers that
+    /// resolve the symbols at runtime instead of `extern` declarations.
+    ffi_runtime_loading: bool,
+    /// Names and `#[cfg]` attributes of the FFI functions
+    /// generated in the runtime loading mode.
+    runtime_ffi_symbols: Vec<(String, String)>,
 }
 
 impl Write for Generator<'_> {
@@ -147,9 +162,18 @@ fn format_doc(doc: &str) -> String {
 #[derive(Debug, Default)]
 struct ConditionTexts {
     attribute: String,
+    /// `#[cfg]` attribute without documentation attributes,
+    /// applicable to statements.
+    cfg_attribute: String,
     doc_text: String,
 }
 
+/// Returns name of the static variable holding the runtime symbol
+/// of the FFI function `ffi_function_name`.
+fn runtime_symbol_name(ffi_function_name: &str) -> String {
+    format!("{}_SYMBOL", ffi_function_name)
+}
+
 /// Generates documentation comments containing
 /// markdown code `doc`.
 fn format_doc_extended(doc: &str, is_outer: bool) -> String {
@@ -176,6 +200,17 @@ fn format_doc_extended(doc: &str, is_outer: bool) -> String {
         + extra_line_breaks
 }
 
+/// Generates `#[deprecated]` attribute if the item is deprecated.
+fn deprecated_attribute(deprecation: &Option<CppDeprecation>) -> String {
+    match deprecation {
+        None => String::new(),
+        Some(CppDeprecation { message: None }) => "#[deprecated]\n".to_string(),
+        Some(CppDeprecation {
+            message: Some(message),
+        }) => format!("#[deprecated(note = {:?})]\n", message),
+    }
+}
+
 fn condition_expression(condition: &Condition) -> String {
     match condition {
         Condition::CppLibraryVersion(version) => format!("cpp_lib_version={:?}", version),
@@ -294,10 +329,13 @@ impl Generator<'_> {
                 .condition(self.current_database.environments());
             if condition != Condition::True {
                 let expression = condition_expression(&condition);
+                condition_texts.cfg_attribute = format!(
+                    "#[cfg(any({}, feature = \"ritual_rustdoc\"))]\n",
+                    expression
+                );
                 condition_texts.attribute = format!(
-                    "#[cfg_attr(feature = \"ritual_rustdoc_nightly\", doc(cfg({})))]\n\
-                    #[cfg(any({}, feature = \"ritual_rustdoc\"))]\n",
-                    expression, expression
+                    "#[cfg_attr(feature = \"ritual_rustdoc_nightly\", doc(cfg({})))]\n{}",
+                    expression, condition_texts.cfg_attribute
                 );
                 // condition_texts.doc_text =
                 // format!("\n\nThis item is available if `{}`.", expression);
@@ -312,6 +350,12 @@ impl Generator<'_> {
             RustItem::EnumValue(_) => {
                 self.generate_enum_value(item.map(|i| i.as_enum_value_ref().unwrap()))
             }
+            RustItem::Constant(_) => {
+                self.generate_constant(item.map(|i| i.as_constant_ref().unwrap()))
+            }
+            RustItem::TypeAlias(_) => {
+                self.generate_type_alias(item.map(|i| i.as_type_alias_ref().unwrap()))
+            }
             RustItem::TraitImpl(_) => self.generate_trait_impl(
                 item.map(|i| i.as_trait_impl_ref().unwrap()),
                 &condition_texts,
@@ -372,6 +416,10 @@ impl Generator<'_> {
                 self,
                 "#![cfg_attr(feature = \"ritual_rustdoc_nightly\", feature(doc_cfg))]"
             )?;
+            if module.item.kind == RustModuleKind::Special(RustSpecialModuleKind::CrateRoot) {
+                // generated code refers to deprecated items of the crate itself
+                writeln!(self, "#![allow(deprecated)]")?;
+            }
 
             if let Some(crate_template_src_path) = &self.crate_template_src_path {
                 let template_path = self.module_path(&module.item.path, crate_template_src_path)?;
@@ -411,8 +459,12 @@ impl Generator<'_> {
                     "include!(concat!(env!(\"OUT_DIR\"), \"/sized_types.rs\"));"
                 )?;
             }
-            RustModuleKind::Special(RustSpecialModuleKind::CrateRoot)
-            | RustModuleKind::Special(RustSpecialModuleKind::Ops)
+            RustModuleKind::Special(RustSpecialModuleKind::CrateRoot) => {
+                self.generate_load_wrapper_library()?;
+                self.generate_children(&module.item.path, None)?;
+            }
+            RustModuleKind::Special(RustSpecialModuleKind::Ops)
+            | RustModuleKind::Special(RustSpecialModuleKind::Protected)
             | RustModuleKind::CppNamespace { .. }
             | RustModuleKind::CppNestedTypes { .. } => {
                 self.generate_children(&module.item.path, None)?;
@@ -433,11 +485,117 @@ impl Generator<'_> {
             self.generate_children(&module.item.path, None)?;
             writeln!(self, "}}\n")?;
             self.pop_file();
+
+            // used by the build script instead of `ffi.in.rs`
+            // if the wrapper library is loaded at runtime
+            let path = self.output_src_path.join("ffi_runtime.in.rs");
+            self.destination.push(create_file(&path)?);
+            self.ffi_runtime_loading = true;
+            self.generate_children(&module.item.path, None)?;
+            self.ffi_runtime_loading = false;
+            self.generate_runtime_symbols_loader()?;
+            self.pop_file();
         }
 
         Ok(())
     }
 
+    /// Generates the function that loads the wrapper library and resolves
+    /// all FFI functions generated in the runtime loading mode.
+    fn generate_runtime_symbols_loader(&mut self) -> Result<()> {
+        let symbols = std::mem::take(&mut self.runtime_ffi_symbols);
+        writeln!(
+            self,
+            "pub fn load_wrapper_library() -> \
+             ::std::result::Result<(), ::cpp_core::RuntimeLibraryError> {{\n\
+             WRAPPER_LIBRARY.load()?;"
+        )?;
+        for (name, cfg_attribute) in symbols {
+            writeln!(
+                self,
+                "{}{}.get(&WRAPPER_LIBRARY)?;",
+                cfg_attribute,
+                runtime_symbol_name(&name)
+            )?;
+        }
+        writeln!(self, "Ok(())\n}}")?;
+        writeln!(
+            self,
+            "pub fn load_wrapper_library_from(path: &::std::path::Path) -> \
+             ::std::result::Result<(), ::cpp_core::RuntimeLibraryError> {{\n\
+             WRAPPER_LIBRARY.load_from(path)?;\n\
+             load_wrapper_library()\n\
+             }}"
+        )?;
+        Ok(())
+    }
+
+    /// Generates the public function that allows to check availability
+    /// of the wrapper library when it's loaded at runtime.
+    fn generate_load_wrapper_library(&mut self) -> Result<()> {
+        let ffi_module = self
+            .current_database
+            .rust_items()
+            .filter_map(|i| i.filter_map(|i| i.as_module_ref()))
+            .find(|module| module.item.kind == RustModuleKind::Special(RustSpecialModuleKind::Ffi))
+            .ok_or_else(|| err_msg("ffi module not found"))?;
+        writeln!(
+            self,
+            "/// Loads the C++ wrapper library of this crate.\n\
+             ///\n\
+             /// If the crate is built with `RITUAL_LIBRARY_LOADING=runtime`, this function \
+             /// loads the wrapper library and resolves all of its functions used by the crate, \
+             /// returning an error if the library or any of the functions is not available. \
+             /// Otherwise the library is linked to the crate, and this function always succeeds.\n\
+             ///\n\
+             /// In the runtime loading mode, any API of the crate that calls into \
+             /// the wrapper library panics if the library or the function can't be loaded. \
+             /// Call this function and check its result before using the crate. \
+             /// A panic in a Rust callback invoked from C++ (e.g. a slot) aborts the process.\n\
+             pub fn load_wrapper_library() -> \
+             ::std::result::Result<(), ::cpp_core::RuntimeLibraryError> {{\n\
+             {ffi}::load_wrapper_library()\n\
+             }}\n\n\
+             /// Loads the C++ wrapper library of this crate from the file `path`.\n\
+             ///\n\
+             /// Same as `load_wrapper_library`, but the library is loaded from `path` instead of \
+             /// being looked up in `RITUAL_RUNTIME_LIBRARY_PATH`, the build directory and \
+             /// the default system paths. It must be called before any other use of the crate. \
+             /// If the library is linked to the crate, `path` is ignored.\n\
+             pub fn load_wrapper_library_from(path: impl AsRef<::std::path::Path>) -> \
+             ::std::result::Result<(), ::cpp_core::RuntimeLibraryError> {{\n\
+             {ffi}::load_wrapper_library_from(path.as_ref())\n\
+             }}\n",
+            ffi = self.rust_path_to_string(&ffi_module.item.path)
+        )?;
+        Ok(())
+    }
+
+    /// Generates body of an FFI function that resolves the function's symbol
+    /// in the wrapper library at runtime and calls it.
+    fn generate_runtime_ffi_call(&self, func: &RustFunction) -> String {
+        let arg_types = func
+            .arguments
+            .iter()
+            .map(|arg| self.rust_type_to_code(arg.argument_type.ffi_type()))
+            .join(", ");
+        let return_type = func.return_type.ffi_type();
+        let maybe_return_type = if return_type.is_unit() {
+            String::new()
+        } else {
+            format!(" -> {}", self.rust_type_to_code(return_type))
+        };
+        format!(
+            "let ffi_function: unsafe extern \"C\" fn({arg_types}){return_type} = \
+                ::std::mem::transmute({symbol}.resolve(&WRAPPER_LIBRARY));\n\
+             ffi_function({args})",
+            symbol = runtime_symbol_name(func.path.last()),
+            arg_types = arg_types,
+            return_type = maybe_return_type,
+            args = func.arguments.iter().map(|arg| &arg.name).join(", "),
+        )
+    }
+
     fn qt_core_path(&self) -> RustPath {
         qt_core_path(&self.current_database.crate_name())
     }
@@ -459,6 +617,11 @@ impl Generator<'_> {
         let doc = doc_formatter::struct_doc(rust_struct.clone(), self.current_database)?
             + &condition_texts.doc_text;
         write!(self, "{}", format_doc(&doc))?;
+        write!(
+            self,
+            "{}",
+            deprecated_attribute(&rust_struct.item.deprecation)
+        )?;
 
         let visibility = if rust_struct.item.is_public {
             "pub "
@@ -467,12 +630,13 @@ impl Generator<'_> {
         };
         match &rust_struct.item.kind {
             RustStructKind::WrapperType(kind) => match kind {
-                RustWrapperTypeKind::EnumWrapper => {
+                RustWrapperTypeKind::EnumWrapper { underlying_type } => {
                     writeln!(
                         self,
                         include_str!("../templates/crate/enum_wrapper.rs.in"),
                         vis = visibility,
-                        name = rust_struct.item.path.last()
+                        name = rust_struct.item.path.last(),
+                        underlying_type = self.rust_type_to_code(underlying_type)
                     )?;
                 }
                 RustWrapperTypeKind::ImmovableClassWrapper => {
@@ -533,20 +697,85 @@ impl Generator<'_> {
                 self.current_database
             )?)
         )?;
-        let struct_path = self.rust_path_to_string(
-            &value
-                .item
-                .path
-                .parent()
-                .expect("enum value must have parent"),
-        );
+        let enum_path = value
+            .item
+            .path
+            .parent()
+            .expect("enum value must have parent");
+        let is_unsigned = self
+            .current_database
+            .find_rust_item(&enum_path)
+            .and_then(|item| item.item.as_struct_ref())
+            .map_or(false, |rust_struct| match &rust_struct.kind {
+                RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper {
+                    underlying_type,
+                }) => underlying_type.is_unsigned_integer(),
+                _ => false,
+            });
+        // unsigned values are stored with wrapping
+        let value_text = if is_unsigned {
+            (value.item.value as u64).to_string()
+        } else {
+            value.item.value.to_string()
+        };
+        let struct_path = self.rust_path_to_string(&enum_path);
+        write!(self, "{}", deprecated_attribute(&value.item.deprecation))?;
         writeln!(self, "#[allow(non_upper_case_globals)]")?;
         writeln!(
             self,
             "pub const {value_name}: {struct_path} = {struct_path}({value});",
             value_name = value.item.path.last(),
             struct_path = struct_path,
-            value = value.item.value
+            value = value_text
+        )?;
+        Ok(())
+    }
+
+    fn generate_constant(&mut self, constant: DbItem<&RustConstant>) -> Result<()> {
+        write!(
+            self,
+            "{}",
+            format_doc(&doc_formatter::constant_doc(
+                constant.clone(),
+                self.current_database
+            )?)
+        )?;
+        let value = match &constant.item.value {
+            RustConstantValue::Literal(CppConstantValue::Integer(value)) => {
+                if constant.item.constant_type == RustType::bool() {
+                    (*value != 0).to_string()
+                } else {
+                    value.to_string()
+                }
+            }
+            RustConstantValue::Literal(CppConstantValue::Float(value)) => value.clone(),
+            RustConstantValue::Literal(CppConstantValue::String(value)) => format!("{:?}", value),
+            RustConstantValue::EnumValue(path) => self.rust_path_to_string(path),
+        };
+        writeln!(
+            self,
+            "pub const {}: {} = {};",
+            constant.item.path.last(),
+            self.rust_type_to_code(&constant.item.constant_type),
+            value
+        )?;
+        Ok(())
+    }
+
+    fn generate_type_alias(&mut self, alias: DbItem<&RustTypeAlias>) -> Result<()> {
+        write!(
+            self,
+            "{}",
+            format_doc(&doc_formatter::type_alias_doc(
+                alias.clone(),
+                self.current_database
+            )?)
+        )?;
+        writeln!(
+            self,
+            "pub type {} = {};",
+            alias.item.path.last(),
+            self.rust_type_to_code(&alias.item.target_type)
         )?;
         Ok(())
     }
@@ -596,6 +825,18 @@ impl Generator<'_> {
                     code
                 )
             }
+            RustToFfiTypeConversion::ArrayRefToPtr { .. } => {
+                let api_is_const = type1.api_type().is_const_pointer_like()?;
+                let array_type = type1.api_type().pointer_like_to_target()?;
+                let code = format!(
+                    "&{}*({} as {} {})",
+                    if api_is_const { "" } else { "mut " },
+                    source_expr,
+                    if api_is_const { "*const" } else { "*mut" },
+                    self.rust_type_to_code(&array_type)
+                );
+                wrap_unsafe(in_unsafe_context, &code)
+            }
             RustToFfiTypeConversion::ValueToPtr => {
                 let code = format!("*{}", source_expr);
                 wrap_unsafe(in_unsafe_context, &code)
@@ -612,6 +853,10 @@ impl Generator<'_> {
                 let code = format!("{}::QBox::from_raw({})", self.qt_core_prefix(), source_expr);
                 wrap_unsafe(in_unsafe_context, &code)
             }
+            RustToFfiTypeConversion::VolatilePtrToPtr => {
+                let code = format!("::cpp_core::VolatilePtr::from_raw({})", source_expr);
+                wrap_unsafe(in_unsafe_context, &code)
+            }
             RustToFfiTypeConversion::QPtrToPtr
             | RustToFfiTypeConversion::UtilsPtrToPtr { .. }
             | RustToFfiTypeConversion::UtilsRefToPtr { .. }
@@ -693,6 +938,15 @@ impl Generator<'_> {
             RustToFfiTypeConversion::ClosureToCallback { .. } => {
                 bail!("ClosureToCallback is not convertable from FFI type");
             }
+            RustToFfiTypeConversion::MoveCppBoxToPtr => {
+                bail!("MoveCppBoxToPtr is not convertable from FFI type");
+            }
+            RustToFfiTypeConversion::VarArgsToPtr => {
+                bail!("VarArgsToPtr is not convertable from FFI type");
+            }
+            RustToFfiTypeConversion::ResultOf(_) => {
+                bail!("ResultOf must be handled by generate_ffi_call");
+            }
         };
         Ok(code1 + &code2)
     }
@@ -716,6 +970,13 @@ impl Generator<'_> {
                     format!("{} as {}", expr, self.rust_type_to_code(type1.ffi_type()))
                 }
             }
+            RustToFfiTypeConversion::ArrayRefToPtr { .. } => {
+                if type1.ffi_type().is_const_pointer_like()? {
+                    format!("{}.as_ptr()", expr)
+                } else {
+                    format!("{}.as_mut_ptr()", expr)
+                }
+            }
             RustToFfiTypeConversion::ValueToPtr => {
                 let is_const = type1.ffi_type().is_const_pointer_like()?;
                 format!(
@@ -728,6 +989,16 @@ impl Generator<'_> {
             RustToFfiTypeConversion::CppBoxToPtr | RustToFfiTypeConversion::QBoxToPtr => {
                 format!("{}.into_raw_ptr()", expr)
             }
+            // the moved-from object is deleted when the `CppBox` is dropped
+            // at the end of the wrapper function
+            RustToFfiTypeConversion::MoveCppBoxToPtr => format!("{}.as_mut_raw_ptr()", expr),
+            RustToFfiTypeConversion::VolatilePtrToPtr => {
+                if type1.ffi_type().is_const_pointer_like()? {
+                    format!("{}.as_raw_ptr()", expr)
+                } else {
+                    format!("{}.as_mut_raw_ptr()", expr)
+                }
+            }
             RustToFfiTypeConversion::UtilsPtrToPtr { .. }
             | RustToFfiTypeConversion::UtilsRefToPtr { .. }
             | RustToFfiTypeConversion::QPtrToPtr { .. } => {
@@ -783,6 +1054,10 @@ impl Generator<'_> {
             RustToFfiTypeConversion::ClosureToCallback { .. } => {
                 "Some(ffi_callback::<T>), Some(deleter::<T>), data".to_string()
             }
+            RustToFfiTypeConversion::VarArgsToPtr => format!("{}.as_ptr()", expr),
+            RustToFfiTypeConversion::ResultOf(_) => {
+                bail!("ResultOf is not possible to use in argument position");
+            }
         };
         Ok(code)
     }
@@ -805,7 +1080,11 @@ impl Generator<'_> {
         format!("T: FnMut({}){} + 'static", args_text, return_type_text)
     }
 
-    fn callback_glue_code(&self, conversion: &RustClosureToCallbackConversion) -> Result<String> {
+    fn callback_glue_code(
+        &self,
+        conversion: &RustClosureToCallbackConversion,
+        closure_name: &str,
+    ) -> Result<String> {
         let mut code = String::new();
         writeln!(
             code,
@@ -816,16 +1095,22 @@ impl Generator<'_> {
             }}"
         )?;
 
+        let return_type = &conversion.closure_return_type;
         writeln!(
             code,
-            "extern \"C\" fn ffi_callback<{}>(data: *mut ::std::ffi::c_void, {}) {{",
+            "extern \"C\" fn ffi_callback<{}>(data: *mut ::std::ffi::c_void, {}){} {{",
             self.callback_bound_code(conversion),
             conversion
                 .closure_arguments
                 .iter()
                 .enumerate()
                 .map(|(i, arg)| format!("arg{}: {}", i, self.rust_type_to_code(arg.ffi_type())))
-                .join(", ")
+                .join(", "),
+            if return_type.ffi_type().is_unit() {
+                String::new()
+            } else {
+                format!(" -> {}", self.rust_type_to_code(return_type.ffi_type()))
+            }
         )?;
 
         let func_args = conversion
@@ -837,15 +1122,22 @@ impl Generator<'_> {
             })?
             .join(", ");
 
-        code.push_str(&wrap_unsafe(
-            false,
-            &format!("(*(data as *mut T))({})", func_args),
-        ));
+        let call = format!("(*(data as *mut T))({})", func_args);
+        if return_type.api_type().is_unit() {
+            code.push_str(&wrap_unsafe(false, &call));
+        } else {
+            let result = self.convert_type_to_ffi("result", return_type)?;
+            code.push_str(&wrap_unsafe(
+                false,
+                &format!("let result = {};\n{}", call, result),
+            ));
+        }
 
         writeln!(code, "}}")?;
         writeln!(
             code,
-            "let data = Box::into_raw(Box::new(callback)) as *mut ::std::ffi::c_void;"
+            "let data = Box::into_raw(Box::new({})) as *mut ::std::ffi::c_void;",
+            closure_name
         )?;
 
         Ok(code)
@@ -871,12 +1163,17 @@ impl Generator<'_> {
 
         let mut result = Vec::new();
 
-        if let Some(conversion) = arguments
+        if let Some((conversion, name)) = arguments
             .iter()
-            .filter_map(|x| x.argument_type.conversion().as_callback_ref())
+            .filter_map(|x| {
+                x.argument_type
+                    .conversion()
+                    .as_callback_ref()
+                    .map(|conversion| (conversion, &x.name))
+            })
             .next()
         {
-            result.push(self.callback_glue_code(conversion)?);
+            result.push(self.callback_glue_code(conversion, name)?);
         }
 
         let mut maybe_result_var_name = None;
@@ -896,6 +1193,41 @@ impl Generator<'_> {
             .find(|(_arg_index, arg)| arg.meaning == CppFfiArgumentMeaning::ReturnValue)
             .map(|(index, _arg)| index);
 
+        let exception_ffi_index = ffi_item
+            .arguments
+            .iter()
+            .position(|arg| arg.meaning == CppFfiArgumentMeaning::Exception);
+        if let Some(i) = exception_ffi_index {
+            final_args.resize(i + 1, None);
+            final_args[i] = Some("&mut ritual_exception".to_string());
+        }
+        let var_arg_count_ffi_index = ffi_item
+            .arguments
+            .iter()
+            .position(|arg| arg.meaning == CppFfiArgumentMeaning::VarArgCount);
+        if let Some(i) = var_arg_count_ffi_index {
+            let var_args = arguments
+                .iter()
+                .find(|arg| {
+                    arg.argument_type.conversion() == &RustToFfiTypeConversion::VarArgsToPtr
+                })
+                .ok_or_else(|| err_msg("var args argument not found"))?;
+            if final_args.len() < i + 1 {
+                final_args.resize(i + 1, None);
+            }
+            final_args[i] = Some(format!("{}.count()", var_args.name));
+        }
+        // the value is wrapped in `Ok` after checking the exception
+        let inner_return_type;
+        let return_type =
+            if let RustToFfiTypeConversion::ResultOf(conversion) = return_type.conversion() {
+                inner_return_type =
+                    RustFinalType::new(return_type.ffi_type().clone(), (**conversion).clone())?;
+                &inner_return_type
+            } else {
+                return_type
+            };
+
         if let Some(i) = return_type_ffi_index {
             let mut return_var_name = "object".to_string();
             let mut ii = 1;
@@ -954,6 +1286,14 @@ impl Generator<'_> {
             result.push(format!("{}\n}}", name));
         }
         let code = result.join("");
+        if exception_ffi_index.is_some() {
+            return self.check_exception_code(
+                code,
+                return_type,
+                maybe_result_var_name.as_deref(),
+                in_unsafe_context,
+            );
+        }
         if maybe_result_var_name.is_none() {
             self.convert_type_from_ffi(&return_type, code, in_unsafe_context, true)
         } else {
@@ -961,6 +1301,51 @@ impl Generator<'_> {
         }
     }
 
+    /// Generates Rust code that evaluates `call_code`, returns `Err` if the FFI function
+    /// reported a C++ exception, and returns the converted value wrapped in `Ok` otherwise.
+    /// `result_var_name` is the variable holding the return value if it's passed
+    /// through an FFI argument.
+    fn check_exception_code(
+        &self,
+        call_code: String,
+        return_type: &RustFinalType,
+        result_var_name: Option<&str>,
+        in_unsafe_context: bool,
+    ) -> Result<String> {
+        let mut code = "let mut ritual_exception: *mut ::std::os::raw::c_char = \
+                        ::std::ptr::null_mut();\n"
+            .to_string();
+        let mut forget_value = String::new();
+        let value = if let Some(name) = result_var_name {
+            writeln!(code, "let {} = {};", name, call_code)?;
+            // the value is not initialized if an exception was thrown
+            forget_value = format!("::std::mem::forget({});\n", name);
+            name.to_string()
+        } else if return_type.api_type().is_unit() {
+            writeln!(code, "{};", call_code)?;
+            "()".to_string()
+        } else {
+            writeln!(code, "let ffi_result = {};", call_code)?;
+            self.convert_type_from_ffi(
+                return_type,
+                "ffi_result".to_string(),
+                in_unsafe_context,
+                false,
+            )?
+        };
+        writeln!(
+            code,
+            "if !ritual_exception.is_null() {{\n{}return Err({});\n}}",
+            forget_value,
+            wrap_unsafe(
+                in_unsafe_context,
+                "::cpp_core::CppException::from_raw(ritual_exception)"
+            )
+        )?;
+        write!(code, "Ok({})", value)?;
+        Ok(code)
+    }
+
     /// Generates Rust code for declaring a function's arguments.
     #[allow(clippy::collapsible_match)]
     fn arg_texts(
@@ -1033,7 +1418,27 @@ impl Generator<'_> {
         } else {
             ""
         };
-        let maybe_unsafe = if func.item.is_unsafe { "unsafe " } else { "" };
+        let is_runtime_ffi_function = self.ffi_runtime_loading && func.item.kind.is_ffi_function();
+        let maybe_unsafe = if func.item.is_unsafe || is_runtime_ffi_function {
+            "unsafe "
+        } else {
+            ""
+        };
+
+        if is_runtime_ffi_function {
+            let name = func.item.path.last();
+            writeln!(
+                self,
+                "{condition}#[allow(non_upper_case_globals)]\n\
+                 static {symbol}: ::cpp_core::RuntimeSymbol = \
+                 ::cpp_core::RuntimeSymbol::new(b\"{name}\\0\");\n",
+                condition = condition_texts.attribute,
+                symbol = runtime_symbol_name(name),
+                name = name,
+            )?;
+            self.runtime_ffi_symbols
+                .push((name.to_string(), condition_texts.cfg_attribute.clone()));
+        }
 
         let body = match &func.item.kind {
             RustFunctionKind::FfiWrapper(data) => Some(self.generate_ffi_call(
@@ -1062,7 +1467,13 @@ impl Generator<'_> {
                 );
                 Some(wrap_unsafe(func.item.is_unsafe, &call))
             }
-            RustFunctionKind::FfiFunction => None,
+            RustFunctionKind::FfiFunction => {
+                if is_runtime_ffi_function {
+                    Some(self.generate_runtime_ffi_call(func.item))
+                } else {
+                    None
+                }
+            }
         };
 
         let maybe_body = match &body {
@@ -1106,10 +1517,16 @@ impl Generator<'_> {
             + &condition_texts.doc_text;
         writeln!(
             self,
-            "{doc}{maybe_inline}{condition}{maybe_pub}{maybe_unsafe} \
+            "{doc}{maybe_deprecated}{maybe_inline}{condition}{maybe_pub}{maybe_unsafe} \
              fn {name}{generic_args_text}({args}){return_type} \
              {maybe_body}\n\n",
             doc = format_doc(&doc),
+            // `#[deprecated]` has no effect on trait impl items
+            maybe_deprecated = if is_in_trait_context {
+                String::new()
+            } else {
+                deprecated_attribute(&func.item.deprecation)
+            },
             maybe_inline = if body.is_some() {
                 "#[inline(always)]\n"
             } else {
@@ -1196,7 +1613,9 @@ impl Generator<'_> {
                     .item;
 
                 let receiver_id = match wrapper {
-                    CppFfiItem::Function(_) => bail!("invalid source ffi item type"),
+                    CppFfiItem::Function(_) | CppFfiItem::Subclass(_) => {
+                        bail!("invalid source ffi item type")
+                    }
                     CppFfiItem::QtSlotWrapper(w) => CppFunction::receiver_id_from_data(
                         RustQtReceiverType::Slot,
                         "slot_",
@@ -1231,9 +1650,153 @@ impl Generator<'_> {
                     )?;
                 }
             }
+            RustExtraImplKind::Subclass(data) => {
+                self.generate_subclass_impl(data, condition_texts)?;
+            }
         }
         Ok(())
     }
+
+    /// Generates the trait for overriding virtual methods of a subclass
+    /// and the `set_impl` method of the subclass.
+    fn generate_subclass_impl(
+        &mut self,
+        data: &RustSubclassImpl,
+        condition_texts: &ConditionTexts,
+    ) -> Result<()> {
+        let type_path = self.rust_path_to_string(&data.target_path);
+        let trait_name = data.trait_path.last();
+
+        let mut trait_methods = String::new();
+        let mut callbacks = String::new();
+        let mut set_callbacks = String::new();
+        for function in &data.functions {
+            let args_text = function
+                .arguments
+                .iter()
+                .map(|arg| {
+                    format!(
+                        ", {}: {}",
+                        arg.name,
+                        self.rust_type_to_code(arg.argument_type.api_type())
+                    )
+                })
+                .join("");
+            let return_type_text = if function.return_type.api_type().is_unit() {
+                String::new()
+            } else {
+                format!(
+                    " -> {}",
+                    self.rust_type_to_code(function.return_type.api_type())
+                )
+            };
+            let body = if let Some(base_function) = &function.base_function {
+                let ffi_args =
+                    once(self.convert_type_to_ffi("this", &function.this_type)?)
+                        .chain(function.arguments.iter().map_if_ok(|arg| {
+                            self.convert_type_to_ffi(&arg.name, &arg.argument_type)
+                        })?)
+                        .join(", ");
+                let call = format!("{}({})", self.rust_path_to_string(base_function), ffi_args);
+                format!(
+                    " {{\n{}\n}}",
+                    self.convert_type_from_ffi(&function.return_type, call, true, true)?
+                )
+            } else {
+                ";".to_string()
+            };
+            write!(
+                trait_methods,
+                "{}unsafe fn {}(&self, this: {}{}){}{}\n",
+                format_doc(&format!(
+                    "Overrides C++ method: {}.",
+                    doc_formatter::wrap_inline_cpp_code(&function.cpp_declaration)
+                )),
+                function.name,
+                self.rust_type_to_code(function.this_type.api_type()),
+                args_text,
+                return_type_text,
+                body
+            )?;
+
+            let callback_name = format!("{}_callback", function.name);
+            let ffi_args_text = function
+                .arguments
+                .iter()
+                .map(|arg| {
+                    format!(
+                        ", arg{}: {}",
+                        arg.ffi_index,
+                        self.rust_type_to_code(arg.argument_type.ffi_type())
+                    )
+                })
+                .join("");
+            let ffi_return_type_text = if function.return_type.ffi_type().is_unit() {
+                String::new()
+            } else {
+                format!(
+                    " -> {}",
+                    self.rust_type_to_code(function.return_type.ffi_type())
+                )
+            };
+            let call_args = once(self.convert_type_from_ffi(
+                &function.this_type,
+                "this".to_string(),
+                true,
+                false,
+            )?)
+            .chain(function.arguments.iter().map_if_ok(|arg| {
+                self.convert_type_from_ffi(
+                    &arg.argument_type,
+                    format!("arg{}", arg.ffi_index),
+                    true,
+                    false,
+                )
+            })?)
+            .join(", ");
+            let call = format!("(*(data as *const T)).{}({})", function.name, call_args);
+            let call_code = if function.return_type.api_type().is_unit() {
+                format!("{};", call)
+            } else {
+                format!(
+                    "let result = {};\n{}",
+                    call,
+                    self.convert_type_to_ffi("result", &function.return_type)?
+                )
+            };
+            writeln!(
+                callbacks,
+                "extern \"C\" fn {name}<T: {trait_name}>(\
+                 data: *mut ::std::ffi::c_void, this: {this}{args}){ret} {{\n\
+                 unsafe {{\n{call}\n}}\n}}",
+                name = callback_name,
+                trait_name = trait_name,
+                this = self.rust_type_to_code(function.this_type.ffi_type()),
+                args = ffi_args_text,
+                ret = ffi_return_type_text,
+                call = call_code,
+            )?;
+            writeln!(
+                set_callbacks,
+                "{}(this, Some({}::<T>));",
+                self.rust_path_to_string(&function.set_callback_function),
+                callback_name
+            )?;
+        }
+
+        writeln!(
+            self,
+            include_str!("../templates/crate/subclass_impl.rs.in"),
+            condition_attribute = condition_texts.attribute,
+            type_path = type_path,
+            trait_name = trait_name,
+            trait_methods = trait_methods,
+            callbacks = callbacks,
+            set_data = self.rust_path_to_string(&data.set_data_function),
+            set_callbacks = set_callbacks,
+        )?;
+        Ok(())
+    }
 }
 
 pub fn generate(
@@ -1246,6 +1809,8 @@ pub fn generate(
         output_src_path: output_src_path.into(),
         crate_template_src_path: crate_template_src_path.map(Into::into),
         current_database,
+        ffi_runtime_loading: false,
+        runtime_ffi_symbols: Vec::new(),
     };
 
     let crate_root = generator
@@ -1260,3 +1825,186 @@ pub fn generate(
     generator.generate_module(crate_root)?;
     Ok(())
 }
+
+#[test]
+fn subclass_impl() {
+    use crate::database::{Database, IndexedDatabase};
+    use crate::rust_info::RustSubclassFunction;
+    use ritual_common::ReadOnly;
+
+    let dir = tempdir::TempDir::new("test_subclass_impl").unwrap();
+    let db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("test_crate".into()),
+            PathBuf::from("test_crate"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let class_type = |name: &str| {
+        RustType::Common(RustCommonType {
+            path: RustPath::from_good_str(&format!("test_crate::{}", name)),
+            generic_arguments: None,
+        })
+    };
+    let this_type = |is_const: bool| {
+        RustFinalType::new(
+            RustType::new_pointer(is_const, class_type("ListenerSubclass")),
+            RustToFfiTypeConversion::UtilsPtrToPtr {},
+        )
+        .unwrap()
+    };
+    let ffi_function =
+        |name: &str| RustPath::from_good_str(&format!("test_crate::__ffi::{}", name));
+    let function = |name: &str,
+                    is_const: bool,
+                    arguments: Vec<RustFunctionArgument>,
+                    return_type: RustFinalType,
+                    is_pure_virtual: bool| RustSubclassFunction {
+        name: name.into(),
+        cpp_declaration: format!("Listener::{}", name),
+        this_type: this_type(is_const),
+        arguments,
+        return_type,
+        set_callback_function: ffi_function(&format!("ctr_set_{}_callback", name)),
+        base_function: if is_pure_virtual {
+            None
+        } else {
+            Some(ffi_function(&format!("ctr_base_{}", name)))
+        },
+    };
+
+    let data = RustSubclassImpl {
+        target_path: RustPath::from_good_str("test_crate::ListenerSubclass"),
+        trait_path: RustPath::from_good_str("test_crate::ListenerImpl"),
+        set_data_function: ffi_function("ctr_set_data"),
+        functions: vec![
+            function(
+                "count",
+                true,
+                vec![],
+                RustFinalType::new(
+                    RustType::Primitive("i32".into()),
+                    RustToFfiTypeConversion::None,
+                )
+                .unwrap(),
+                true,
+            ),
+            function(
+                "notify",
+                false,
+                vec![RustFunctionArgument {
+                    argument_type: RustFinalType::new(
+                        RustType::new_pointer(true, class_type("QString")),
+                        RustToFfiTypeConversion::UtilsRefToPtr {},
+                    )
+                    .unwrap(),
+                    name: "text".into(),
+                    ffi_index: 2,
+                }],
+                RustFinalType::new(RustType::unit(), RustToFfiTypeConversion::None).unwrap(),
+                false,
+            ),
+            function(
+                "flags",
+                true,
+                vec![],
+                RustFinalType::new(
+                    RustType::Primitive("i32".into()),
+                    RustToFfiTypeConversion::QFlagsToUInt {
+                        api_type: RustType::Common(RustCommonType {
+                            path: RustPath::from_good_str("test_crate::QFlags"),
+                            generic_arguments: Some(vec![class_type("Flag")]),
+                        }),
+                    },
+                )
+                .unwrap(),
+                false,
+            ),
+            function(
+                "current",
+                false,
+                vec![],
+                RustFinalType::new(
+                    RustType::new_pointer(false, class_type("Item")),
+                    RustToFfiTypeConversion::UtilsRefToPtr {},
+                )
+                .unwrap(),
+                false,
+            ),
+        ],
+    };
+
+    let mut generator = Generator {
+        output_src_path: dir.path().into(),
+        crate_template_src_path: None,
+        destination: Vec::new(),
+        current_database: &db,
+        ffi_runtime_loading: false,
+        runtime_ffi_symbols: Vec::new(),
+    };
+    let path = dir.path().join("lib.rs");
+    generator.push_file(&path).unwrap();
+    generator
+        .generate_subclass_impl(&data, &ConditionTexts::default())
+        .unwrap();
+    generator.pop_file();
+    let code = file_to_string(&path).unwrap();
+
+    assert!(code.contains("pub trait ListenerImpl: 'static {"));
+    let this_text = "this: ::cpp_core::Ptr<crate::ListenerSubclass>";
+
+    // pure virtual method has no default implementation
+    assert!(code.contains(&format!("unsafe fn count(&self, {}) -> i32;", this_text)));
+    assert!(!code.contains("ctr_base_count"));
+
+    // other methods call the base implementation by default
+    assert!(code.contains(&format!(
+        "unsafe fn notify(&self, {}, text: ::cpp_core::Ref<crate::QString>) {{\n\
+         crate::__ffi::ctr_base_notify(this.as_raw_ptr() as *mut crate::ListenerSubclass, \
+         text.as_raw_ptr())\n}}",
+        this_text
+    )));
+
+    // const method returning QFlags
+    assert!(code.contains(&format!(
+        "unsafe fn flags(&self, {}) -> crate::QFlags<crate::Flag> {{\n\
+         let ffi_result = {{ crate::__ffi::ctr_base_flags(this.as_raw_ptr()) }};\n\
+         crate::QFlags::from(ffi_result)\n}}",
+        this_text
+    )));
+    assert!(code.contains("let result = (*(data as *const T)).flags("));
+    assert!(code.contains("result.to_int()"));
+
+    // method returning a reference
+    assert!(code.contains(&format!(
+        "unsafe fn current(&self, {}) -> ::cpp_core::Ref<crate::Item> {{",
+        this_text
+    )));
+    assert!(code.contains("result.as_raw_ptr() as *mut crate::Item"));
+
+    // callbacks forwarding calls to the implementation
+    assert!(code.contains(
+        "extern \"C\" fn count_callback<T: ListenerImpl>(\
+         data: *mut ::std::ffi::c_void, this: *const crate::ListenerSubclass) -> i32 {"
+    ));
+    assert!(code.contains(
+        "extern \"C\" fn notify_callback<T: ListenerImpl>(\
+         data: *mut ::std::ffi::c_void, this: *mut crate::ListenerSubclass, \
+         arg2: *const crate::QString) {"
+    ));
+
+    // set_impl
+    assert!(code.contains("impl crate::ListenerSubclass {"));
+    assert!(code.contains(
+        "pub unsafe fn set_impl<T: ListenerImpl>(&self, imp: T) \
+         -> ::std::result::Result<(), T> {"
+    ));
+    assert!(code.contains(
+        "if !crate::__ffi::ctr_set_data(this, data, Some(deleter::<T>)) {\n            \
+         return Err(*Box::from_raw(data as *mut T));"
+    ));
+    assert!(code.contains("crate::__ffi::ctr_set_count_callback(this, Some(count_callback::<T>));"));
+    assert!(
+        code.contains("crate::__ffi::ctr_set_notify_callback(this, Some(notify_callback::<T>));")
+    );
+}
@@ -1,7 +1,8 @@
 use crate::config::CrateDependencyKind;
 use crate::cpp_checks::CppChecks;
 use crate::cpp_data::{
-    inherits, CppItem, CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind,
+    inherits, CppConstantValue, CppItem, CppPath, CppPathItem, CppTypeDeclaration,
+    CppTypeDeclarationKind,
 };
 use crate::cpp_ffi_data::{
     CppCast, CppFfiArgumentMeaning, CppFfiFunction, CppFfiFunctionKind, CppFfiItem, CppFfiType,
@@ -9,19 +10,22 @@ use crate::cpp_ffi_data::{
 };
 use crate::cpp_ffi_generator::ffi_type;
 use crate::cpp_function::{CppFunction, CppOperator, ReturnValueAllocationPlace};
+use crate::cpp_parser::evaluate_literal;
 use crate::cpp_type::{
     is_qflags, CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind,
-    CppSpecificNumericType, CppSpecificNumericTypeKind, CppType, CppTypeRole,
+    CppSpecificNumericType, CppSpecificNumericTypeKind, CppTemplateArgumentValue, CppType,
+    CppTypeRole,
 };
 use crate::database::{DbItem, ItemId, ItemWithSource};
 use crate::processor::ProcessorData;
 use crate::rust_info::{
-    NameType, RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData,
-    RustFlagEnumImpl, RustFunction, RustFunctionArgument, RustFunctionCaptionStrategy,
-    RustFunctionKind, RustFunctionSelfArgKind, RustItem, RustModule, RustModuleKind, RustPathScope,
-    RustQtReceiverData, RustQtReceiverImpl, RustQtReceiverType, RustReexport, RustReexportSource,
-    RustSignalOrSlotGetter, RustSizedType, RustSpecialModuleKind, RustStruct, RustStructKind,
-    RustTraitAssociatedType, RustTraitImpl, RustTraitImplExtraKind, RustTypeCaptionStrategy,
+    NameType, RustConstant, RustConstantValue, RustEnumValue, RustExtraImpl, RustExtraImplKind,
+    RustFfiWrapperData, RustFlagEnumImpl, RustFunction, RustFunctionArgument,
+    RustFunctionCaptionStrategy, RustFunctionKind, RustFunctionSelfArgKind, RustItem, RustModule,
+    RustModuleKind, RustPathScope, RustQtReceiverData, RustQtReceiverImpl, RustQtReceiverType,
+    RustReexport, RustReexportSource, RustSignalOrSlotGetter, RustSizedType, RustSpecialModuleKind,
+    RustStruct, RustStructKind, RustSubclassFunction, RustSubclassImpl, RustTraitAssociatedType,
+    RustTraitImpl, RustTraitImplExtraKind, RustTypeAlias, RustTypeCaptionStrategy,
     RustWrapperTypeKind, UnnamedRustFunction,
 };
 use crate::rust_type::{
@@ -557,6 +561,7 @@ impl State<'_, '_> {
                 kind,
                 is_const,
                 target,
+                ..
             } => {
                 let rust_target = if target.deref() == &CppType::Void {
                     RustType::Common(RustCommonType {
@@ -678,7 +683,9 @@ impl State<'_, '_> {
                     generic_arguments: Some(vec![pointer]),
                 })
             }
-            CppType::TemplateParameter { .. } => bail!("invalid cpp type"),
+            CppType::TemplateParameter { .. } | CppType::TemplateArgumentValue(_) => {
+                bail!("invalid cpp type")
+            }
         };
 
         Ok(rust_type)
@@ -801,8 +808,32 @@ impl State<'_, '_> {
                         RustToFfiTypeConversion::UtilsRefToPtr {},
                     ));
                 }
+            } else if cpp_ffi_type.conversion() == &CppToFfiTypeConversion::RValueReferenceToPointer
+            {
+                if !argument_meaning.is_argument() {
+                    bail!("rvalue references are only supported in arguments");
+                }
+                // the value is moved from an object owned by the caller,
+                // and the moved-from object is deleted after the call
+                let is_deletable = if let Some(checks) = checks {
+                    self.is_type_deletable(cpp_ffi_type.ffi_type(), checks)?
+                } else {
+                    true
+                };
+                if !is_deletable {
+                    bail!("moved-from object can't be deleted");
+                }
+                api_to_ffi_conversion = RustToFfiTypeConversion::MoveCppBoxToPtr;
             } else if argument_meaning == &CppFfiArgumentMeaning::This {
                 api_to_ffi_conversion = RustToFfiTypeConversion::RefToPtr { lifetime: None };
+            } else if cpp_ffi_type.ffi_type().pointer_like_is_volatile()? {
+                // volatile values must only be accessed with volatile reads and writes,
+                // so safe wrappers are only used for non-class types
+                api_to_ffi_conversion = if target.is_class() {
+                    RustToFfiTypeConversion::None
+                } else {
+                    RustToFfiTypeConversion::VolatilePtrToPtr
+                };
             } else if argument_meaning == &CppFfiArgumentMeaning::ReturnValue {
                 if target.is_class() {
                     api_to_ffi_conversion =
@@ -844,6 +875,7 @@ impl State<'_, '_> {
                     kind,
                     is_const,
                     target,
+                    ..
                 } => {
                     if kind != &CppPointerLikeTypeKind::Reference {
                         bail!(
@@ -913,6 +945,7 @@ impl State<'_, '_> {
             kind: RustFunctionKind::FfiFunction,
             arguments: args,
             is_unsafe: false,
+            deprecation: None,
         };
         Ok(function)
     }
@@ -1023,6 +1056,9 @@ impl State<'_, '_> {
 
         if operator_info.second_arg_is_reference {
             let other_arg = &mut function.arguments[1].argument_type;
+            if other_arg.conversion() == &RustToFfiTypeConversion::MoveCppBoxToPtr {
+                bail!("moved argument can't be passed by reference");
+            }
             *other_arg = RustFinalType::new(
                 other_arg.ffi_type().clone(),
                 RustToFfiTypeConversion::RefTo(Box::new(other_arg.conversion().clone())),
@@ -1284,6 +1320,134 @@ impl State<'_, '_> {
         Ok(())
     }
 
+    /// Replaces the callback, deleter and user data arguments generated for
+    /// a `std::function` argument with a single closure argument.
+    fn convert_std_function_to_closure(
+        &self,
+        ffi_function: &CppFfiFunction,
+        function: &mut UnnamedRustFunction,
+        checks: &CppChecks,
+    ) -> Result<()> {
+        let (ffi_index, arguments, return_type) = match ffi_function
+            .arguments
+            .iter()
+            .enumerate()
+            .find_map(|(index, arg)| {
+                if let CppToFfiTypeConversion::StdFunctionToCallback {
+                    arguments,
+                    return_type,
+                } = arg.argument_type.conversion()
+                {
+                    Some((index, arguments, return_type))
+                } else {
+                    None
+                }
+            }) {
+            Some(data) => data,
+            None => return Ok(()),
+        };
+
+        let closure_arguments = arguments.iter().map_if_ok(|arg| {
+            self.rust_final_type(
+                arg,
+                // values are produced behind FFI, as in slot wrappers
+                &CppFfiArgumentMeaning::ReturnValue,
+                ReturnValueAllocationPlace::NotApplicable,
+                Some(checks),
+            )
+        })?;
+        let mut closure_return_type = self.rust_final_type(
+            return_type,
+            &CppFfiArgumentMeaning::Argument(0),
+            ReturnValueAllocationPlace::NotApplicable,
+            Some(checks),
+        )?;
+        // `impl Trait` is not allowed in closure bounds
+        if let RustToFfiTypeConversion::ImplCastInto(conversion) =
+            closure_return_type.conversion().clone()
+        {
+            closure_return_type =
+                RustFinalType::new(closure_return_type.ffi_type().clone(), *conversion)?;
+        }
+
+        function.arguments.retain(|arg| {
+            !matches!(
+                ffi_function.arguments[arg.ffi_index].meaning,
+                CppFfiArgumentMeaning::CallbackContext(_)
+            )
+        });
+        let arg = function
+            .arguments
+            .iter_mut()
+            .find(|arg| arg.ffi_index == ffi_index)
+            .ok_or_else(|| err_msg("callback argument not found"))?;
+        arg.argument_type = RustFinalType::new(
+            arg.argument_type.ffi_type().clone(),
+            RustToFfiTypeConversion::ClosureToCallback(Box::new(RustClosureToCallbackConversion {
+                closure_arguments,
+                closure_return_type,
+            })),
+        )?;
+        Ok(())
+    }
+
+    /// Replaces types in the public API of `rust_function` with Rust type aliases
+    /// corresponding to the C++ type aliases used in the declaration of `cpp_function`.
+    fn apply_type_aliases(
+        &self,
+        ffi_function: &CppFfiFunction,
+        cpp_function: &CppFunction,
+        rust_function: &mut UnnamedRustFunction,
+    ) -> Result<()> {
+        for arg in &mut rust_function.arguments {
+            let ffi_arg = ffi_function
+                .arguments
+                .get(arg.ffi_index)
+                .ok_or_else(|| err_msg("invalid ffi argument index"))?;
+            let alias_path = if let CppFfiArgumentMeaning::Argument(index) = &ffi_arg.meaning {
+                cpp_function
+                    .arguments
+                    .get(*index)
+                    .ok_or_else(|| err_msg("invalid cpp argument index"))?
+                    .type_alias
+                    .as_ref()
+            } else {
+                None
+            };
+            if let Some(alias) = alias_path.and_then(|path| self.find_type_alias(path)) {
+                let alias_type = RustType::Common(RustCommonType {
+                    path: alias.path.clone(),
+                    generic_arguments: None,
+                });
+                arg.argument_type = arg
+                    .argument_type
+                    .with_type_alias(&alias.target_type, &alias_type);
+            }
+        }
+        if let Some(alias_path) = &cpp_function.return_type_alias {
+            if let Some(alias) = self.find_type_alias(alias_path) {
+                let alias_type = RustType::Common(RustCommonType {
+                    path: alias.path.clone(),
+                    generic_arguments: None,
+                });
+                rust_function.return_type = rust_function
+                    .return_type
+                    .with_type_alias(&alias.target_type, &alias_type);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the Rust type alias generated for the C++ type alias `cpp_path`.
+    fn find_type_alias(&self, cpp_path: &CppPath) -> Option<&RustTypeAlias> {
+        self.data
+            .db
+            .find_rust_items_for_cpp_path(cpp_path, true)
+            // the alias is unknown if it wasn't parsed (e.g. it's not in the target headers)
+            .ok()?
+            .find_map(|item| item.item.as_type_alias_ref())
+    }
+
     /// Converts one function to a `RustSingleMethod`.
     fn process_rust_function(
         &self,
@@ -1300,13 +1464,23 @@ impl State<'_, '_> {
 
         let mut arguments = Vec::new();
         for (arg_index, arg) in function.arguments.iter().enumerate() {
-            if arg.meaning != CppFfiArgumentMeaning::ReturnValue {
-                let arg_type = self.rust_final_type(
-                    &arg.argument_type,
-                    &arg.meaning,
-                    function.allocation_place,
-                    Some(checks),
-                )?;
+            if arg.meaning != CppFfiArgumentMeaning::ReturnValue
+                && arg.meaning != CppFfiArgumentMeaning::Exception
+                && arg.meaning != CppFfiArgumentMeaning::VarArgCount
+            {
+                let arg_type = if arg.meaning == CppFfiArgumentMeaning::VarArgs {
+                    RustFinalType::new(
+                        self.ffi_type_to_rust_ffi_type(arg.argument_type.ffi_type())?,
+                        RustToFfiTypeConversion::VarArgsToPtr,
+                    )?
+                } else {
+                    self.rust_final_type(
+                        &arg.argument_type,
+                        &arg.meaning,
+                        function.allocation_place,
+                        Some(checks),
+                    )?
+                };
                 arguments.push(RustFunctionArgument {
                     ffi_index: arg_index,
                     argument_type: arg_type,
@@ -1343,6 +1517,36 @@ impl State<'_, '_> {
                 Some(checks),
             )?
         };
+
+        let cpp_item = self
+            .data
+            .db
+            .source_cpp_item(&item.id)?
+            .ok_or_else(|| err_msg("source cpp item not found"))?
+            .item;
+
+        if let CppFfiFunctionKind::FieldAccessor { accessor_type } = &function.kind {
+            // items of volatile arrays are accessed through `VolatilePtr` instead
+            let array_size = cpp_item
+                .as_field_ref()
+                .filter(|field| !field.is_volatile)
+                .and_then(|field| field.array_size);
+            if let Some(size) = array_size {
+                if accessor_type == &CppFieldAccessorType::ConstRefGetter
+                    || accessor_type == &CppFieldAccessorType::MutRefGetter
+                {
+                    // the FFI function returns a pointer to the first item
+                    return_type = RustFinalType::new(
+                        return_type.ffi_type().clone(),
+                        RustToFfiTypeConversion::ArrayRefToPtr {
+                            lifetime: None,
+                            size,
+                        },
+                    )?;
+                }
+            }
+        }
+
         if return_type.api_type().is_ref() && return_type.api_type().lifetime().is_none() {
             let mut found = false;
             for arg in &arguments {
@@ -1355,8 +1559,10 @@ impl State<'_, '_> {
             if !found {
                 let mut next_lifetime_num = 0;
                 for arg in &mut arguments {
+                    // the returned reference can't borrow from variadic arguments
                     if arg.argument_type.api_type().is_ref()
                         && arg.argument_type.api_type().lifetime().is_none()
+                        && arg.argument_type.conversion() != &RustToFfiTypeConversion::VarArgsToPtr
                     {
                         arg.argument_type = arg
                             .argument_type
@@ -1384,15 +1590,19 @@ impl State<'_, '_> {
             return_type,
             kind: RustFunctionKind::FfiWrapper(RustFfiWrapperData { ffi_function_path }),
             is_unsafe: true,
+            deprecation: cpp_item
+                .as_function_ref()
+                .and_then(|function| function.deprecation.clone()),
         };
         self.convert_callbacks_to_closure(&item.id, &mut unnamed_function, checks)?;
-
-        let cpp_item = self
-            .data
-            .db
-            .source_cpp_item(&item.id)?
-            .ok_or_else(|| err_msg("source cpp item not found"))?
-            .item;
+        self.convert_std_function_to_closure(function, &mut unnamed_function, checks)?;
+        if let Some(cpp_function) = cpp_item.as_function_ref() {
+            self.apply_type_aliases(function, cpp_function, &mut unnamed_function)?;
+        }
+        let catches_exceptions = function.exception_argument().is_some();
+        if catches_exceptions {
+            unnamed_function.return_type = unnamed_function.return_type.with_cpp_exception();
+        }
 
         if let CppFfiFunctionKind::Function = &function.kind {
             let cpp_function = cpp_item
@@ -1416,7 +1626,9 @@ impl State<'_, '_> {
             if cpp_function.operator.as_ref() == Some(&CppOperator::NotEqualTo) {
                 bail!("NotEqualTo is not needed in public API because PartialEq is used");
             }
-            if let Some(operator_info) = TraitImplInfo::new(cpp_function) {
+            // trait methods can't return `Result`
+            let operator_info = TraitImplInfo::new(cpp_function).filter(|_| !catches_exceptions);
+            if let Some(operator_info) = operator_info {
                 match State::process_operator_as_trait_impl(
                     unnamed_function.clone(),
                     operator_info,
@@ -1478,7 +1690,16 @@ impl State<'_, '_> {
             }
         }
 
-        let desired_path = self.generate_rust_path(cpp_path, NameType::ApiFunction(item))?;
+        let desired_path =
+            self.generate_rust_path(cpp_path, NameType::ApiFunction(item.clone()))?;
+        if desired_path.parent()? == self.special_module_paths[&RustSpecialModuleKind::Protected] {
+            // free functions can't have `self` argument
+            for arg in &mut unnamed_function.arguments {
+                if arg.name == "self" {
+                    arg.name = "this".to_string();
+                }
+            }
+        }
         results.push(ProcessedFfiItem::Function(FunctionWithDesiredPath {
             function: unnamed_function,
             desired_path,
@@ -1578,6 +1799,15 @@ impl State<'_, '_> {
     fn type_list_caption(&self, types: &[CppType], context: &RustPath) -> Result<String> {
         let mut captions = Vec::new();
         for arg in types {
+            if let CppType::TemplateArgumentValue(value) = arg {
+                // values of non-type template parameters are named after the value itself,
+                // e.g. `std::array<int, 4>` becomes `ArrayOfInt4`
+                captions.push(match value {
+                    CppTemplateArgumentValue::Integer(_) => value.ascii_caption(),
+                    CppTemplateArgumentValue::EnumValue(path) => path.last().name.clone(),
+                });
+                continue;
+            }
             let rust_type = self.rust_final_type(
                 &ffi_type(arg, CppTypeRole::NotReturnType)?,
                 &CppFfiArgumentMeaning::Argument(0),
@@ -1645,11 +1875,11 @@ impl State<'_, '_> {
                 }
             }
             CppFfiFunctionKind::FieldAccessor { accessor_type } => {
-                let field = cpp_item
-                    .as_field_ref()
+                let path = cpp_item
+                    .path()
                     .ok_or_else(|| err_msg("invalid source cpp item type"))?;
 
-                let name = &field.path.last().name;
+                let name = &path.last().name;
                 let function_name = match accessor_type {
                     CppFieldAccessorType::CopyGetter | CppFieldAccessorType::ConstRefGetter => {
                         name.to_string()
@@ -1682,6 +1912,30 @@ impl State<'_, '_> {
         }
     }
 
+    /// Returns true if the FFI function provides access to a protected member.
+    fn is_protected_function(&self, item: &DbItem<&CppFfiFunction>) -> Result<bool> {
+        Ok(self
+            .data
+            .db
+            .source_cpp_item(&item.id)?
+            .map_or(false, |cpp_item| cpp_item.item.is_protected()))
+    }
+
+    /// Returns scope for a function that provides access to protected member `cpp_path`.
+    /// Such functions are placed in the `protected` module, and their names
+    /// are prefixed with the name of the class.
+    fn protected_path_scope(&self, cpp_path: &CppPath) -> Result<RustPathScope> {
+        let class_type = self.find_wrapper_type(&cpp_path.parent()?)?;
+        let class_path = class_type
+            .item
+            .path()
+            .ok_or_else(|| err_msg("wrapper type must have a path"))?;
+        Ok(RustPathScope {
+            path: self.special_module_paths[&RustSpecialModuleKind::Protected].clone(),
+            prefix: Some(format!("{}_", class_path.last().to_snake_case())),
+        })
+    }
+
     fn generate_rust_path(&self, cpp_path: &CppPath, name_type: NameType<'_>) -> Result<RustPath> {
         if let Some(hook) = self.data.config.rust_path_hook() {
             if let Some(path) = hook(cpp_path, name_type.clone(), &self.data)? {
@@ -1689,6 +1943,9 @@ impl State<'_, '_> {
             }
         }
         let scope = match &name_type {
+            NameType::ApiFunction(item) if self.is_protected_function(item)? => {
+                self.protected_path_scope(cpp_path)?
+            }
             NameType::FfiFunction => RustPathScope {
                 path: self.special_module_paths[&RustSpecialModuleKind::Ffi].clone(),
                 prefix: None,
@@ -1697,13 +1954,17 @@ impl State<'_, '_> {
                 path: self.special_module_paths[&RustSpecialModuleKind::SizedTypes].clone(),
                 prefix: None,
             },
-            NameType::QtSlotWrapper { .. } | NameType::QtSignalWrapper { .. } => {
+            NameType::QtSlotWrapper { .. }
+            | NameType::QtSignalWrapper { .. }
+            | NameType::Subclass { .. }
+            | NameType::SubclassTrait { .. } => {
                 // crate root
                 self.default_path_scope()
             }
             NameType::Type { .. }
             | NameType::Module { .. }
             | NameType::EnumValue
+            | NameType::Constant
             | NameType::ApiFunction { .. }
             | NameType::ReceiverFunction { .. } => {
                 if let Ok(parent) = cpp_path.parent() {
@@ -1783,6 +2044,18 @@ impl State<'_, '_> {
             NameType::Module { .. } => self
                 .cpp_path_item_to_name(&cpp_path.last(), &scope.path, &name_type)?
                 .to_snake_case(),
+            NameType::Constant => {
+                let name = self.cpp_path_item_to_name(&cpp_path.last(), &scope.path, &name_type)?;
+                // names like `LIB_VERSION` are already in the right case
+                if name
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+                {
+                    name
+                } else {
+                    name.to_upper_case_words()
+                }
+            }
             NameType::FfiFunction => cpp_path.last().name.clone(),
             NameType::QtSlotWrapper { signal_arguments } => {
                 if signal_arguments.is_empty() {
@@ -1800,6 +2073,16 @@ impl State<'_, '_> {
                     format!("SignalOf_{}", captions).to_class_case()
                 }
             }
+            NameType::Subclass { base_class } => {
+                let name =
+                    self.cpp_path_item_to_name(base_class.last(), &scope.path, &name_type)?;
+                format!("{}_subclass", name).to_class_case()
+            }
+            NameType::SubclassTrait { base_class } => {
+                let name =
+                    self.cpp_path_item_to_name(base_class.last(), &scope.path, &name_type)?;
+                format!("{}_impl", name).to_class_case()
+            }
         };
 
         if name_type == NameType::FfiFunction {
@@ -1838,6 +2121,9 @@ impl State<'_, '_> {
             CppFfiItem::QtSignalWrapper(_) => {
                 bail!("signal wrappers do not need to be processed here");
             }
+            CppFfiItem::Subclass(_) => {
+                bail!("subclasses do not need to be processed here");
+            }
         }
     }
 
@@ -1869,14 +2155,20 @@ impl State<'_, '_> {
 
         let mut qt_slot_wrapper = None;
         let mut qt_signal_wrapper = None;
+        let mut subclass = None;
         if let Some(source_ffi_item) = self.data.db.source_ffi_item(&item.id)? {
             if let Some(item) = source_ffi_item
                 .clone()
                 .filter_map(|i| i.as_slot_wrapper_ref())
             {
                 qt_slot_wrapper = Some(item);
-            } else if let Some(item) = source_ffi_item.filter_map(|i| i.as_signal_wrapper_ref()) {
+            } else if let Some(item) = source_ffi_item
+                .clone()
+                .filter_map(|i| i.as_signal_wrapper_ref())
+            {
                 qt_signal_wrapper = Some(item);
+            } else if let Some(item) = source_ffi_item.filter_map(|i| i.as_subclass_ref()) {
+                subclass = Some(item);
             }
         }
 
@@ -1892,6 +2184,10 @@ impl State<'_, '_> {
             NameType::QtSignalWrapper {
                 signal_arguments: &wrapper.item.signal_arguments,
             }
+        } else if let Some(subclass) = &subclass {
+            NameType::Subclass {
+                base_class: &subclass.item.base_class,
+            }
         } else {
             NameType::Type {
                 is_from_other_crate,
@@ -1922,6 +2218,7 @@ impl State<'_, '_> {
                 }),
                 is_public: true,
                 qt_receiver_data: None,
+                deprecation: None,
             });
 
             rust_items.push(internal_rust_item);
@@ -2000,11 +2297,312 @@ impl State<'_, '_> {
             kind: RustStructKind::WrapperType(wrapper_kind),
             is_public: true,
             qt_receiver_data,
+            deprecation: data.deprecation.clone(),
         });
         rust_items.push(public_rust_item);
         Ok(rust_items)
     }
 
+    /// Generates a Rust constant for a C++ constant with a known value.
+    /// Accessor functions are generated for the constant as well.
+    /// Returns type of a Rust constant containing `value`
+    /// or `None` if such constants are not supported
+    /// or `value` doesn't fit in `cpp_type`.
+    fn constant_type(
+        &self,
+        cpp_type: &CppType,
+        value: &CppConstantValue,
+    ) -> Result<Option<RustType>> {
+        let constant_type = match (cpp_type, value) {
+            (CppType::BuiltInNumeric(_), CppConstantValue::Integer(value))
+            | (CppType::SpecificNumeric(_), CppConstantValue::Integer(value)) => {
+                if !cpp_type.can_hold_integer(*value) {
+                    return Ok(None);
+                }
+                self.ffi_type_to_rust_ffi_type(cpp_type)?
+            }
+            (CppType::BuiltInNumeric(numeric), CppConstantValue::Float(_))
+                if numeric.is_float() =>
+            {
+                self.ffi_type_to_rust_ffi_type(cpp_type)?
+            }
+            (
+                CppType::SpecificNumeric(CppSpecificNumericType {
+                    kind: CppSpecificNumericTypeKind::FloatingPoint,
+                    ..
+                }),
+                CppConstantValue::Float(_),
+            ) => self.ffi_type_to_rust_ffi_type(cpp_type)?,
+            (
+                CppType::PointerLike {
+                    kind: CppPointerLikeTypeKind::Pointer,
+                    target,
+                    ..
+                },
+                CppConstantValue::String(_),
+            ) if target.deref() == &CppType::BuiltInNumeric(CppBuiltInNumericType::Char) => {
+                RustType::PointerLike {
+                    kind: RustPointerLikeTypeKind::Reference {
+                        lifetime: Some("static".into()),
+                    },
+                    is_const: true,
+                    target: Box::new(RustType::Primitive("str".into())),
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(constant_type))
+    }
+
+    fn process_cpp_constant(
+        &self,
+        path: &CppPath,
+        cpp_type: &CppType,
+        value: &CppConstantValue,
+    ) -> Result<Vec<RustItem>> {
+        let constant_type = if let Some(t) = self.constant_type(cpp_type, value)? {
+            t
+        } else {
+            return Ok(Vec::new());
+        };
+        let rust_item = RustItem::Constant(RustConstant {
+            path: self.generate_rust_path(path, NameType::Constant)?,
+            constant_type,
+            value: RustConstantValue::Literal(value.clone()),
+            default_value_of_argument: None,
+        });
+        Ok(vec![rust_item])
+    }
+
+    /// Returns name of the Rust API function generated for the C++ function `cpp_item_id`.
+    fn rust_function_name(&self, cpp_item_id: &ItemId) -> Option<String> {
+        for ffi_item in self.data.db.items_from_source(cpp_item_id) {
+            if !ffi_item.item.is_ffi_item() {
+                continue;
+            }
+            for rust_item in self.data.db.items_from_source(&ffi_item.id) {
+                let function = rust_item
+                    .item
+                    .as_rust_item()
+                    .and_then(|item| item.as_function_ref());
+                if let Some(function) = function {
+                    if let RustFunctionKind::FfiWrapper(_) = &function.kind {
+                        return Some(function.path.last().to_string());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Generates constants containing default values of arguments of `function`
+    /// if they are literals or enum values. Names of the constants start with
+    /// `function_name`, the name of the corresponding Rust function.
+    fn process_default_argument_values(
+        &self,
+        cpp_item: DbItem<&CppItem>,
+        function: &CppFunction,
+        function_name: &str,
+    ) -> Result<Vec<RustItem>> {
+        let is_template = function.path.items().iter().any(|item| {
+            item.template_arguments
+                .iter()
+                .flatten()
+                .any(|arg| arg.is_or_contains_template_parameter())
+        });
+        if is_template || function.is_operator() {
+            return Ok(Vec::new());
+        }
+        if let Some(original_item) = self.data.db.original_cpp_item(&cpp_item.id)? {
+            if let Some(original_function) = original_item.item.as_function_ref() {
+                if original_function.arguments.len() != function.arguments.len() {
+                    // defaults are only exported for the full version of the function
+                    return Ok(Vec::new());
+                }
+            }
+        }
+
+        let mut rust_items = Vec::new();
+        for (index, arg) in function.arguments.iter().enumerate() {
+            let default_value = if let Some(value) = &arg.default_value {
+                value
+            } else {
+                continue;
+            };
+            let (constant_type, value) = match &arg.argument_type {
+                CppType::Enum { path } => {
+                    let value_name = default_value.rsplit("::").next().unwrap_or_default();
+                    let is_identifier = !value_name.is_empty()
+                        && value_name
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    if !is_identifier {
+                        continue;
+                    }
+                    let value_path = path.join(CppPathItem::from_good_str(value_name));
+                    let rust_value = self
+                        .data
+                        .db
+                        .find_rust_items_for_cpp_path(&value_path, true)
+                        .ok()
+                        .and_then(|mut items| items.find_map(|item| item.item.as_enum_value_ref()));
+                    let rust_value = if let Some(rust_value) = rust_value {
+                        rust_value
+                    } else {
+                        // the default value is not a plain value of the argument's enum
+                        continue;
+                    };
+                    let enum_type = if let Ok(enum_type) = self.find_wrapper_type(path) {
+                        enum_type
+                    } else {
+                        continue;
+                    };
+                    let constant_type = RustType::Common(RustCommonType {
+                        path: enum_type.item.path().unwrap().clone(),
+                        generic_arguments: None,
+                    });
+                    (
+                        constant_type,
+                        RustConstantValue::EnumValue(rust_value.path.clone()),
+                    )
+                }
+                cpp_type => {
+                    let mut value = if let Some(value) = evaluate_literal(default_value) {
+                        value
+                    } else {
+                        continue;
+                    };
+                    if let (CppType::BuiltInNumeric(numeric), CppConstantValue::Integer(integer)) =
+                        (cpp_type, &value)
+                    {
+                        if numeric.is_float() {
+                            value = CppConstantValue::Float(format!("{:?}", *integer as f64));
+                        }
+                    }
+                    if let Ok(Some(constant_type)) = self.constant_type(cpp_type, &value) {
+                        (constant_type, RustConstantValue::Literal(value))
+                    } else {
+                        continue;
+                    }
+                }
+            };
+            let name =
+                CppPathItem::from_good_str(&format!("{}_default_{}", function_name, arg.name));
+            let cpp_path = if let Ok(parent) = function.path.parent() {
+                parent.join(name)
+            } else {
+                CppPath::from_item(name)
+            };
+            rust_items.push(RustItem::Constant(RustConstant {
+                path: self.generate_rust_path(&cpp_path, NameType::Constant)?,
+                constant_type,
+                value,
+                default_value_of_argument: Some(index),
+            }));
+        }
+        Ok(rust_items)
+    }
+
+    /// Generates signal and slot getters for Qt signals and slots.
+    fn process_qt_receiver_function(
+        &self,
+        cpp_item: DbItem<&CppItem>,
+        cpp_function: &CppFunction,
+    ) -> Result<Vec<RustItem>> {
+        let receiver_type = if let Some(member_data) = &cpp_function.member {
+            if member_data.is_signal {
+                RustQtReceiverType::Signal
+            } else if member_data.is_slot {
+                RustQtReceiverType::Slot
+            } else {
+                return Ok(Vec::new());
+            }
+        } else {
+            return Ok(Vec::new());
+        };
+
+        let source_ffi_item = self.data.db.source_ffi_item(&cpp_item.id)?;
+        let has_source_slot_wrapper = source_ffi_item
+            .clone()
+            .map_or(false, |item| item.item.is_slot_wrapper());
+        if has_source_slot_wrapper {
+            // no need to add slot accessors because
+            // `AsReceiver` is implemented directly on structs
+            return Ok(Vec::new());
+        }
+        let is_wrapped_signal = source_ffi_item.map_or(false, |item| item.item.is_signal_wrapper());
+
+        let original_item = self
+            .data
+            .db
+            .original_cpp_item(&cpp_item.id)?
+            .ok_or_else(|| err_msg("cpp item must have original cpp item"))?;
+
+        if let Some(original_function) = original_item.item.as_function_ref() {
+            if original_function.arguments.len() != cpp_function.arguments.len() {
+                return Ok(Vec::new());
+            }
+        }
+
+        let function_kind = RustFunctionKind::SignalOrSlotGetter(RustSignalOrSlotGetter {});
+
+        let path = self.generate_rust_path(
+            &cpp_function.path,
+            NameType::ReceiverFunction {
+                receiver_type,
+                is_wrapped_signal,
+            },
+        )?;
+
+        let class_type = self.find_wrapper_type(&cpp_function.path.parent()?)?;
+        let self_type = RustType::PointerLike {
+            kind: RustPointerLikeTypeKind::Reference { lifetime: None },
+            is_const: true,
+            target: Box::new(RustType::Common(RustCommonType {
+                path: class_type.item.path().unwrap().clone(),
+                generic_arguments: None,
+            })),
+        };
+
+        let self_type = RustFinalType::new(self_type, RustToFfiTypeConversion::None)?;
+
+        let return_type_path = match receiver_type {
+            RustQtReceiverType::Signal => self.qt_core_path().join("Signal"),
+            RustQtReceiverType::Slot => self.qt_core_path().join("Receiver"),
+        };
+
+        let arguments = cpp_function
+            .arguments
+            .iter()
+            .map_if_ok(|arg| -> Result<_> {
+                // TODO: rust generator shouldn't know about cpp ffi types
+                let ffi_type = ffi_type(&arg.argument_type, CppTypeRole::NotReturnType)?;
+                self.ffi_type_to_rust_ffi_type(ffi_type.ffi_type())
+            })?;
+
+        let return_type = RustType::Common(RustCommonType {
+            path: return_type_path,
+            generic_arguments: Some(vec![RustType::Tuple(arguments)]),
+        });
+
+        let return_type = RustFinalType::new(return_type, RustToFfiTypeConversion::None)?;
+
+        let rust_function = RustFunction {
+            is_public: true,
+            is_unsafe: false,
+            path,
+            kind: function_kind,
+            arguments: vec![RustFunctionArgument {
+                argument_type: self_type,
+                name: "self".to_string(),
+                ffi_index: 42,
+            }],
+            return_type,
+            deprecation: cpp_function.deprecation.clone(),
+        };
+        Ok(vec![RustItem::Function(rust_function)])
+    }
+
     fn process_cpp_item(&self, cpp_item: DbItem<&CppItem>) -> Result<Vec<RustItem>> {
         if let Some(ffi_item) = self.data.db.source_ffi_item(&cpp_item.id)? {
             if !self.data.db.cpp_checks(&ffi_item.id)?.any_success() {
@@ -2027,11 +2625,11 @@ impl State<'_, '_> {
                 });
                 Ok(vec![rust_item])
             }
-            CppItem::Type(data) => match data.kind {
-                CppTypeDeclarationKind::Class { .. } => {
+            CppItem::Type(data) => match &data.kind {
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union => {
                     self.process_cpp_class(cpp_item.map(|v| v.as_type_ref().unwrap()))
                 }
-                CppTypeDeclarationKind::Enum => {
+                CppTypeDeclarationKind::Enum { underlying_type } => {
                     let rust_path = self.generate_rust_path(
                         &data.path,
                         NameType::Type {
@@ -2040,9 +2638,12 @@ impl State<'_, '_> {
                     )?;
                     let rust_item = RustItem::Struct(RustStruct {
                         path: rust_path,
-                        kind: RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper),
+                        kind: RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper {
+                            underlying_type: self.ffi_type_to_rust_ffi_type(underlying_type)?,
+                        }),
                         is_public: true,
                         qt_receiver_data: None,
+                        deprecation: data.deprecation.clone(),
                     });
 
                     Ok(vec![rust_item])
@@ -2054,105 +2655,60 @@ impl State<'_, '_> {
                 let rust_item = RustItem::EnumValue(RustEnumValue {
                     path: rust_path,
                     value: value.value,
+                    deprecation: value.deprecation.clone(),
                 });
 
                 Ok(vec![rust_item])
             }
             CppItem::Function(cpp_function) => {
-                let receiver_type = if let Some(member_data) = &cpp_function.member {
-                    if member_data.is_signal {
-                        RustQtReceiverType::Signal
-                    } else if member_data.is_slot {
-                        RustQtReceiverType::Slot
-                    } else {
-                        return Ok(Vec::new());
-                    }
-                } else {
-                    return Ok(Vec::new());
-                };
-
-                let source_ffi_item = self.data.db.source_ffi_item(&cpp_item.id)?;
-                let has_source_slot_wrapper = source_ffi_item
-                    .clone()
-                    .map_or(false, |item| item.item.is_slot_wrapper());
-                if has_source_slot_wrapper {
-                    // no need to add slot accessors because
-                    // `AsReceiver` is implemented directly on structs
-                    return Ok(Vec::new());
+                self.process_qt_receiver_function(cpp_item, cpp_function)
+            }
+            CppItem::ClassField(field) => match &field.value {
+                Some(value) if field.is_static && field.is_const => {
+                    self.process_cpp_constant(&field.path, &field.field_type, value)
                 }
-                let is_wrapped_signal =
-                    source_ffi_item.map_or(false, |item| item.item.is_signal_wrapper());
-
-                let original_item = self
-                    .data
-                    .db
-                    .original_cpp_item(&cpp_item.id)?
-                    .ok_or_else(|| err_msg("cpp item must have original cpp item"))?;
-
-                if let Some(original_function) = original_item.item.as_function_ref() {
-                    if original_function.arguments.len() != cpp_function.arguments.len() {
-                        return Ok(Vec::new());
-                    }
+                _ => Ok(Vec::new()),
+            },
+            CppItem::GlobalVariable(variable) => match &variable.value {
+                Some(value) if variable.is_const => {
+                    self.process_cpp_constant(&variable.path, &variable.variable_type, value)
                 }
-
-                let function_kind = RustFunctionKind::SignalOrSlotGetter(RustSignalOrSlotGetter {});
-
-                let path = self.generate_rust_path(
-                    &cpp_function.path,
-                    NameType::ReceiverFunction {
-                        receiver_type,
-                        is_wrapped_signal,
-                    },
-                )?;
-
-                let class_type = self.find_wrapper_type(&cpp_function.path.parent()?)?;
-                let self_type = RustType::PointerLike {
-                    kind: RustPointerLikeTypeKind::Reference { lifetime: None },
-                    is_const: true,
-                    target: Box::new(RustType::Common(RustCommonType {
-                        path: class_type.item.path().unwrap().clone(),
-                        generic_arguments: None,
-                    })),
-                };
-
-                let self_type = RustFinalType::new(self_type, RustToFfiTypeConversion::None)?;
-
-                let return_type_path = match receiver_type {
-                    RustQtReceiverType::Signal => self.qt_core_path().join("Signal"),
-                    RustQtReceiverType::Slot => self.qt_core_path().join("Receiver"),
+                _ => Ok(Vec::new()),
+            },
+            CppItem::MacroConstant(constant) => {
+                self.process_cpp_constant(&constant.path, &constant.value_type(), &constant.value)
+            }
+            CppItem::TypeAlias(alias) => {
+                let target_type = match &alias.target_type {
+                    CppType::Class(path) if is_qflags(path) => {
+                        // QFlags is not wrapped as a class
+                        let template_arguments = path.last().template_arguments.as_ref();
+                        let enum_path = match template_arguments.and_then(|args| args.get(0)) {
+                            Some(CppType::Enum { path }) => path,
+                            _ => bail!("invalid QFlags argument type"),
+                        };
+                        let rust_enum_path = self
+                            .find_wrapper_type(enum_path)?
+                            .item
+                            .path()
+                            .ok_or_else(|| err_msg("enum wrapper has no path"))?
+                            .clone();
+                        self.create_qflags(&rust_enum_path)
+                    }
+                    _ => self.ffi_type_to_rust_ffi_type(&alias.target_type)?,
                 };
-
-                let arguments = cpp_function
-                    .arguments
-                    .iter()
-                    .map_if_ok(|arg| -> Result<_> {
-                        // TODO: rust generator shouldn't know about cpp ffi types
-                        let ffi_type = ffi_type(&arg.argument_type, CppTypeRole::NotReturnType)?;
-                        self.ffi_type_to_rust_ffi_type(ffi_type.ffi_type())
-                    })?;
-
-                let return_type = RustType::Common(RustCommonType {
-                    path: return_type_path,
-                    generic_arguments: Some(vec![RustType::Tuple(arguments)]),
+                let rust_item = RustItem::TypeAlias(RustTypeAlias {
+                    path: self.generate_rust_path(
+                        &alias.path,
+                        NameType::Type {
+                            is_from_other_crate: false,
+                        },
+                    )?,
+                    target_type,
                 });
-
-                let return_type = RustFinalType::new(return_type, RustToFfiTypeConversion::None)?;
-
-                let rust_function = RustFunction {
-                    is_public: true,
-                    is_unsafe: false,
-                    path,
-                    kind: function_kind,
-                    arguments: vec![RustFunctionArgument {
-                        argument_type: self_type,
-                        name: "self".to_string(),
-                        ffi_index: 42,
-                    }],
-                    return_type,
-                };
-                Ok(vec![RustItem::Function(rust_function)])
+                Ok(vec![rust_item])
             }
-            CppItem::ClassField(_) | CppItem::ClassBase(_) => {
+            CppItem::ClassBase(_) => {
                 // only need to process FFI items
                 Ok(Vec::new())
             }
@@ -2206,13 +2762,16 @@ impl State<'_, '_> {
             RustSpecialModuleKind::CrateRoot => vec![crate_name],
             RustSpecialModuleKind::Ffi => vec![crate_name, "__ffi".to_string()],
             RustSpecialModuleKind::Ops => vec![crate_name, "ops".to_string()],
+            RustSpecialModuleKind::Protected => vec![crate_name, "protected".to_string()],
             RustSpecialModuleKind::SizedTypes => vec![crate_name, "__sized_types".to_string()],
         };
         let rust_path = RustPath::from_parts(rust_path_parts);
 
         let rust_item = RustItem::Module(RustModule {
             is_public: match kind {
-                RustSpecialModuleKind::CrateRoot | RustSpecialModuleKind::Ops => true,
+                RustSpecialModuleKind::CrateRoot
+                | RustSpecialModuleKind::Ops
+                | RustSpecialModuleKind::Protected => true,
                 RustSpecialModuleKind::Ffi | RustSpecialModuleKind::SizedTypes => false,
             },
             path: rust_path.clone(),
@@ -2235,9 +2794,64 @@ impl State<'_, '_> {
         Ok(())
     }
 
+    /// Generates constants for default values of arguments of C++ functions.
+    /// Names of the constants depend on the final names of Rust functions,
+    /// so this must be done after `finalize_functions`.
+    fn generate_default_argument_constants(&mut self) -> Result<()> {
+        for cpp_item_id in self.data.db.cpp_item_ids().collect_vec() {
+            // constants generated by a previous run are kept
+            let is_processed = self
+                .data
+                .db
+                .items_from_source(&cpp_item_id)
+                .filter_map(|item| item.item.as_rust_item())
+                .any(|item| item.as_constant_ref().is_some());
+            if is_processed {
+                continue;
+            }
+            let function_name = if let Some(name) = self.rust_function_name(&cpp_item_id) {
+                name
+            } else {
+                continue;
+            };
+            let cpp_item = self.data.db.cpp_item(&cpp_item_id)?;
+            let function = if let Some(function) = cpp_item.item.as_function_ref() {
+                function
+            } else {
+                continue;
+            };
+            let rust_items = match self.process_default_argument_values(
+                cpp_item.clone(),
+                function,
+                &function_name,
+            ) {
+                Ok(rust_items) => rust_items,
+                Err(err) => {
+                    debug!(
+                        "failed to process default argument values: {} {}: {}",
+                        cpp_item.id, &cpp_item.item, err
+                    );
+                    print_trace(&err, Some(log::Level::Trace));
+                    continue;
+                }
+            };
+            for rust_item in rust_items {
+                self.add_rust_item(Some(cpp_item_id.clone()), rust_item)?;
+            }
+        }
+        Ok(())
+    }
+
     fn process_cpp_items(&mut self) -> Result<()> {
         let mut processed_ids = HashSet::new();
-        let all_cpp_item_ids = self.data.db.cpp_item_ids().collect_vec();
+        // items processed by a previous run are skipped; the C++ parser deletes
+        // Rust items of C++ items that use changed types
+        let all_cpp_item_ids = self
+            .data
+            .db
+            .cpp_item_ids()
+            .filter(|id| !self.data.db.has_rust_items(id))
+            .collect_vec();
         loop {
             let mut any_processed = false;
             for cpp_item_id in all_cpp_item_ids.clone() {
@@ -2356,6 +2970,18 @@ impl State<'_, '_> {
     ) -> Result<()> {
         let all_strategies = RustFunctionCaptionStrategy::all();
 
+        // Wrapper functions of a previous run are replaced. Names of overloaded functions
+        // depend on the whole group, so the group is always named again, even if only
+        // some of its functions were added or changed since the previous run.
+        self.data.db.delete_items(|item| {
+            item.item
+                .as_rust_item()
+                .and_then(|item| item.as_function_ref())
+                .map_or(false, |function| {
+                    matches!(function.kind, RustFunctionKind::FfiWrapper(_))
+                })
+        });
+
         for (_group_path, functions) in grouped_functions {
             let mut chosen_strategy = None;
             if functions.len() > 1 {
@@ -2400,6 +3026,197 @@ impl State<'_, '_> {
         }
         Ok(())
     }
+
+    /// Returns path of the Rust FFI function generated for the method `name`
+    /// of a class generated by ritual.
+    fn find_generated_ffi_function(&self, class_path: &CppPath, name: &str) -> Result<RustPath> {
+        let cpp_path = class_path.join(CppPathItem::from_good_str(name));
+        for ffi_item in self.data.db.ffi_items() {
+            let is_match = ffi_item.item.as_function_ref().map_or(false, |f| {
+                f.allocation_place != ReturnValueAllocationPlace::Stack
+            }) && self
+                .data
+                .db
+                .source_cpp_item(&ffi_item.id)?
+                .and_then(|item| item.item.as_function_ref())
+                .map_or(false, |f| f.path == cpp_path);
+            if !is_match {
+                continue;
+            }
+            let rust_function = self
+                .data
+                .db
+                .rust_items()
+                .filter(|item| item.source_id.as_ref() == Some(&ffi_item.id))
+                .filter_map(|item| item.item.as_function_ref())
+                .find(|f| f.kind.is_ffi_function());
+            if let Some(rust_function) = rust_function {
+                return Ok(rust_function.path.clone());
+            }
+        }
+        bail!("FFI function not found: {}", cpp_path.to_cpp_pseudo_code())
+    }
+
+    /// Generates the trait for overriding virtual methods of a subclass
+    /// generated from the FFI item `ffi_item_id`.
+    fn process_subclass(&self, ffi_item_id: &ItemId) -> Result<RustItem> {
+        let ffi_item = self.data.db.ffi_item(ffi_item_id)?;
+        let subclass = ffi_item
+            .item
+            .as_subclass_ref()
+            .ok_or_else(|| err_msg("subclass expected"))?;
+        let checks = self.data.db.cpp_checks(ffi_item_id)?;
+
+        let target_type = self.find_wrapper_type(&subclass.class_path)?;
+        let target_path = target_type
+            .item
+            .path()
+            .ok_or_else(|| err_msg("subclass rust item must have path"))?
+            .clone();
+
+        let mut functions = Vec::new();
+        for function in &subclass.functions {
+            let this_type = RustFinalType::new(
+                self.ffi_type_to_rust_ffi_type(&function.callback_type.arguments[1])?,
+                RustToFfiTypeConversion::UtilsPtrToPtr {},
+            )?;
+
+            let arguments = function
+                .arguments
+                .iter()
+                .zip(&function.function.arguments)
+                .enumerate()
+                .map_if_ok(|(index, (arg, cpp_arg))| -> Result<_> {
+                    let argument_type =
+                        if let CppToFfiTypeConversion::ValueToPointer { .. } = arg.conversion() {
+                            // the value is borrowed from the caller
+                            RustFinalType::new(
+                                self.ffi_type_to_rust_ffi_type(arg.ffi_type())?,
+                                RustToFfiTypeConversion::UtilsRefToPtr {},
+                            )?
+                        } else {
+                            self.rust_final_type(
+                                arg,
+                                &CppFfiArgumentMeaning::ReturnValue,
+                                ReturnValueAllocationPlace::NotApplicable,
+                                Some(&checks),
+                            )?
+                        };
+                    let name = if cpp_arg.name.is_empty() {
+                        format!("arg{}", index)
+                    } else {
+                        cpp_arg.name.to_snake_case()
+                    };
+                    Ok(RustFunctionArgument {
+                        argument_type,
+                        name: sanitize_rust_identifier(&name, false),
+                        // the callback receives user data and `this` first
+                        ffi_index: index + 2,
+                    })
+                })?;
+
+            let return_type = if let CppToFfiTypeConversion::ValueToPointer { .. } =
+                function.return_type.conversion()
+            {
+                let return_type = self.rust_final_type(
+                    &function.return_type,
+                    &CppFfiArgumentMeaning::ReturnValue,
+                    ReturnValueAllocationPlace::Heap,
+                    Some(&checks),
+                )?;
+                if return_type.conversion() != &RustToFfiTypeConversion::CppBoxToPtr {
+                    bail!("returned value must be passed in CppBox");
+                }
+                return_type
+            } else {
+                let return_type = self.rust_final_type(
+                    &function.return_type,
+                    &CppFfiArgumentMeaning::Argument(0),
+                    ReturnValueAllocationPlace::NotApplicable,
+                    Some(&checks),
+                )?;
+                // `impl Trait` is not allowed in trait method return types
+                if let RustToFfiTypeConversion::ImplCastInto(conversion) =
+                    return_type.conversion().clone()
+                {
+                    RustFinalType::new(return_type.ffi_type().clone(), *conversion)?
+                } else {
+                    return_type
+                }
+            };
+
+            let is_pure_virtual = function
+                .function
+                .member
+                .as_ref()
+                .map_or(false, |m| m.is_pure_virtual);
+            let base_function = if is_pure_virtual {
+                None
+            } else {
+                Some(self.find_generated_ffi_function(
+                    &subclass.class_path,
+                    &format!("base_{}", function.name),
+                )?)
+            };
+
+            functions.push(RustSubclassFunction {
+                name: sanitize_rust_identifier(&function.name, false),
+                cpp_declaration: function.function.short_text(),
+                this_type,
+                arguments,
+                return_type,
+                set_callback_function: self.find_generated_ffi_function(
+                    &subclass.class_path,
+                    &format!("set_{}_callback", function.name),
+                )?,
+                base_function,
+            });
+        }
+
+        let trait_path = self.generate_rust_path(
+            &subclass.base_class,
+            NameType::SubclassTrait {
+                base_class: &subclass.base_class,
+            },
+        )?;
+        Ok(RustItem::ExtraImpl(RustExtraImpl {
+            // the trait is declared in the module of the extra impl
+            parent_path: trait_path.parent()?,
+            kind: RustExtraImplKind::Subclass(RustSubclassImpl {
+                set_data_function: self
+                    .find_generated_ffi_function(&subclass.class_path, "set_data")?,
+                target_path,
+                trait_path,
+                functions,
+            }),
+        }))
+    }
+
+    /// Generates traits for overriding virtual methods of generated subclasses.
+    /// This is done after processing FFI items because the traits use
+    /// FFI functions of the subclasses.
+    fn process_subclasses(&mut self) -> Result<()> {
+        for ffi_item_id in self.data.db.ffi_item_ids().collect_vec() {
+            let ffi_item = self.data.db.ffi_item(&ffi_item_id)?;
+            // items processed by a previous run are skipped
+            if !ffi_item.item.is_subclass() || self.data.db.has_rust_items(&ffi_item_id) {
+                continue;
+            }
+            if !self.data.db.cpp_checks(&ffi_item_id)?.any_success() {
+                continue;
+            }
+            match self.process_subclass(&ffi_item_id) {
+                Ok(rust_item) => {
+                    self.add_rust_item(Some(ffi_item_id), rust_item)?;
+                }
+                Err(err) => {
+                    debug!("failed to process subclass: {}: {}", ffi_item_id, err);
+                    print_trace(&err, Some(log::Level::Trace));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
@@ -2411,6 +3228,7 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
         RustSpecialModuleKind::CrateRoot,
         RustSpecialModuleKind::Ffi,
         RustSpecialModuleKind::Ops,
+        RustSpecialModuleKind::Protected,
         RustSpecialModuleKind::SizedTypes,
     ] {
         state.generate_special_module(module)?;
@@ -2431,6 +3249,8 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     state.process_cpp_items()?;
     let grouped_functions = state.process_ffi_items()?;
     state.finalize_functions(grouped_functions)?;
+    state.generate_default_argument_constants()?;
+    state.process_subclasses()?;
 
     Ok(())
 }
@@ -1,6 +1,6 @@
 //! Types holding information about generates Rust API.
 
-use crate::cpp_data::CppPath;
+use crate::cpp_data::{CppConstantValue, CppDeprecation, CppPath};
 use crate::cpp_ffi_data::CppFfiFunction;
 use crate::cpp_type::CppType;
 use crate::database::DbItem;
@@ -17,6 +17,39 @@ pub struct RustEnumValue {
     pub path: RustPath,
     /// Corresponding value
     pub value: i64,
+    /// Set if the source C++ item is deprecated
+    pub deprecation: Option<CppDeprecation>,
+}
+
+/// Value of a generated Rust constant
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum RustConstantValue {
+    /// Literal value evaluated from C++ code
+    Literal(CppConstantValue),
+    /// Path to the Rust constant of a C++ enum value
+    EnumValue(RustPath),
+}
+
+/// Rust constant generated from a C++ constant variable or static field
+/// or from a default value of a function argument
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RustConstant {
+    pub path: RustPath,
+    /// Type of the constant
+    pub constant_type: RustType,
+    /// Value evaluated from the C++ initializer
+    pub value: RustConstantValue,
+    /// Index of the argument of the source C++ function
+    /// if the constant contains the argument's default value
+    pub default_value_of_argument: Option<usize>,
+}
+
+/// Rust type alias generated from a C++ `typedef` or `using` declaration
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RustTypeAlias {
+    pub path: RustPath,
+    /// Type the alias refers to
+    pub target_type: RustType,
 }
 
 /// Information about a Qt slot wrapper on Rust side
@@ -29,9 +62,14 @@ pub struct RustQtSlotWrapper {
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum RustWrapperTypeKind {
-    EnumWrapper,
+    EnumWrapper {
+        /// FFI type of the enum's values (e.g. `std::os::raw::c_int` or `u8`)
+        underlying_type: RustType,
+    },
     ImmovableClassWrapper,
-    MovableClassWrapper { sized_type_path: RustPath },
+    MovableClassWrapper {
+        sized_type_path: RustPath,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -87,6 +125,9 @@ pub struct RustStruct {
     pub is_public: bool,
 
     pub qt_receiver_data: Option<RustQtReceiverData>,
+
+    /// Set if the source C++ type is deprecated
+    pub deprecation: Option<CppDeprecation>,
 }
 
 /// Location of a Rust method.
@@ -161,6 +202,7 @@ pub struct UnnamedRustFunction {
     pub kind: RustFunctionKind,
     pub arguments: Vec<RustFunctionArgument>,
     pub return_type: RustFinalType,
+    pub deprecation: Option<CppDeprecation>,
 }
 
 impl UnnamedRustFunction {
@@ -172,6 +214,7 @@ impl UnnamedRustFunction {
             kind: self.kind,
             arguments: self.arguments,
             return_type: self.return_type,
+            deprecation: self.deprecation,
         }
     }
 
@@ -300,6 +343,9 @@ pub struct RustFunction {
     pub arguments: Vec<RustFunctionArgument>,
     /// C++ and Rust return types at all levels.
     pub return_type: RustFinalType,
+
+    /// Set if the source C++ function is deprecated
+    pub deprecation: Option<CppDeprecation>,
 }
 
 /// Information about type of `self` argument of the function.
@@ -352,6 +398,7 @@ pub enum RustSpecialModuleKind {
     CrateRoot,
     Ffi,
     Ops,
+    Protected,
     SizedTypes,
 }
 
@@ -369,6 +416,7 @@ impl RustModuleKind {
                 RustSpecialModuleKind::CrateRoot => true,
                 RustSpecialModuleKind::Ffi => false,
                 RustSpecialModuleKind::Ops => true,
+                RustSpecialModuleKind::Protected => true,
                 RustSpecialModuleKind::SizedTypes => false,
             },
             RustModuleKind::CppNamespace { .. } => true,
@@ -479,10 +527,43 @@ pub struct RustFlagEnumImpl {
     pub enum_path: RustPath,
 }
 
+/// Virtual method that can be overridden by implementing a subclass trait.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustSubclassFunction {
+    /// Name of the trait method
+    pub name: String,
+    /// C++ declaration of the overridden method (used in documentation)
+    pub cpp_declaration: String,
+    /// Type of the `this` argument passed to the trait method
+    pub this_type: RustFinalType,
+    /// Arguments of the trait method. `ffi_index` refers to
+    /// the arguments of the callback (excluding the user data).
+    pub arguments: Vec<RustFunctionArgument>,
+    pub return_type: RustFinalType,
+    /// FFI function that sets the callback
+    pub set_callback_function: RustPath,
+    /// FFI function that calls the base class implementation
+    /// (`None` for pure virtual methods)
+    pub base_function: Option<RustPath>,
+}
+
+/// Trait for overriding virtual methods of a generated C++ subclass
+/// and the method that installs an implementation of the trait.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustSubclassImpl {
+    /// Rust type of the C++ subclass
+    pub target_path: RustPath,
+    pub trait_path: RustPath,
+    /// FFI function that sets the user data of the subclass
+    pub set_data_function: RustPath,
+    pub functions: Vec<RustSubclassFunction>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RustExtraImplKind {
     FlagEnum(RustFlagEnumImpl),
     QtReceiverImpl(RustQtReceiverImpl),
+    Subclass(RustSubclassImpl),
 }
 
 impl RustExtraImplKind {
@@ -494,6 +575,9 @@ impl RustExtraImplKind {
             RustExtraImplKind::QtReceiverImpl(_) => {
                 matches!(other, RustExtraImplKind::QtReceiverImpl(_))
             }
+            RustExtraImplKind::Subclass(_) => {
+                matches!(other, RustExtraImplKind::Subclass(_))
+            }
         }
     }
 }
@@ -522,6 +606,8 @@ pub enum RustItem {
     Module(RustModule),
     Struct(RustStruct),
     EnumValue(RustEnumValue),
+    Constant(RustConstant),
+    TypeAlias(RustTypeAlias),
     TraitImpl(RustTraitImpl),
     ExtraImpl(RustExtraImpl),
     Function(RustFunction),
@@ -534,6 +620,8 @@ impl RustItem {
             RustItem::Module(data) => Some(&data.path),
             RustItem::Struct(data) => Some(&data.path),
             RustItem::EnumValue(data) => Some(&data.path),
+            RustItem::Constant(data) => Some(&data.path),
+            RustItem::TypeAlias(data) => Some(&data.path),
             RustItem::Function(data) => Some(&data.path),
             RustItem::Reexport(data) => Some(&data.path),
             RustItem::TraitImpl(_) | RustItem::ExtraImpl(_) => None,
@@ -587,6 +675,20 @@ impl RustItem {
             None
         }
     }
+    pub fn as_constant_ref(&self) -> Option<&RustConstant> {
+        if let RustItem::Constant(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+    pub fn as_type_alias_ref(&self) -> Option<&RustTypeAlias> {
+        if let RustItem::TypeAlias(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
     pub fn as_reexport_ref(&self) -> Option<&RustReexport> {
         if let RustItem::Reexport(value) = self {
             Some(value)
@@ -635,6 +737,12 @@ impl RustItem {
             RustItem::EnumValue(_) => {
                 matches!(other, RustItem::EnumValue(_))
             }
+            RustItem::Constant(_) => {
+                matches!(other, RustItem::Constant(_))
+            }
+            RustItem::TypeAlias(_) => {
+                matches!(other, RustItem::TypeAlias(_))
+            }
             RustItem::TraitImpl(data) => {
                 if let RustItem::TraitImpl(other) = other {
                     data.extra_kind == other.extra_kind
@@ -724,6 +832,8 @@ impl RustItem {
             RustItem::Module(data) => format!("mod {}", data.path.full_name(None)),
             RustItem::Struct(data) => format!("struct {}", data.path.full_name(None)),
             RustItem::EnumValue(data) => format!("enum value {}", data.path.full_name(None)),
+            RustItem::Constant(data) => format!("const {}", data.path.full_name(None)),
+            RustItem::TypeAlias(data) => format!("type {}", data.path.full_name(None)),
             RustItem::TraitImpl(data) => format!(
                 "impl {} for {}",
                 rust_common_type_to_code(&data.trait_type, None),
@@ -763,6 +873,7 @@ pub enum NameType<'a> {
         is_from_other_crate: bool,
     },
     EnumValue,
+    Constant,
     Module {
         is_from_other_crate: bool,
     },
@@ -779,6 +890,12 @@ pub enum NameType<'a> {
     QtSignalWrapper {
         signal_arguments: &'a [CppType],
     },
+    Subclass {
+        base_class: &'a CppPath,
+    },
+    SubclassTrait {
+        base_class: &'a CppPath,
+    },
 }
 
 impl NameType<'_> {
@@ -139,6 +139,11 @@ pub enum RustToFfiTypeConversion {
     RefToPtr {
         lifetime: Option<String>,
     },
+    /// `&[T; N]` to `*const T` (or similar mutable types)
+    ArrayRefToPtr {
+        lifetime: Option<String>,
+        size: usize,
+    },
     /// `Ptr<T>` to `*const T` (or similar mutable type)
     UtilsPtrToPtr {},
     /// `Ref<T>` to `*const T` (or similar mutable types)
@@ -153,6 +158,11 @@ pub enum RustToFfiTypeConversion {
     CppBoxToPtr,
     /// `QBox<T>` to `*mut T`
     QBoxToPtr,
+    /// `CppBox<T>` to `*mut T` for an rvalue reference argument
+    /// (the value is moved from the object, and the `CppBox` is dropped after the call)
+    MoveCppBoxToPtr,
+    /// `VolatilePtr<T>` to `*const T` (or similar mutable type)
+    VolatilePtrToPtr,
     /// `qt_core::flags::Flags<T>` to `c_int`
     QFlagsToUInt {
         api_type: RustType,
@@ -167,6 +177,12 @@ pub enum RustToFfiTypeConversion {
     RefTo(Box<RustToFfiTypeConversion>),
     ImplCastInto(Box<RustToFfiTypeConversion>),
     ClosureToCallback(Box<RustClosureToCallbackConversion>),
+    /// `&cpp_core::VarArgs` to `*const c_void`
+    /// (the number of values is passed through an additional FFI argument)
+    VarArgsToPtr,
+    /// `Result<T, cpp_core::CppException>` to the FFI type of `T`
+    /// (the exception is passed through an additional FFI argument)
+    ResultOf(Box<RustToFfiTypeConversion>),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -252,13 +268,32 @@ impl RustFinalType {
                     bail!("not a pointer like type");
                 }
             }
+            RustToFfiTypeConversion::ArrayRefToPtr { lifetime, size } => {
+                if let RustType::PointerLike {
+                    is_const, target, ..
+                } = &ffi_type
+                {
+                    RustType::PointerLike {
+                        is_const: *is_const,
+                        kind: RustPointerLikeTypeKind::Reference {
+                            lifetime: lifetime.clone(),
+                        },
+                        target: Box::new(RustType::Array {
+                            item_type: target.clone(),
+                            size: *size,
+                        }),
+                    }
+                } else {
+                    bail!("not a pointer like type");
+                }
+            }
             RustToFfiTypeConversion::UtilsPtrToPtr {} => utils_ptr(&ffi_type)?,
             RustToFfiTypeConversion::UtilsRefToPtr {} => utils_ref(&ffi_type)?,
             RustToFfiTypeConversion::OptionUtilsRefToPtr {} => {
                 RustType::new_option(utils_ref(&ffi_type)?)
             }
             RustToFfiTypeConversion::ValueToPtr => ffi_type.pointer_like_to_target()?,
-            RustToFfiTypeConversion::CppBoxToPtr => {
+            RustToFfiTypeConversion::CppBoxToPtr | RustToFfiTypeConversion::MoveCppBoxToPtr => {
                 let target = ffi_type.pointer_like_to_target()?;
                 RustType::Common(RustCommonType {
                     path: RustPath::from_good_str("cpp_core::CppBox"),
@@ -279,6 +314,13 @@ impl RustFinalType {
                     generic_arguments: Some(vec![target.clone()]),
                 })
             }
+            RustToFfiTypeConversion::VolatilePtrToPtr => {
+                let target = ffi_type.pointer_like_to_target()?;
+                RustType::Common(RustCommonType {
+                    path: RustPath::from_good_str("cpp_core::VolatilePtr"),
+                    generic_arguments: Some(vec![target]),
+                })
+            }
             RustToFfiTypeConversion::QFlagsToUInt { api_type }
             | RustToFfiTypeConversion::AsCast { api_type } => api_type.clone(),
             RustToFfiTypeConversion::UnitToAnything => RustType::unit(),
@@ -297,6 +339,17 @@ impl RustFinalType {
             RustToFfiTypeConversion::ClosureToCallback { .. } => {
                 RustType::GenericParameter("T".into())
             }
+            RustToFfiTypeConversion::VarArgsToPtr => RustType::new_reference(
+                true,
+                RustType::Common(RustCommonType {
+                    path: RustPath::from_good_str("cpp_core::VarArgs"),
+                    generic_arguments: None,
+                }),
+            ),
+            RustToFfiTypeConversion::ResultOf(conversion) => {
+                let intermediate = RustFinalType::new(ffi_type.clone(), (**conversion).clone())?;
+                RustType::new_cpp_result(intermediate.api_type)
+            }
         };
         Ok(RustFinalType {
             api_type,
@@ -318,15 +371,42 @@ impl RustFinalType {
     }
 
     pub fn with_lifetime(&self, lifetime: String) -> Result<Self> {
-        if let RustToFfiTypeConversion::RefToPtr { .. } = &self.conversion {
-            RustFinalType::new(
+        match &self.conversion {
+            RustToFfiTypeConversion::RefToPtr { .. } => RustFinalType::new(
                 self.ffi_type.clone(),
                 RustToFfiTypeConversion::RefToPtr {
                     lifetime: Some(lifetime),
                 },
-            )
-        } else {
-            bail!("not a RefToPtr type");
+            ),
+            RustToFfiTypeConversion::ArrayRefToPtr { size, .. } => RustFinalType::new(
+                self.ffi_type.clone(),
+                RustToFfiTypeConversion::ArrayRefToPtr {
+                    lifetime: Some(lifetime),
+                    size: *size,
+                },
+            ),
+            _ => bail!("not a RefToPtr type"),
+        }
+    }
+
+    /// Returns a copy of this type that uses `alias` instead of `target`
+    /// in the public API type. `alias` must be a type alias of `target`,
+    /// so the conversion to the FFI type is not affected.
+    pub fn with_type_alias(&self, target: &RustType, alias: &RustType) -> Self {
+        RustFinalType {
+            ffi_type: self.ffi_type.clone(),
+            api_type: self.api_type.replace(target, alias),
+            conversion: self.conversion.clone(),
+        }
+    }
+
+    /// Returns a copy of this type that reports a caught C++ exception
+    /// by returning `Err` in the public API type.
+    pub fn with_cpp_exception(&self) -> Self {
+        RustFinalType {
+            ffi_type: self.ffi_type.clone(),
+            api_type: RustType::new_cpp_result(self.api_type.clone()),
+            conversion: RustToFfiTypeConversion::ResultOf(Box::new(self.conversion.clone())),
         }
     }
 }
@@ -458,6 +538,11 @@ pub enum RustType {
     },
     ImplTrait(RustCommonType),
     GenericParameter(String),
+    /// Fixed-size array type (`[T; N]`)
+    Array {
+        item_type: Box<RustType>,
+        size: usize,
+    },
 }
 
 impl RustType {
@@ -486,6 +571,20 @@ impl RustType {
         }
     }
 
+    /// Returns `Result<target, cpp_core::CppException>` type.
+    pub fn new_cpp_result(target: RustType) -> Self {
+        RustType::Common(RustCommonType {
+            path: RustPath::from_good_str("std::result::Result"),
+            generic_arguments: Some(vec![
+                target,
+                RustType::Common(RustCommonType {
+                    path: RustPath::from_good_str("cpp_core::CppException"),
+                    generic_arguments: None,
+                }),
+            ]),
+        })
+    }
+
     pub fn new_option(target: RustType) -> Self {
         RustType::Common(RustCommonType {
             path: RustPath::from_good_str("std::option::Option"),
@@ -581,6 +680,9 @@ impl RustType {
                 name
             }
             RustType::FunctionPointer { .. } => "fn".to_string(),
+            RustType::Array { item_type, .. } => {
+                format!("{}_array", item_type.caption(context, strategy)?)
+            }
             RustType::ImplTrait(trait_type) => {
                 if trait_type.path == RustPath::from_good_str("cpp_core::CastInto") {
                     trait_type
@@ -596,6 +698,26 @@ impl RustType {
         })
     }
 
+    /// Returns true if this is an unsigned integer type
+    /// (e.g. `u8` or `std::os::raw::c_uint`).
+    pub fn is_unsigned_integer(&self) -> bool {
+        match self {
+            RustType::Primitive(name) => {
+                ["u8", "u16", "u32", "u64", "u128", "usize"].contains(&name.as_str())
+            }
+            RustType::Common(RustCommonType {
+                path,
+                generic_arguments: None,
+            }) => {
+                path.parts.len() == 3
+                    && path.parts[..2] == ["std", "os"]
+                    && ["c_uchar", "c_ushort", "c_uint", "c_ulong", "c_ulonglong"]
+                        .contains(&path.last())
+            }
+            _ => false,
+        }
+    }
+
     /// Returns true if this type is a reference.
     pub fn is_ref(&self) -> bool {
         match self {
@@ -618,6 +740,51 @@ impl RustType {
         r
     }
 
+    /// Returns a copy of this type with all occurrences of `from` replaced with `to`.
+    pub fn replace(&self, from: &RustType, to: &RustType) -> RustType {
+        if self == from {
+            return to.clone();
+        }
+        let replace_common = |t: &RustCommonType| RustCommonType {
+            path: t.path.clone(),
+            generic_arguments: t
+                .generic_arguments
+                .as_ref()
+                .map(|args| args.iter().map(|arg| arg.replace(from, to)).collect()),
+        };
+        match self {
+            RustType::Tuple(types) => {
+                RustType::Tuple(types.iter().map(|t| t.replace(from, to)).collect())
+            }
+            RustType::Common(t) => RustType::Common(replace_common(t)),
+            RustType::ImplTrait(t) => RustType::ImplTrait(replace_common(t)),
+            RustType::FunctionPointer(function) => {
+                RustType::FunctionPointer(RustFunctionPointerType {
+                    arguments: function
+                        .arguments
+                        .iter()
+                        .map(|arg| arg.replace(from, to))
+                        .collect(),
+                    return_type: Box::new(function.return_type.replace(from, to)),
+                })
+            }
+            RustType::PointerLike {
+                kind,
+                is_const,
+                target,
+            } => RustType::PointerLike {
+                kind: kind.clone(),
+                is_const: *is_const,
+                target: Box::new(target.replace(from, to)),
+            },
+            RustType::Array { item_type, size } => RustType::Array {
+                item_type: Box::new(item_type.replace(from, to)),
+                size: *size,
+            },
+            RustType::Primitive(_) | RustType::GenericParameter(_) => self.clone(),
+        }
+    }
+
     /// Returns name of the lifetime of this type,
     /// or `None` if there isn't any lifetime in this type.
     #[allow(clippy::collapsible_match)]
@@ -669,6 +836,7 @@ impl RustType {
                 false
             }
             RustType::Tuple(types) => types.iter().any(RustType::is_unsafe_argument),
+            RustType::Array { item_type, .. } => item_type.is_unsafe_argument(),
             RustType::FunctionPointer(function) => {
                 function.return_type.is_unsafe_argument()
                     || function.arguments.iter().any(RustType::is_unsafe_argument)
@@ -775,6 +943,17 @@ impl RustType {
                 }
             }
             RustType::GenericParameter(_) => self == other,
+            RustType::Array { item_type, size } => {
+                if let RustType::Array {
+                    item_type: other_item_type,
+                    size: other_size,
+                } = other
+                {
+                    size == other_size && item_type.can_be_same_as(other_item_type)
+                } else {
+                    false
+                }
+            }
         }
     }
 }
@@ -26,10 +26,13 @@ pub fn empty_membership() -> CppFunctionMemberData {
         is_virtual: false,
         is_pure_virtual: false,
         is_const: false,
+        is_volatile: false,
         is_static: false,
         visibility: CppVisibility::Public,
         is_signal: false,
         is_slot: false,
+        is_final: false,
+        is_override: false,
     }
 }
 
@@ -43,6 +46,10 @@ pub fn empty_regular_method() -> CppFunction {
         operator: None,
         declaration_code: None,
         cast: None,
+        is_deleted: false,
+        is_noexcept: false,
+        deprecation: None,
+        return_type_alias: None,
     }
 }
 
@@ -62,6 +69,8 @@ fn argument_types_equal2() {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     assert!(!method1.argument_types_equal(&method2));
     assert!(!method2.argument_types_equal(&method1));
@@ -75,11 +84,15 @@ fn argument_types_equal3() {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "x".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     assert!(method1.argument_types_equal(&method2));
     assert!(method2.argument_types_equal(&method1));
@@ -93,11 +106,15 @@ fn argument_types_equal4() {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: true,
+        default_value: None,
+        type_alias: None,
     });
     assert!(method1.argument_types_equal(&method2));
     assert!(method2.argument_types_equal(&method1));
@@ -111,6 +128,8 @@ fn argument_types_equal5() {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::Enum {
@@ -118,6 +137,8 @@ fn argument_types_equal5() {
         },
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     assert!(!method1.argument_types_equal(&method2));
     assert!(!method2.argument_types_equal(&method1));
@@ -131,6 +152,8 @@ fn argument_types_equal6() {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::new_pointer(
@@ -139,6 +162,8 @@ fn argument_types_equal6() {
         ),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     assert!(!method1.argument_types_equal(&method2));
     assert!(!method2.argument_types_equal(&method1));
@@ -151,6 +176,8 @@ fn argument_types_equal7() {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     };
     let mut method2 = empty_regular_method();
     method1.arguments.push(int.clone());
@@ -206,6 +233,8 @@ fn c_signature_simple_func() {
         },
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     let r = to_ffi(&method1, None);
     assert!(r.arguments.len() == 1);
@@ -226,6 +255,80 @@ fn c_signature_simple_func() {
     );
 }
 
+#[test]
+fn c_signature_std_function() {
+    let string_type = CppType::Class(CppPath::from_good_str("QString"));
+    let function_type = CppFunctionPointerType {
+        return_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+        arguments: vec![CppType::new_reference(true, string_type.clone())],
+        allows_variadic_arguments: false,
+    };
+    let mut std_function_path = CppPath::from_good_str("std::function");
+    std_function_path.last_mut().template_arguments =
+        Some(vec![CppType::FunctionPointer(function_type)]);
+    assert_eq!(
+        std_function_path.to_cpp_code().unwrap(),
+        "std::function< int(QString const &) >"
+    );
+
+    let mut method1 = empty_regular_method();
+    method1.arguments.push(CppFunctionArgument {
+        argument_type: CppType::new_reference(true, CppType::Class(std_function_path)),
+        name: "handler".to_string(),
+        has_default_value: false,
+        default_value: None,
+        type_alias: None,
+    });
+    let r = to_ffi(&method1, None);
+    assert_eq!(r.arguments.len(), 3);
+    assert_eq!(r.arguments[0].name, "handler");
+    assert_eq!(r.arguments[0].meaning, CppFfiArgumentMeaning::Argument(0));
+    let void_ptr = CppType::new_pointer(false, CppType::Void);
+    assert_eq!(
+        r.arguments[0].argument_type.ffi_type(),
+        &CppType::FunctionPointer(CppFunctionPointerType {
+            return_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+            arguments: vec![void_ptr.clone(), CppType::new_pointer(true, string_type)],
+            allows_variadic_arguments: false,
+        })
+    );
+    assert!(matches!(
+        r.arguments[0].argument_type.conversion(),
+        CppToFfiTypeConversion::StdFunctionToCallback { .. }
+    ));
+    assert_eq!(r.arguments[1].name, "handler_deleter");
+    assert_eq!(
+        r.arguments[1].meaning,
+        CppFfiArgumentMeaning::CallbackContext(0)
+    );
+    assert_eq!(r.arguments[2].name, "handler_data");
+    assert_eq!(r.arguments[2].argument_type.ffi_type(), &void_ptr);
+    assert_eq!(
+        r.arguments[2].meaning,
+        CppFfiArgumentMeaning::CallbackContext(0)
+    );
+}
+
+#[test]
+fn c_signature_exception() {
+    let mut method1 = empty_regular_method();
+    method1.return_type = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    let mut r = to_ffi(&method1, None);
+    assert!(r.exception_argument().is_none());
+    crate::cpp_ffi_generator::add_exception_argument(&mut r).unwrap();
+    assert_eq!(r.arguments.len(), 1);
+    let arg = r.exception_argument().unwrap();
+    assert_eq!(arg.name, "ritual_exception");
+    assert_eq!(arg.meaning, CppFfiArgumentMeaning::Exception);
+    assert_eq!(arg.to_cpp_code().unwrap(), "char * * ritual_exception");
+    assert_eq!(r.return_type.ffi_type(), &method1.return_type);
+
+    method1.is_noexcept = true;
+    let mut r = to_ffi(&method1, None);
+    crate::cpp_ffi_generator::add_exception_argument(&mut r).unwrap();
+    assert!(r.arguments.is_empty());
+}
+
 #[test]
 fn c_signature_method_with_this() {
     let mut method1 = empty_regular_method();
@@ -236,6 +339,8 @@ fn c_signature_method_with_this() {
         argument_type: CppType::Class(CppPath::from_good_str("MyClass2")),
         name: "my_arg".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
 
     assert!(!method1.is_constructor());
@@ -288,6 +393,8 @@ fn c_signature_static_method() {
         },
         name: "arg1".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     let r = to_ffi(&method1, None);
     assert!(r.arguments.len() == 1);
@@ -322,6 +429,8 @@ fn c_signature_constructor() {
         ),
         name: "arg1".to_string(),
         has_default_value: true,
+        default_value: None,
+        type_alias: None,
     });
 
     assert!(method1.is_constructor());
@@ -463,6 +572,8 @@ fn c_signature_method_returning_class() {
         argument_type: CppType::Class(CppPath::from_good_str("MyClass2")),
         name: "my_arg".to_string(),
         has_default_value: false,
+        default_value: None,
+        type_alias: None,
     });
     let r_stack = to_ffi(&method1, Some(CppPath::from_good_str("MyClass3")));
     assert!(r_stack.arguments.len() == 3);
@@ -601,10 +712,13 @@ fn short_text1() {
             is_virtual: false,
             is_pure_virtual: false,
             is_const: true,
+            is_volatile: false,
             is_static: false,
             visibility: CppVisibility::Protected,
             is_signal: false,
             is_slot: false,
+            is_final: false,
+            is_override: false,
         }),
         operator: None,
         return_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
@@ -613,19 +727,54 @@ fn short_text1() {
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
                 name: "arg1".to_string(),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             },
             CppFunctionArgument {
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Double),
                 name: "arg2".to_string(),
                 has_default_value: true,
+                default_value: None,
+                type_alias: None,
             },
         ],
         allows_variadic_arguments: false,
         cast: None,
         declaration_code: None,
+        is_deleted: false,
+        is_noexcept: false,
+        deprecation: None,
+        return_type_alias: None,
     };
     assert_eq!(
         method.short_text(),
         "protected int Class1::method1(int arg1, double arg2 = …) const"
     );
 }
+
+#[test]
+fn c_signature_variadic() {
+    let mut method1 = empty_regular_method();
+    method1.arguments.push(CppFunctionArgument {
+        argument_type: CppType::new_pointer(
+            true,
+            CppType::BuiltInNumeric(CppBuiltInNumericType::Char),
+        ),
+        name: "format".to_string(),
+        has_default_value: false,
+        default_value: None,
+        type_alias: None,
+    });
+    method1.allows_variadic_arguments = true;
+    let r = to_ffi(&method1, None);
+    assert_eq!(r.arguments.len(), 3);
+    assert_eq!(r.arguments[0].meaning, CppFfiArgumentMeaning::Argument(0));
+    let arg = r.var_args_argument().unwrap();
+    assert_eq!(arg.meaning, CppFfiArgumentMeaning::VarArgs);
+    assert_eq!(arg.to_cpp_code().unwrap(), "void const * var_args");
+    assert_eq!(r.arguments[2].meaning, CppFfiArgumentMeaning::VarArgCount);
+    assert_eq!(
+        r.arguments[2].argument_type.ffi_type(),
+        &CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+    );
+}
@@ -1,13 +1,19 @@
 use crate::config::{Config, CrateProperties};
+use crate::cpp_checks::CppChecksItem;
 use crate::cpp_data::*;
 use crate::cpp_function::*;
 use crate::cpp_operator::CppOperator;
+use crate::cpp_parser_report::{CppParserReport, CppParserSkipCategory};
 use crate::cpp_type::*;
-use crate::processor;
+use crate::database::{CppDocComment, CppDocCommentParam, DatabaseClient};
+use crate::processor::{self, ProcessorData};
 use crate::workspace::Workspace;
 use ritual_common::cpp_build_config::CppBuildPaths;
+use ritual_common::errors::Result;
 use ritual_common::file_utils::create_dir;
 use ritual_common::file_utils::create_file;
+use ritual_common::file_utils::load_json;
+use ritual_common::target::{current_target, LibraryTarget};
 use std::io::Write;
 
 struct ParserCppData {
@@ -17,9 +23,21 @@ struct ParserCppData {
     methods: Vec<CppFunction>,
     enum_values: Vec<CppEnumValue>,
     namespaces: Vec<CppPath>,
+    variables: Vec<CppGlobalVariable>,
+    macros: Vec<CppMacroConstant>,
+    aliases: Vec<CppTypeAlias>,
+    doc_comments: Vec<(CppPath, CppDocComment)>,
+    report: CppParserReport,
 }
 
 fn run_parser(code: &'static str) -> ParserCppData {
+    run_parser_with_config(code, |_| {})
+}
+
+fn run_parser_with_config(
+    code: &'static str,
+    configure: impl FnOnce(&mut Config),
+) -> ParserCppData {
     let dir = tempdir::TempDir::new("test_cpp_parser_run").unwrap();
 
     let mut workspace = Workspace::new(dir.path().into()).unwrap();
@@ -40,6 +58,7 @@ fn run_parser(code: &'static str) -> ParserCppData {
     config.add_include_directive(include_name);
     config.set_cpp_build_paths(paths);
     config.add_target_include_path(include_file_path);
+    configure(&mut config);
 
     processor::process(&mut workspace, &config, &["cpp_parser".into()], None).unwrap();
 
@@ -78,6 +97,29 @@ fn run_parser(code: &'static str) -> ParserCppData {
             .filter_map(|item| item.item.as_namespace_ref())
             .map(|ns| ns.path.clone())
             .collect(),
+        variables: database
+            .cpp_items()
+            .filter_map(|item| item.item.as_global_variable_ref())
+            .cloned()
+            .collect(),
+        macros: database
+            .cpp_items()
+            .filter_map(|item| item.item.as_macro_constant_ref())
+            .cloned()
+            .collect(),
+        aliases: database
+            .cpp_items()
+            .filter_map(|item| item.item.as_type_alias_ref())
+            .cloned()
+            .collect(),
+        doc_comments: database
+            .cpp_items()
+            .filter_map(|item| {
+                let doc = database.find_doc_for(&item.id).unwrap()?;
+                Some((item.item.path()?.clone(), doc.item.comment.clone()?))
+            })
+            .collect(),
+        report: load_json(workspace.log_path().join("cpp_parser_report_A.json")).unwrap(),
     }
 }
 
@@ -97,10 +139,16 @@ fn simple_func() {
                 name: "x".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("int func1 ( int x )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
 }
@@ -127,10 +175,16 @@ fn simple_func_with_default_value() {
                 name: "x".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
                 has_default_value: true,
+                default_value: Some("42".to_string()),
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("bool func1 ( int x = 42 )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
 }
@@ -192,10 +246,16 @@ fn functions_with_class_arg() {
                 name: "x".to_string(),
                 argument_type: CppType::Class(CppPath::from_good_str("Magic")),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("bool func1 ( Magic x )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
     assert_eq!(
@@ -212,10 +272,16 @@ fn functions_with_class_arg() {
                     CppType::Class(CppPath::from_good_str("Magic"))
                 ),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("bool func1 ( Magic * x )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
     assert_eq!(
@@ -232,10 +298,16 @@ fn functions_with_class_arg() {
                     CppType::Class(CppPath::from_good_str("Magic"))
                 ),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("bool func2 ( const Magic & )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
 }
@@ -285,10 +357,16 @@ fn variadic_func() {
                     CppType::BuiltInNumeric(CppBuiltInNumericType::Char)
                 ),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: true,
             cast: None,
             declaration_code: Some("int my_printf ( const char * format , ... )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
 }
@@ -332,10 +410,16 @@ fn free_template_func() {
                     name: "T".into(),
                 }),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("template < typename T > T abs ( T value )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
 }
@@ -367,16 +451,24 @@ fn free_func_operator_sub() {
                         name: "a".to_string(),
                         argument_type: CppType::Class(CppPath::from_good_str("C1")),
                         has_default_value: false,
+                        default_value: None,
+                        type_alias: None,
                     },
                     CppFunctionArgument {
                         name: "b".to_string(),
                         argument_type: CppType::Class(CppPath::from_good_str("C1")),
                         has_default_value: false,
+                        default_value: None,
+                        type_alias: None,
                     },
                 ],
                 allows_variadic_arguments: false,
                 cast: None,
                 declaration_code: Some("C1 operator - ( C1 a , C1 b )".to_string()),
+                is_deleted: false,
+                is_noexcept: false,
+                deprecation: None,
+                return_type_alias: None,
             }
         );
     }
@@ -411,10 +503,13 @@ fn simple_class_method() {
                 is_virtual: false,
                 is_pure_virtual: false,
                 is_const: false,
+                is_volatile: false,
                 is_static: false,
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                is_final: false,
+                is_override: false,
             }),
             operator: None,
             return_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
@@ -422,10 +517,16 @@ fn simple_class_method() {
                 name: "x".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("int func1 ( int x )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
 }
@@ -601,10 +702,13 @@ fn template_class_method() {
                 is_virtual: false,
                 is_pure_virtual: false,
                 is_const: false,
+                is_volatile: false,
                 is_static: false,
                 visibility: CppVisibility::Public,
                 is_signal: false,
                 is_slot: false,
+                is_final: false,
+                is_override: false,
             }),
             operator: None,
             return_type: CppType::TemplateParameter(CppTemplateParameter {
@@ -616,10 +720,16 @@ fn template_class_method() {
                 name: "index".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
                 has_default_value: false,
+                default_value: None,
+                type_alias: None,
             }],
             allows_variadic_arguments: false,
             cast: None,
             declaration_code: Some("T get ( int index )".to_string()),
+            is_deleted: false,
+            is_noexcept: false,
+            deprecation: None,
+            return_type_alias: None,
         }
     );
     assert_eq!(
@@ -757,17 +867,24 @@ fn simple_enum() {
     );
     assert_eq!(data.types.len(), 1);
     assert_eq!(data.types[0].path, CppPath::from_good_str("Enum1"));
-    assert_eq!(data.types[0].kind, CppTypeDeclarationKind::Enum);
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+        }
+    );
     assert_eq!(
         data.enum_values,
         vec![
             CppEnumValue {
                 value: 0,
                 path: CppPath::from_good_str("Enum1::Good"),
+                deprecation: None,
             },
             CppEnumValue {
                 value: 1,
                 path: CppPath::from_good_str("Enum1::Bad"),
+                deprecation: None,
             },
         ]
     );
@@ -788,21 +905,29 @@ fn simple_enum2() {
     );
     assert_eq!(data.types.len(), 1);
     assert_eq!(data.types[0].path, CppPath::from_good_str("ns1::Enum1"));
-    assert_eq!(data.types[0].kind, CppTypeDeclarationKind::Enum);
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+        }
+    );
     assert_eq!(
         data.enum_values,
         vec![
             CppEnumValue {
                 value: 1,
                 path: CppPath::from_good_str("ns1::Enum1::Good"),
+                deprecation: None,
             },
             CppEnumValue {
                 value: 2,
                 path: CppPath::from_good_str("ns1::Enum1::Bad"),
+                deprecation: None,
             },
             CppEnumValue {
                 value: 3,
                 path: CppPath::from_good_str("ns1::Enum1::Questionable"),
+                deprecation: None,
             },
         ]
     );
@@ -1003,7 +1128,110 @@ fn non_type_template_parameter() {
         };
         ",
     );
-    assert!(data.types.is_empty());
+    assert_eq!(data.types.len(), 1);
+    assert_eq!(
+        data.types[0].path,
+        CppPath::from_item(CppPathItem {
+            name: "QAtomicOpsSupport".into(),
+            template_arguments: Some(vec![CppType::TemplateParameter(CppTemplateParameter {
+                nested_level: 0,
+                index: 0,
+                name: "Type1".into(),
+            })]),
+        })
+    );
+}
+
+#[test]
+fn non_type_template_argument() {
+    let data = run_parser(
+        "
+        template<typename T, int N>
+        class FixedBuffer {
+        public:
+            T at(int index) const;
+        };
+        class C1 {
+            public:
+            FixedBuffer<int, 4> buffer();
+        };
+        ",
+    );
+    assert_eq!(data.types.len(), 2);
+    assert_eq!(
+        data.types[0].path,
+        CppPath::from_item(CppPathItem {
+            name: "FixedBuffer".into(),
+            template_arguments: Some(vec![
+                CppType::TemplateParameter(CppTemplateParameter {
+                    nested_level: 0,
+                    index: 0,
+                    name: "T".into(),
+                }),
+                CppType::TemplateParameter(CppTemplateParameter {
+                    nested_level: 0,
+                    index: 1,
+                    name: "N".into(),
+                }),
+            ]),
+        })
+    );
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(
+        data.methods[1].return_type,
+        CppType::Class(CppPath::from_item(CppPathItem {
+            name: "FixedBuffer".into(),
+            template_arguments: Some(vec![
+                CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+                CppType::TemplateArgumentValue(CppTemplateArgumentValue::Integer(4)),
+            ]),
+        })),
+    );
+}
+
+#[test]
+fn enum_template_argument() {
+    let data = run_parser(
+        "
+        namespace ns {
+            enum Mode { Fast, Slow };
+            enum class Color { Red = 1, Green = 2 };
+        }
+        template<ns::Mode M> class ModeTag {};
+        template<ns::Color C> class ColorTag {};
+        template<unsigned long long N> class Big {};
+        class C2 {
+            public:
+            ModeTag<ns::Slow> mode();
+            ColorTag<ns::Color::Green> color();
+            Big<18446744073709551615ull> big();
+        };
+        ",
+    );
+    let return_type = |name: &str| {
+        data.methods
+            .iter()
+            .find(|f| f.path == CppPath::from_good_str(name))
+            .map(|f| f.return_type.clone())
+    };
+    let tag = |class: &str, value: &str| {
+        CppType::Class(CppPath::from_item(CppPathItem {
+            name: class.into(),
+            template_arguments: Some(vec![CppType::TemplateArgumentValue(
+                CppTemplateArgumentValue::EnumValue(CppPath::from_good_str(value)),
+            )]),
+        }))
+    };
+    assert_eq!(
+        return_type("C2::mode"),
+        Some(tag("ModeTag", "ns::Mode::Slow"))
+    );
+    assert_eq!(
+        return_type("C2::color"),
+        Some(tag("ColorTag", "ns::Color::Green"))
+    );
+    // values that don't fit in `i64` are not supported
+    assert_eq!(return_type("C2::big"), None);
 }
 
 #[test]
@@ -1220,3 +1448,1014 @@ fn elaborated_enum_type() {
         }
     );
 }
+
+#[test]
+fn template_partial_specialization() {
+    let data = run_parser(
+        "
+        template<typename T>
+        struct Traits {
+            T value();
+        };
+        template<typename T>
+        struct Traits<T*> {
+            T pointee();
+        };
+        ",
+    );
+    let param = CppType::TemplateParameter(CppTemplateParameter {
+        nested_level: 0,
+        index: 0,
+        name: "T".into(),
+    });
+    let partial_path = CppPath::from_item(CppPathItem {
+        name: "Traits".into(),
+        template_arguments: Some(vec![CppType::new_pointer(false, param.clone())]),
+    });
+    assert_eq!(data.types.len(), 2);
+    assert!(!data.types[0].is_template_partial_specialization());
+    assert_eq!(data.types[1].path, partial_path);
+    assert!(data.types[1].is_template_partial_specialization());
+
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(
+        data.methods[1].path,
+        partial_path.join(CppPathItem::from_good_str("pointee"))
+    );
+    assert_eq!(data.methods[1].return_type, param);
+}
+
+#[test]
+fn function_attributes() {
+    let data = run_parser(
+        "
+        class Base {
+        public:
+            Base(const Base& other) = delete;
+            virtual ~Base() noexcept;
+            virtual int f1() const;
+            virtual void f2() throw();
+        };
+        class Derived : public Base {
+        public:
+            int f1() const override;
+            void f2() throw() final;
+        };
+        ",
+    );
+    let find = |name: &str| {
+        data.methods
+            .iter()
+            .find(|m| m.path.to_cpp_pseudo_code() == name)
+            .unwrap()
+    };
+
+    let copy_constructor = find("Base::Base");
+    assert!(copy_constructor.is_deleted);
+    assert!(!copy_constructor.is_noexcept);
+
+    let destructor = find("Base::~Base");
+    assert!(!destructor.is_deleted);
+    assert!(destructor.is_noexcept);
+
+    let base_f1 = find("Base::f1");
+    assert!(base_f1.can_be_overridden());
+    assert!(!base_f1.is_override());
+    assert!(!base_f1.is_noexcept);
+
+    let derived_f1 = find("Derived::f1");
+    assert!(derived_f1.is_override());
+    assert!(!derived_f1.is_final());
+    assert!(derived_f1.can_be_overridden());
+
+    let derived_f2 = find("Derived::f2");
+    assert!(derived_f2.is_final());
+    assert!(!derived_f2.is_override());
+    assert!(!derived_f2.can_be_overridden());
+    assert!(derived_f2.is_noexcept);
+}
+
+#[test]
+fn deprecated_items() {
+    let data = run_parser(
+        "
+        [[deprecated(\"use g() instead\")]]
+        void f();
+        void g();
+        struct [[deprecated]] Old {};
+        enum E { A, B [[deprecated(\"use A\")]] };
+        ",
+    );
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(
+        data.methods[0].deprecation,
+        Some(CppDeprecation {
+            message: Some("use g() instead".into())
+        })
+    );
+    assert_eq!(data.methods[1].deprecation, None);
+
+    let old = data
+        .types
+        .iter()
+        .find(|t| t.path == CppPath::from_good_str("Old"))
+        .unwrap();
+    assert_eq!(old.deprecation, Some(CppDeprecation { message: None }));
+
+    assert_eq!(data.enum_values.len(), 2);
+    assert_eq!(data.enum_values[0].deprecation, None);
+    assert_eq!(
+        data.enum_values[1].deprecation,
+        Some(CppDeprecation {
+            message: Some("use A".into())
+        })
+    );
+}
+
+#[test]
+fn global_variables_and_constants() {
+    let data = run_parser(
+        "
+        namespace config {
+            constexpr int kMaxItems = 16;
+            constexpr double kRatio = 0.5;
+            constexpr const char* kName = \"items\";
+            extern int counter;
+        }
+        class Limits {
+        public:
+            static constexpr unsigned int kLimit = 4;
+            static int instances;
+        };
+        ",
+    );
+    assert_eq!(data.variables.len(), 4);
+    let int_type = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+
+    assert_eq!(
+        data.variables[0].path,
+        CppPath::from_good_str("config::kMaxItems")
+    );
+    assert_eq!(data.variables[0].variable_type, int_type);
+    assert!(data.variables[0].is_const);
+    assert_eq!(data.variables[0].value, Some(CppConstantValue::Integer(16)));
+
+    assert_eq!(
+        data.variables[1].value,
+        Some(CppConstantValue::Float("0.5".into()))
+    );
+    assert_eq!(
+        data.variables[2].value,
+        Some(CppConstantValue::String("items".into()))
+    );
+
+    assert_eq!(
+        data.variables[3].path,
+        CppPath::from_good_str("config::counter")
+    );
+    assert_eq!(data.variables[3].variable_type, int_type);
+    assert!(!data.variables[3].is_const);
+    assert_eq!(data.variables[3].value, None);
+
+    assert_eq!(data.fields.len(), 2);
+    assert_eq!(
+        data.fields[0].path,
+        CppPath::from_good_str("Limits::kLimit")
+    );
+    assert!(data.fields[0].is_static);
+    assert!(data.fields[0].is_const);
+    assert_eq!(data.fields[0].value, Some(CppConstantValue::Integer(4)));
+    assert!(data.fields[1].is_static);
+    assert!(!data.fields[1].is_const);
+    assert_eq!(data.fields[1].value, None);
+}
+
+#[test]
+fn wide_integer_constants() {
+    let data = run_parser(
+        "
+        constexpr long long kBig = 0x100000000LL;
+        constexpr unsigned int kAllBits = 0xFFFFFFFFu;
+        constexpr unsigned long long kHuge = 0xFFFFFFFFFFFFFFFFull;
+        ",
+    );
+    assert_eq!(data.variables.len(), 3);
+    assert_eq!(
+        data.variables[0].value,
+        Some(CppConstantValue::Integer(0x1_0000_0000))
+    );
+    assert_eq!(
+        data.variables[1].value,
+        Some(CppConstantValue::Integer(0xFFFF_FFFF))
+    );
+    assert_eq!(data.variables[2].value, None);
+}
+
+#[test]
+fn macro_constants() {
+    let data = run_parser(
+        "
+        #define LIB_VERSION 0x0502
+        #define LIB_FLAG (1 << 3)
+        #define LIB_FLAGS (LIB_FLAG | 1)
+        #define LIB_NAME \"lib\"
+        #define LIB_SCALE -1.5
+        #define LIB_MAX(a, b) ((a) > (b) ? (a) : (b))
+        #define LIB_EXPORT
+        #define LIB_TYPE int
+        ",
+    );
+    assert_eq!(
+        data.macros,
+        vec![
+            CppMacroConstant {
+                path: CppPath::from_good_str("LIB_VERSION"),
+                value: CppConstantValue::Integer(0x0502),
+            },
+            CppMacroConstant {
+                path: CppPath::from_good_str("LIB_FLAG"),
+                value: CppConstantValue::Integer(8),
+            },
+            CppMacroConstant {
+                path: CppPath::from_good_str("LIB_FLAGS"),
+                value: CppConstantValue::Integer(9),
+            },
+            CppMacroConstant {
+                path: CppPath::from_good_str("LIB_NAME"),
+                value: CppConstantValue::String("lib".into()),
+            },
+            CppMacroConstant {
+                path: CppPath::from_good_str("LIB_SCALE"),
+                value: CppConstantValue::Float("-1.5".into()),
+            },
+        ]
+    );
+    assert_eq!(
+        data.macros[0].value_type(),
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+    );
+}
+
+#[test]
+fn unions_and_anonymous_types() {
+    let data = run_parser(
+        "
+        union Value {
+            int i;
+            float f;
+        };
+        typedef struct {
+            int x;
+        } Point;
+        struct Event {
+            int kind;
+            union {
+                int code;
+                double amount;
+            };
+        };
+        enum { kLimit = 8 };
+        class Flags {
+        public:
+            enum { kDefault = 2 };
+        };
+        ",
+    );
+    assert_eq!(data.types.len(), 4);
+    assert_eq!(data.types[0].path, CppPath::from_good_str("Value"));
+    assert_eq!(data.types[0].kind, CppTypeDeclarationKind::Union);
+    assert!(data.types[0].kind.is_class());
+    assert_eq!(data.types[1].path, CppPath::from_good_str("Point"));
+    assert_eq!(data.types[1].kind, CppTypeDeclarationKind::Class);
+    assert_eq!(data.types[2].path, CppPath::from_good_str("Event"));
+    assert_eq!(data.types[3].path, CppPath::from_good_str("Flags"));
+
+    let field_paths = data
+        .fields
+        .iter()
+        .map(|f| f.path.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        field_paths,
+        vec![
+            CppPath::from_good_str("Value::i"),
+            CppPath::from_good_str("Value::f"),
+            CppPath::from_good_str("Point::x"),
+            CppPath::from_good_str("Event::kind"),
+            CppPath::from_good_str("Event::code"),
+            CppPath::from_good_str("Event::amount"),
+            CppPath::from_good_str("Flags::kDefault"),
+        ]
+    );
+    let flag = &data.fields[6];
+    assert!(flag.is_static);
+    assert!(flag.is_const);
+    assert_eq!(flag.value, Some(CppConstantValue::Integer(2)));
+
+    assert_eq!(data.variables.len(), 1);
+    assert_eq!(data.variables[0].path, CppPath::from_good_str("kLimit"));
+    // the underlying type of an anonymous enum is chosen by the compiler
+    assert!(matches!(
+        data.variables[0].variable_type,
+        CppType::BuiltInNumeric(_)
+    ));
+    assert_eq!(data.variables[0].value, Some(CppConstantValue::Integer(8)));
+}
+
+#[test]
+fn anonymous_member_types() {
+    let data = run_parser(
+        "
+        namespace ns {
+            struct S {
+                union {
+                    int i;
+                    float f;
+                } u;
+                struct {
+                    struct {
+                        int depth;
+                    } inner;
+                } outer;
+            };
+        }
+        ",
+    );
+    let find_type = |path: &str| {
+        data.types
+            .iter()
+            .find(|t| t.path == CppPath::from_good_str(path))
+            .unwrap_or_else(|| panic!("type not found: {}", path))
+    };
+    assert_eq!(data.types.len(), 4);
+    assert_eq!(find_type("ns::S").anonymous_member, None);
+    let union_type = find_type("ns::S_u");
+    assert_eq!(union_type.kind, CppTypeDeclarationKind::Union);
+    assert_eq!(union_type.anonymous_member, Some("ns::S::u".to_string()));
+    assert_eq!(
+        find_type("ns::S_outer").anonymous_member,
+        Some("ns::S::outer".to_string())
+    );
+    assert_eq!(
+        find_type("ns::S_outer_inner").anonymous_member,
+        Some("ns::S::outer.inner".to_string())
+    );
+
+    let find_field = |path: &str| {
+        data.fields
+            .iter()
+            .find(|f| f.path == CppPath::from_good_str(path))
+            .unwrap_or_else(|| panic!("field not found: {}", path))
+    };
+    assert_eq!(data.fields.len(), 6);
+    assert_eq!(
+        find_field("ns::S::u").field_type,
+        CppType::Class(CppPath::from_good_str("ns::S_u"))
+    );
+    assert_eq!(
+        find_field("ns::S_u::f").field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Float)
+    );
+    assert_eq!(
+        find_field("ns::S::outer").field_type,
+        CppType::Class(CppPath::from_good_str("ns::S_outer"))
+    );
+    assert_eq!(
+        find_field("ns::S_outer::inner").field_type,
+        CppType::Class(CppPath::from_good_str("ns::S_outer_inner"))
+    );
+    find_field("ns::S_u::i");
+    find_field("ns::S_outer_inner::depth");
+}
+
+#[test]
+fn bit_fields_and_arrays() {
+    let data = run_parser(
+        "
+        struct Packet {
+            unsigned int flags : 3;
+            unsigned int kind : 5;
+            int data[4];
+            const char name[16] = \"packet\";
+        };
+        ",
+    );
+    assert_eq!(data.fields.len(), 4);
+    let uint_type = CppType::BuiltInNumeric(CppBuiltInNumericType::UInt);
+
+    assert_eq!(data.fields[0].path, CppPath::from_good_str("Packet::flags"));
+    assert_eq!(data.fields[0].field_type, uint_type);
+    assert_eq!(data.fields[0].bit_width, Some(3));
+    assert_eq!(data.fields[0].array_size, None);
+    assert_eq!(data.fields[1].bit_width, Some(5));
+
+    assert_eq!(data.fields[2].path, CppPath::from_good_str("Packet::data"));
+    assert_eq!(
+        data.fields[2].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+    );
+    assert_eq!(data.fields[2].bit_width, None);
+    assert_eq!(data.fields[2].array_size, Some(4));
+    assert!(!data.fields[2].is_const);
+
+    assert_eq!(
+        data.fields[3].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Char)
+    );
+    assert_eq!(data.fields[3].array_size, Some(16));
+    assert!(data.fields[3].is_const);
+}
+
+#[test]
+fn enum_underlying_types() {
+    let data = run_parser(
+        "
+        enum class Small : unsigned char {
+            Low = 1,
+            High = 200
+        };
+        enum Big : unsigned long long {
+            Max = 0xFFFFFFFFFFFFFFFF
+        };
+        enum class Scoped { Value = -1 };
+        ",
+    );
+    assert_eq!(data.types.len(), 3);
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::UChar)
+        }
+    );
+    assert_eq!(
+        data.types[1].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::ULongLong)
+        }
+    );
+    assert_eq!(
+        data.types[2].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+        }
+    );
+
+    let values = data.enum_values.iter().map(|v| v.value).collect::<Vec<_>>();
+    // values that don't fit in `i64` are stored with wrapping
+    assert_eq!(values, vec![1, 200, -1, -1]);
+    assert_eq!(data.enum_values[2].path, CppPath::from_good_str("Big::Max"));
+}
+
+#[test]
+fn type_aliases() {
+    let data = run_parser(
+        "
+        class Widget {};
+        typedef Widget* WidgetPtr;
+        using Callback = void (*)(int);
+        typedef struct Handle Handle;
+        typedef int int32_t;
+        namespace ns {
+            typedef unsigned int Id;
+            const Widget& widget(Id id);
+        }
+        class List {
+        public:
+            using size_type = int;
+            size_type size() const;
+        private:
+            typedef double Secret;
+        };
+        ",
+    );
+    let int = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    assert_eq!(
+        data.aliases,
+        vec![
+            CppTypeAlias {
+                path: CppPath::from_good_str("WidgetPtr"),
+                target_type: CppType::new_pointer(
+                    false,
+                    CppType::Class(CppPath::from_good_str("Widget"))
+                ),
+            },
+            CppTypeAlias {
+                path: CppPath::from_good_str("Callback"),
+                target_type: CppType::FunctionPointer(CppFunctionPointerType {
+                    return_type: Box::new(CppType::Void),
+                    arguments: vec![int.clone()],
+                    allows_variadic_arguments: false,
+                }),
+            },
+            CppTypeAlias {
+                path: CppPath::from_good_str("ns::Id"),
+                target_type: CppType::BuiltInNumeric(CppBuiltInNumericType::UInt),
+            },
+            CppTypeAlias {
+                path: CppPath::from_good_str("List::size_type"),
+                target_type: int.clone(),
+            },
+        ]
+    );
+
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(data.methods[0].path, CppPath::from_good_str("ns::widget"));
+    assert_eq!(
+        data.methods[0].arguments[0].argument_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::UInt)
+    );
+    assert_eq!(
+        data.methods[0].arguments[0].type_alias,
+        Some(CppPath::from_good_str("ns::Id"))
+    );
+    assert_eq!(data.methods[0].return_type_alias, None);
+    assert_eq!(data.methods[1].return_type, int);
+    assert_eq!(
+        data.methods[1].return_type_alias,
+        Some(CppPath::from_good_str("List::size_type"))
+    );
+}
+
+#[test]
+fn volatile_types() {
+    let data = run_parser(
+        "
+        struct Device {
+            volatile unsigned int status;
+            volatile int buffer[2];
+            int value() const volatile;
+            void write(volatile int* reg, int value);
+        };
+        ",
+    );
+    let int = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    assert_eq!(data.fields.len(), 2);
+    assert_eq!(
+        data.fields[0].path,
+        CppPath::from_good_str("Device::status")
+    );
+    assert_eq!(
+        data.fields[0].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::UInt)
+    );
+    assert!(data.fields[0].is_volatile);
+    assert!(!data.fields[0].is_const);
+    assert_eq!(data.fields[1].field_type, int);
+    assert_eq!(data.fields[1].array_size, Some(2));
+    assert!(data.fields[1].is_volatile);
+
+    assert_eq!(data.methods.len(), 2);
+    let member = data.methods[0].member.as_ref().unwrap();
+    assert!(member.is_const);
+    assert!(member.is_volatile);
+    let member = data.methods[1].member.as_ref().unwrap();
+    assert!(!member.is_volatile);
+    assert_eq!(
+        data.methods[1].arguments[0].argument_type,
+        CppType::new_pointer(false, int.clone())
+            .with_volatile_target()
+            .unwrap()
+    );
+    assert_eq!(
+        data.methods[1].arguments[0]
+            .argument_type
+            .to_cpp_code(None)
+            .unwrap(),
+        "int volatile*"
+    );
+}
+
+#[test]
+fn doc_comments() {
+    let data = run_parser(
+        "
+        /// A buffer of bytes.
+        class Buffer {
+        public:
+            /**
+             * \\brief Resizes the buffer.
+             *
+             * Existing data is preserved. Use \\c capacity() to avoid reallocations.
+             * \\param size New size in bytes.
+             * \\return True on success.
+             */
+            bool resize(int size);
+
+            // Not a documentation comment.
+            int size() const;
+        };
+        ",
+    );
+    assert_eq!(
+        data.doc_comments,
+        vec![
+            (
+                CppPath::from_good_str("Buffer"),
+                CppDocComment {
+                    brief: None,
+                    paragraphs: vec!["A buffer of bytes.".to_string()],
+                    params: Vec::new(),
+                    returns: None,
+                }
+            ),
+            (
+                CppPath::from_good_str("Buffer::resize"),
+                CppDocComment {
+                    brief: Some("Resizes the buffer.".to_string()),
+                    paragraphs: vec![
+                        "Existing data is preserved. Use `capacity()` to avoid reallocations."
+                            .to_string()
+                    ],
+                    params: vec![CppDocCommentParam {
+                        name: "size".to_string(),
+                        text: "New size in bytes.".to_string(),
+                    }],
+                    returns: Some("True on success.".to_string()),
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn default_argument_values() {
+    let data = run_parser(
+        "
+        namespace ns {
+            enum class Mode { Fast, Slow };
+            struct Point { Point(int x, int y); };
+            void func1(Mode mode = Mode::Slow, const char* name = \"a, b\",
+                       Point p = Point(1, -2), double factor = -0.5);
+        }
+        ",
+    );
+    let function = data
+        .methods
+        .iter()
+        .find(|f| f.path == CppPath::from_good_str("ns::func1"))
+        .unwrap();
+    assert!(function.arguments.iter().all(|arg| arg.has_default_value));
+    assert_eq!(
+        function
+            .arguments
+            .iter()
+            .map(|arg| arg.default_value.as_deref())
+            .collect::<Vec<_>>(),
+        vec![
+            Some("Mode::Slow"),
+            Some("\"a, b\""),
+            Some("Point(1, -2)"),
+            Some("-0.5"),
+        ]
+    );
+}
+
+#[test]
+fn inline_namespaces_and_aliases() {
+    let data = run_parser(
+        "
+        namespace lib {
+            inline namespace v2 {
+                class Widget {};
+                template<typename T> class Box {};
+            }
+        }
+        namespace l = lib;
+        void show(const l::Widget& widget);
+        template<typename T> void process(l::Box<T>* box);
+        ",
+    );
+    assert_eq!(data.namespaces, vec![CppPath::from_good_str("lib")]);
+    assert!(data
+        .types
+        .iter()
+        .any(|t| t.path == CppPath::from_good_str("lib::Widget")));
+    assert!(data
+        .types
+        .iter()
+        .any(|t| t.path.to_templateless_string() == "lib::Box"));
+
+    let show = data
+        .methods
+        .iter()
+        .find(|f| f.path == CppPath::from_good_str("show"))
+        .unwrap();
+    assert_eq!(
+        show.arguments[0].argument_type,
+        CppType::new_reference(true, CppType::Class(CppPath::from_good_str("lib::Widget")))
+    );
+
+    let process = data
+        .methods
+        .iter()
+        .find(|f| f.path.to_templateless_string() == "process")
+        .unwrap();
+    let box_path = match &process.arguments[0].argument_type {
+        CppType::PointerLike { target, .. } => match &**target {
+            CppType::Class(path) => path.clone(),
+            other => panic!("unexpected type: {:?}", other),
+        },
+        other => panic!("unexpected type: {:?}", other),
+    };
+    assert_eq!(box_path.to_templateless_string(), "lib::Box");
+}
+
+#[test]
+fn parser_report() {
+    let data = run_parser(
+        "
+        class Outer {
+        private:
+            class Secret {};
+        public:
+            void use_secret(Secret* secret);
+            void ok();
+        };
+        ",
+    );
+    assert!(data
+        .methods
+        .iter()
+        .any(|f| f.path == CppPath::from_good_str("Outer::ok")));
+    assert_eq!(data.report.skipped_declarations.len(), 1);
+    let skipped = &data.report.skipped_declarations[0];
+    assert_eq!(skipped.kind, "function");
+    assert_eq!(skipped.name, "Outer::use_secret");
+    assert_eq!(skipped.category, CppParserSkipCategory::PrivateType);
+    assert!(skipped.message.contains("Outer::Secret"));
+    let location = skipped.location.as_ref().unwrap();
+    assert!(location.include_file_path.ends_with("myfakelib.h"));
+    assert_eq!(location.line, 6);
+}
+
+#[test]
+fn parser_report_categories() {
+    let data = run_parser_with_config(
+        "
+        class Hidden {};
+        class Outer {
+        private:
+            class Secret {};
+        public:
+            void use_callback(void (*callback)(Secret*));
+        };
+        template<char C> class Letter {};
+        void use_hidden(Hidden* hidden);
+        void use_member_pointer(int Outer::* member);
+        void use_letter(Letter<'a'> letter);
+        ",
+        |config| {
+            config.set_cpp_parser_path_hook(|path| Ok(path.to_templateless_string() != "Hidden"));
+        },
+    );
+    let category = |name: &str| {
+        data.report
+            .skipped_declarations
+            .iter()
+            .find(|declaration| declaration.name == name)
+            .unwrap_or_else(|| panic!("{} is not reported", name))
+            .category
+    };
+    assert_eq!(
+        category("use_hidden"),
+        CppParserSkipCategory::BlacklistedPath
+    );
+    assert_eq!(
+        category("use_member_pointer"),
+        CppParserSkipCategory::UnsupportedType
+    );
+    assert_eq!(
+        category("use_letter"),
+        CppParserSkipCategory::TemplateArgument
+    );
+    // the innermost category is used for nested errors
+    assert_eq!(
+        category("Outer::use_callback"),
+        CppParserSkipCategory::PrivateType
+    );
+    let callback = data
+        .report
+        .skipped_declarations
+        .iter()
+        .find(|declaration| declaration.name == "Outer::use_callback")
+        .unwrap();
+    assert!(callback.message.contains("function type"));
+    assert!(callback
+        .message
+        .contains("Type uses private class (Outer::Secret)"));
+}
+
+#[test]
+fn incremental_parsing() {
+    let dir = tempdir::TempDir::new("test_cpp_parser_incremental").unwrap();
+    let mut workspace = Workspace::new(dir.path().into()).unwrap();
+
+    let include_dir = dir.path().join("include");
+    create_dir(&include_dir).unwrap();
+    let write_header = |name: &str, code: &str| {
+        let mut file = create_file(include_dir.join(name)).unwrap();
+        writeln!(file, "{}", code).unwrap();
+    };
+    write_header("a.h", "namespace ns { void a1(); }");
+    write_header(
+        "b.h",
+        "#include \"b_config.h\"\nnamespace ns { void B_FUNCTION(); }",
+    );
+    write_header("b_config.h", "#define B_FUNCTION b1");
+
+    let mut paths = CppBuildPaths::new();
+    paths.add_include_path(&include_dir);
+    let mut config = Config::new(CrateProperties::new("A", "0.0.0"));
+    config.add_include_directive("a.h");
+    config.add_include_directive("b.h");
+    config.set_cpp_build_paths(paths);
+    config.add_target_include_path(&include_dir);
+
+    let function_ids = |workspace: &mut Workspace| {
+        processor::process(workspace, &config, &["cpp_parser".into()], None).unwrap();
+        let database = workspace
+            .get_database_client("A", &[], true, false)
+            .unwrap();
+        database
+            .cpp_items()
+            .filter_map(|item| {
+                let function = item.item.as_function_ref()?;
+                Some((function.path.to_cpp_pseudo_code(), item.id))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let first = function_ids(&mut workspace);
+    assert_eq!(first.len(), 2);
+    let b1_id = first
+        .iter()
+        .find(|(path, _)| path == "ns::b1")
+        .unwrap()
+        .1
+        .clone();
+
+    write_header("a.h", "namespace ns { void a2(); }");
+    let second = function_ids(&mut workspace);
+    let paths = second
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(paths.len(), 2);
+    assert!(paths.contains(&"ns::a2"));
+    assert!(paths.contains(&"ns::b1"));
+    // unchanged header is not parsed again
+    assert_eq!(
+        second.iter().find(|(path, _)| path == "ns::b1").unwrap().1,
+        b1_id
+    );
+
+    // headers are parsed again if an included file changes
+    write_header("b_config.h", "#define B_FUNCTION b2");
+    let third = function_ids(&mut workspace);
+    let paths = third
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(paths.len(), 2);
+    assert!(paths.contains(&"ns::a2"));
+    assert!(paths.contains(&"ns::b2"));
+
+    let namespaces = |workspace: &mut Workspace| {
+        let database = workspace
+            .get_database_client("A", &[], true, false)
+            .unwrap();
+        let mut namespaces = database
+            .cpp_items()
+            .filter_map(|item| match item.item {
+                CppItem::Namespace(namespace) => Some(namespace.path.to_cpp_pseudo_code()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        namespaces.sort();
+        namespaces
+    };
+
+    write_header("a.h", "namespace other { void a3(); }");
+    function_ids(&mut workspace);
+    assert_eq!(namespaces(&mut workspace), vec!["ns", "other"]);
+
+    // namespaces without declarations are deleted
+    write_header("a.h", "void a4();");
+    function_ids(&mut workspace);
+    assert_eq!(namespaces(&mut workspace), vec!["ns"]);
+}
+
+/// Replaces `cpp_checker`: all FFI items pass the checks in the current environment.
+fn fake_cpp_checks(data: &mut ProcessorData<'_>) -> Result<()> {
+    let env = LibraryTarget {
+        target: current_target(),
+        cpp_library_version: None,
+    };
+    data.db.add_environment(env.clone());
+    for ffi_item_id in data.db.ffi_item_ids().collect::<Vec<_>>() {
+        data.db.add_cpp_checks_item(
+            ffi_item_id,
+            CppChecksItem {
+                env: env.clone(),
+                is_success: true,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Returns text representations of all C++, FFI and Rust items of `database`,
+/// independent of item IDs.
+fn database_items(database: &DatabaseClient) -> Vec<String> {
+    let mut items = database
+        .cpp_items()
+        .map(|item| format!("{:?}", item.item))
+        .chain(database.ffi_items().map(|item| format!("{:?}", item.item)))
+        .chain(database.rust_items().map(|item| format!("{:?}", item.item)))
+        .collect::<Vec<_>>();
+    items.sort();
+    items
+}
+
+#[test]
+fn incremental_processing() {
+    let dir = tempdir::TempDir::new("test_incremental_processing").unwrap();
+    let include_dir = dir.path().join("include");
+    create_dir(&include_dir).unwrap();
+    let write_header = |name: &str, code: &str| {
+        let mut file = create_file(include_dir.join(name)).unwrap();
+        writeln!(file, "{}", code).unwrap();
+    };
+    write_header(
+        "types.h",
+        "struct Point { int x; };\nstruct Size { int width; };",
+    );
+    // uses types from `types.h` without including it
+    write_header(
+        "api.h",
+        "namespace ns {
+            void set(int value);
+            void set(Point* point);
+            int count();
+         }
+         class Widget {
+         public:
+             void move(Point* point);
+             void move(int x, int y);
+         };",
+    );
+    write_header("other.h", "namespace other { void ping(); }");
+
+    let mut paths = CppBuildPaths::new();
+    paths.add_include_path(&include_dir);
+    let mut config = Config::new(CrateProperties::new("A", "0.0.0"));
+    config.add_include_directive("types.h");
+    config.add_include_directive("api.h");
+    config.add_include_directive("other.h");
+    config.set_cpp_build_paths(paths);
+    config.add_target_include_path(&include_dir);
+    config
+        .processing_steps_mut()
+        .add_custom("fake_cpp_checks", fake_cpp_checks);
+
+    let process = |workspace: &mut Workspace| {
+        let steps = [
+            "[cpp_parser..cpp_subclasses]",
+            "fake_cpp_checks",
+            "rust_generator",
+        ];
+        let steps = steps.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        processor::process(workspace, &config, &steps, None).unwrap();
+        let database = workspace
+            .get_database_client("A", &[], true, false)
+            .unwrap();
+        database_items(&database)
+    };
+
+    let incremental_dir = dir.path().join("incremental");
+    create_dir(&incremental_dir).unwrap();
+    let mut incremental_workspace = Workspace::new(incremental_dir).unwrap();
+    let first = process(&mut incremental_workspace);
+
+    // `Point` is changed and `Size` is replaced with `Rect`, so FFI and Rust items
+    // of `ns::set(Point*)` and `Widget::move(Point*)` are generated again
+    write_header(
+        "types.h",
+        "struct Point { int x; int y; };\nstruct Rect { int width; int height; };",
+    );
+    let incremental = process(&mut incremental_workspace);
+    assert_ne!(first, incremental);
+
+    let full_dir = dir.path().join("full");
+    create_dir(&full_dir).unwrap();
+    let mut full_workspace = Workspace::new(full_dir).unwrap();
+    let full = process(&mut full_workspace);
+
+    for item in &incremental {
+        assert!(full.contains(item), "stale item: {}", item);
+    }
+    for item in &full {
+        assert!(incremental.contains(item), "missing item: {}", item);
+    }
+    assert_eq!(incremental, full);
+}
@@ -2,9 +2,12 @@ use crate::cpp_data::{CppPath, CppPathItem};
 use crate::cpp_ffi_data::CppToFfiTypeConversion;
 use crate::cpp_ffi_generator::ffi_type;
 use crate::cpp_type::{
-    CppBuiltInNumericType, CppFunctionPointerType, CppSpecificNumericType,
-    CppSpecificNumericTypeKind, CppTemplateParameter, CppType, CppTypeRole,
+    CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind, CppSpecificNumericType,
+    CppSpecificNumericTypeKind, CppTemplateArgumentValue, CppTemplateParameter, CppType,
+    CppTypeRole,
 };
+use std::collections::HashSet;
+use std::iter::once;
 
 fn assert_type_to_ffi_unchanged(t: &CppType) {
     for role in &[CppTypeRole::NotReturnType, CppTypeRole::ReturnType] {
@@ -380,3 +383,122 @@ fn instantiate1() {
         )
     );
 }
+
+#[test]
+fn instantiate_non_type_parameter() {
+    let type1 = CppType::Class(CppPath::from_item(CppPathItem {
+        name: "FixedBuffer".into(),
+        template_arguments: Some(vec![
+            CppType::TemplateParameter(CppTemplateParameter {
+                nested_level: 0,
+                index: 0,
+                name: "T".into(),
+            }),
+            CppType::TemplateParameter(CppTemplateParameter {
+                nested_level: 0,
+                index: 1,
+                name: "N".into(),
+            }),
+        ]),
+    }));
+    let r = type1
+        .instantiate(
+            0,
+            &[
+                CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+                CppType::TemplateArgumentValue(CppTemplateArgumentValue::Integer(4)),
+            ],
+        )
+        .unwrap();
+    assert!(!r.is_or_contains_template_parameter());
+    assert_eq!(r.to_cpp_code(None).unwrap(), "FixedBuffer< int, 4 >");
+    assert_eq!(r.ascii_caption(), "FixedBuffer_int_4");
+    assert!(ffi_type(
+        &CppType::TemplateArgumentValue(CppTemplateArgumentValue::Integer(4)),
+        CppTypeRole::NotReturnType
+    )
+    .is_err());
+}
+
+#[test]
+fn can_hold_integer() {
+    let int_type = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    assert!(int_type.can_hold_integer(-1));
+    assert!(int_type.can_hold_integer(2_147_483_647));
+    assert!(!int_type.can_hold_integer(2_147_483_648));
+
+    let uint_type = CppType::BuiltInNumeric(CppBuiltInNumericType::UInt);
+    assert!(uint_type.can_hold_integer(4_294_967_295));
+    assert!(!uint_type.can_hold_integer(-1));
+
+    let ulonglong_type = CppType::BuiltInNumeric(CppBuiltInNumericType::ULongLong);
+    assert!(ulonglong_type.can_hold_integer(i64::max_value()));
+    assert!(!ulonglong_type.can_hold_integer(-1));
+
+    let quint8 = CppType::SpecificNumeric(CppSpecificNumericType {
+        path: CppPath::from_good_str("quint8"),
+        bits: 8,
+        kind: CppSpecificNumericTypeKind::Integer { is_signed: false },
+    });
+    assert!(quint8.can_hold_integer(255));
+    assert!(!quint8.can_hold_integer(256));
+
+    let double_type = CppType::BuiltInNumeric(CppBuiltInNumericType::Double);
+    assert!(!double_type.can_hold_integer(0));
+}
+
+#[test]
+fn refers_to_any() {
+    let paths = once(CppPath::from_good_str("ns::Foo")).collect::<HashSet<_>>();
+    let foo = CppType::Class(CppPath::from_good_str("ns::Foo"));
+    assert!(foo.refers_to_any(&paths));
+    assert!(CppType::new_pointer(true, foo.clone()).refers_to_any(&paths));
+
+    let vector = CppType::Class(CppPath::from_item(CppPathItem {
+        name: "QVector".into(),
+        template_arguments: Some(vec![foo.clone()]),
+    }));
+    assert!(vector.refers_to_any(&paths));
+
+    let function_pointer = CppType::FunctionPointer(CppFunctionPointerType {
+        return_type: Box::new(CppType::Void),
+        arguments: vec![CppType::new_reference(false, vector)],
+        allows_variadic_arguments: false,
+    });
+    assert!(function_pointer.refers_to_any(&paths));
+
+    let bar = CppType::Enum {
+        path: CppPath::from_good_str("ns::Bar"),
+    };
+    assert!(!bar.refers_to_any(&paths));
+    assert!(!CppType::BuiltInNumeric(CppBuiltInNumericType::Int).refers_to_any(&paths));
+}
+
+#[test]
+fn rvalue_reference() {
+    let class_type = CppType::Class(CppPath::from_good_str("QString"));
+    let type1 = CppType::PointerLike {
+        kind: CppPointerLikeTypeKind::RValueReference,
+        is_const: false,
+        is_volatile: false,
+        target: Box::new(class_type.clone()),
+    };
+    assert_eq!(type1.to_cpp_code(None).unwrap(), "QString &&");
+
+    let ffi_arg = ffi_type(&type1, CppTypeRole::NotReturnType).unwrap();
+    assert_eq!(ffi_arg.original_type(), &type1);
+    assert_eq!(ffi_arg.ffi_type(), &CppType::new_pointer(false, class_type));
+    assert_eq!(
+        ffi_arg.conversion(),
+        &CppToFfiTypeConversion::RValueReferenceToPointer
+    );
+    assert!(ffi_type(&type1, CppTypeRole::ReturnType).is_err());
+
+    let int_type = CppType::PointerLike {
+        kind: CppPointerLikeTypeKind::RValueReference,
+        is_const: false,
+        is_volatile: false,
+        target: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+    };
+    assert!(ffi_type(&int_type, CppTypeRole::NotReturnType).is_err());
+}
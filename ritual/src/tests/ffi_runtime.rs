@@ -0,0 +1,181 @@
+use crate::config::{Config, CrateProperties};
+use crate::cpp_checks::CppChecksItem;
+use crate::processor::{self, ProcessorData};
+use crate::rust_code_generator;
+use crate::workspace::Workspace;
+use itertools::Itertools;
+use regex::Regex;
+use ritual_common::cpp_build_config::CppBuildPaths;
+use ritual_common::errors::Result;
+use ritual_common::file_utils::{create_dir, create_file, file_to_string};
+use ritual_common::target::{current_target, LibraryTarget};
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn environment(version: &str) -> LibraryTarget {
+    LibraryTarget {
+        target: current_target(),
+        cpp_library_version: Some(version.into()),
+    }
+}
+
+/// Replaces `cpp_checker`: all FFI items pass the checks in both library versions,
+/// except for `new_function` that is only available in the newer one.
+fn fake_cpp_checks(data: &mut ProcessorData<'_>) -> Result<()> {
+    let old_env = environment("1.0");
+    let new_env = environment("1.1");
+    data.db.add_environment(old_env.clone());
+    data.db.add_environment(new_env.clone());
+    for ffi_item_id in data.db.ffi_item_ids().collect_vec() {
+        let is_new = data
+            .db
+            .source_cpp_item(&ffi_item_id)?
+            .and_then(|item| item.item.path().cloned())
+            .map_or(false, |path| path.last().name == "new_function");
+        data.db.add_cpp_checks_item(
+            ffi_item_id.clone(),
+            CppChecksItem {
+                env: old_env.clone(),
+                is_success: !is_new,
+            },
+        );
+        data.db.add_cpp_checks_item(
+            ffi_item_id,
+            CppChecksItem {
+                env: new_env.clone(),
+                is_success: true,
+            },
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn runtime_symbols() {
+    let dir = tempdir::TempDir::new("test_ffi_runtime").unwrap();
+    let mut workspace = Workspace::new(dir.path().into()).unwrap();
+
+    let include_dir = dir.path().join("include");
+    create_dir(&include_dir).unwrap();
+    let include_name = "myfakelib.h";
+    let include_file_path = include_dir.join(&include_name);
+    {
+        let mut include_file = create_file(&include_file_path).unwrap();
+        writeln!(
+            include_file,
+            "{}",
+            "
+            int get_value();
+            void new_function(int x);
+            int format_text(char* buffer, const char* format, ...);
+
+            class Counter {
+            public:
+                Counter();
+                void add(int count);
+                int log(const char* format, ...) const;
+            };
+            "
+        )
+        .unwrap();
+    }
+
+    let mut paths = CppBuildPaths::new();
+    paths.add_include_path(include_dir);
+
+    let mut config = Config::new(CrateProperties::new("A", "0.0.0"));
+    config.add_include_directive(include_name);
+    config.set_cpp_build_paths(paths);
+    config.add_target_include_path(include_file_path);
+    config
+        .processing_steps_mut()
+        .add_custom("fake_cpp_checks", fake_cpp_checks);
+
+    let steps = [
+        "[cpp_parser..cpp_subclasses]",
+        "fake_cpp_checks",
+        "rust_generator",
+    ];
+    let steps = steps.iter().map(|s| s.to_string()).collect_vec();
+    processor::process(&mut workspace, &config, &steps, None).unwrap();
+
+    let database = workspace
+        .get_database_client("A", &[], true, false)
+        .unwrap();
+    let output_path = dir.path().join("out");
+    create_dir(&output_path).unwrap();
+    rust_code_generator::generate(&database, &output_path, None::<PathBuf>).unwrap();
+
+    let ffi_functions = database
+        .rust_items()
+        .filter_map(|item| item.filter_map(|item| item.as_function_ref()))
+        .filter(|function| function.item.kind.is_ffi_function())
+        .collect_vec();
+    let ffi_names = ffi_functions
+        .iter()
+        .map(|function| function.item.path.last().to_string())
+        .collect::<BTreeSet<_>>();
+    let variadic_names = ffi_functions
+        .iter()
+        .filter(|function| {
+            database
+                .source_cpp_item(&function.id)
+                .unwrap()
+                .and_then(|item| item.item.as_function_ref())
+                .map_or(false, |function| function.allows_variadic_arguments)
+        })
+        .map(|function| function.item.path.last().to_string())
+        .collect_vec();
+    assert_eq!(variadic_names.len(), 2);
+
+    let code = file_to_string(output_path.join("ffi_runtime.in.rs")).unwrap();
+    let symbol_regex = Regex::new(r"static (\w+)_SYMBOL: ::cpp_core::RuntimeSymbol").unwrap();
+    let symbols = symbol_regex
+        .captures_iter(&code)
+        .map(|captures| captures[1].to_string())
+        .collect::<BTreeSet<_>>();
+    let loader_regex = Regex::new(r"(\w+)_SYMBOL\.get\(&WRAPPER_LIBRARY\)\?;").unwrap();
+    let loaded_symbols = loader_regex
+        .captures_iter(&code)
+        .map(|captures| captures[1].to_string())
+        .collect_vec();
+
+    // every FFI function has a symbol, and the loader resolves each of them once
+    assert_eq!(symbols, ffi_names);
+    assert_eq!(loaded_symbols.len(), symbols.len());
+    assert_eq!(
+        loaded_symbols.iter().cloned().collect::<BTreeSet<_>>(),
+        symbols
+    );
+    for name in &variadic_names {
+        assert!(code.contains(&format!(
+            "::std::mem::transmute({}_SYMBOL.resolve(&WRAPPER_LIBRARY));",
+            name
+        )));
+    }
+    assert!(code.contains("WRAPPER_LIBRARY.load()?;"));
+    assert!(code.contains("WRAPPER_LIBRARY.load_from(path)?;\nload_wrapper_library()"));
+
+    // a symbol missing in some library versions is only resolved where it's available
+    let new_function_name = ffi_functions
+        .iter()
+        .find(|function| {
+            database
+                .source_cpp_item(&function.id)
+                .unwrap()
+                .and_then(|item| item.item.path().cloned())
+                .map_or(false, |path| path.last().name == "new_function")
+        })
+        .unwrap()
+        .item
+        .path
+        .last()
+        .to_string();
+    let cfg = "#[cfg(any(cpp_lib_version=\"1.1\", feature = \"ritual_rustdoc\"))]\n";
+    assert!(code.contains(&format!(
+        "{}{}_SYMBOL.get(&WRAPPER_LIBRARY)?;",
+        cfg, new_function_name
+    )));
+    assert_eq!(code.matches(cfg).count(), 3);
+}
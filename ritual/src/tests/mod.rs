@@ -5,3 +5,4 @@ mod cpp_method;
 mod cpp_operator;
 mod cpp_parser;
 mod cpp_type;
+mod ffi_runtime;
@@ -0,0 +1,22 @@
+/* Generated by ritual.
+ * See the template at "ritual/templates/c_lib/ffi.h".
+ *
+ * C declarations of the functions exported by the wrapper library of the `{crate_name}` crate.
+ * C++ classes are represented by opaque structs and can only be used through pointers.
+ * Enums are represented by their underlying integer types.
+ *
+ * Declarations that are only available in some versions of the C++ library
+ * are guarded by `RITUAL_CPP_LIB_VERSION`. Define it to the version number used
+ * when building the wrapper library to make them available.
+ */
+
+#ifndef {guard}
+#define {guard}
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {{
+#endif
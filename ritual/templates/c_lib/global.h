@@ -14,9 +14,23 @@
 // placement new statements require this
 #include <new>
 
+// for passing rvalue references
+#include <utility>
+
 // for exit()
 #include <cstdlib>
 
+// for callbacks passed as std::function
+#include <functional>
+#include <memory>
+
+// for copying messages of caught exceptions
+#include <cstring>
+#include <exception>
+
+// for calling C-variadic functions
+#include <type_traits>
+
 #ifdef _WIN32
     #define RITUAL_EXPORT __declspec(dllexport)
 #else
@@ -67,6 +81,75 @@ namespace ritual {
         void (*m_deleter)(void*);
         T m_callback;
     };
+
+    // Returns a pointer to the user data of a callback wrapped
+    // in `std::function`. `deleter` is called when all copies
+    // of the function object are destroyed.
+    inline std::shared_ptr<void> callback_data(void* data, void (*deleter)(void*)) {
+        return std::shared_ptr<void>(data, [deleter](void* data) {
+            if (deleter) {
+                deleter(data);
+            }
+        });
+    }
+
+    // Returns a copy of the message of a caught exception.
+    // The buffer is allocated with `malloc` and must be freed by the caller.
+    inline char* exception_message(const char* message) {
+        size_t size = std::strlen(message) + 1;
+        char* buffer = static_cast<char*>(std::malloc(size));
+        if (!buffer) {
+            // null value would mean that no exception was thrown
+            std::abort();
+        }
+        std::memcpy(buffer, message, size);
+        return buffer;
+    }
+
+    // A value passed as a variadic argument.
+    // Must match `RawVarArg` in `cpp_core`.
+    struct VarArg {
+        int kind; // 0 - int, 1 - double, 2 - pointer
+        int int_value;
+        double double_value;
+        const void* pointer_value;
+    };
+
+    // Must match `cpp_core::MAX_VAR_ARGS`.
+    const int max_var_args = 4;
+
+    template<class R, class F, class... Ts>
+    R call_var_args(const F& f, const VarArg* args, int count, Ts... values);
+
+    template<class R, class F, class... Ts>
+    R call_var_args_next(const F&, const VarArg*, int, std::false_type, Ts...) {
+        // checked by `cpp_core::VarArgs`
+        std::abort();
+    }
+
+    template<class R, class F, class... Ts>
+    R call_var_args_next(const F& f, const VarArg* args, int count, std::true_type, Ts... values) {
+        switch (args->kind) {
+        case 0:
+            return call_var_args<R>(f, args + 1, count - 1, values..., args->int_value);
+        case 1:
+            return call_var_args<R>(f, args + 1, count - 1, values..., args->double_value);
+        default:
+            return call_var_args<R>(f, args + 1, count - 1, values..., args->pointer_value);
+        }
+    }
+
+    // Calls `f` with `values` followed by `count` values from `args`
+    // converted to their C types. A call is instantiated for each combination
+    // of argument types, so the number of arguments is limited by `max_var_args`.
+    template<class R, class F, class... Ts>
+    R call_var_args(const F& f, const VarArg* args, int count, Ts... values) {
+        if (count == 0) {
+            return f(values...);
+        }
+        return call_var_args_next<R>(f, args, count,
+            std::integral_constant<bool, (sizeof...(Ts) < max_var_args)>(), values...);
+    }
 }
 
 #endif // RITUAL_GLOBAL_H
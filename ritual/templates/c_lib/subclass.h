@@ -0,0 +1,27 @@
+// Class generated by ritual.
+// See the template at "ritual/templates/c_lib/subclass.h".
+class {class_name} : public {base_class} {{
+public:
+{constructors}
+    ~{class_name}() {{
+        if (m_deleter) {{
+            m_deleter(m_data);
+        }}
+    }}
+
+    // Exceptions can't be propagated through Rust code.
+    // The data is never replaced because a callback using it may be running.
+    bool set_data(void* data, void (*deleter)(void*)) noexcept {{
+        if (m_deleter) {{
+            return false;
+        }}
+        m_data = data;
+        m_deleter = deleter;
+        return true;
+    }}
+{methods}
+private:
+    void* m_data = nullptr;
+    void (*m_deleter)(void*) = nullptr;
+{fields}
+}};
@@ -13,7 +13,10 @@
 //!   (`RITUAL_LIBRARY_PATH`, `RITUAL_FRAMEWORK_PATH`, `RITUAL_INCLUDE_PATH`);
 //! - Select active build configuration based on current target;
 //! - Build the C++ wrapper library;
-//! - Generate `ffi.rs` file with actual linking attributes;
+//! - Generate `ffi.rs` file with actual linking attributes
+//!   (or with runtime loading of the wrapper library if `RITUAL_LIBRARY_LOADING=runtime`;
+//!   the loaded library is looked up in `RITUAL_RUNTIME_LIBRARY_PATH` directories,
+//!   the build directory and the default system paths);
 //! - Determine sizes of C++ types with stack allocation place;
 //! - Print `cargo` attributes required for linking the crate.
 //! It appears a common task to perform other custom operations in the build script,
@@ -26,7 +29,9 @@
 
 use log::info;
 pub use ritual_common as common;
-use ritual_common::cpp_build_config::{CppBuildConfig, CppBuildPaths, CppLibraryType};
+use ritual_common::cpp_build_config::{
+    CppBuildConfig, CppBuildPaths, CppLibraryLoading, CppLibraryType,
+};
 use ritual_common::cpp_lib_builder::{BuildType, CMakeConfigData, CppLibBuilder};
 use ritual_common::errors::{bail, err_msg, FancyUnwrap, Result, ResultExt};
 use ritual_common::file_utils::{create_file, file_to_string, load_json, path_to_str};
@@ -143,9 +148,20 @@ impl Config {
         let profile = env::var("PROFILE").with_context(|_| "PROFILE env var is missing")?;
         info!("Building C++ wrapper library");
 
-        let library_type = cpp_build_config_data
-            .library_type()
-            .ok_or_else(|| err_msg("library type (shared or static) is not set"))?;
+        let library_loading = match env::var(env_var_names::LIBRARY_LOADING) {
+            Ok(value) => CppLibraryLoading::from_env_value(&value)?,
+            Err(_) => cpp_build_config_data
+                .library_loading()
+                .unwrap_or(CppLibraryLoading::Linked),
+        };
+        let library_type = if library_loading == CppLibraryLoading::Runtime {
+            // only a shared library can be loaded at runtime
+            CppLibraryType::Shared
+        } else {
+            cpp_build_config_data
+                .library_type()
+                .ok_or_else(|| err_msg("library type (shared or static) is not set"))?
+        };
 
         let cmake_config = CMakeConfigData {
             cpp_build_config_data: &cpp_build_config_data,
@@ -174,39 +190,68 @@ impl Config {
         {
             info!("Generating ffi.rs file");
             let mut ffi_file = create_file(out_dir.join("ffi.rs"))?;
-            if cpp_build_config_data.library_type() == Some(CppLibraryType::Shared) {
+            let lib_name = &self.build_script_data.cpp_wrapper_lib_name;
+            if library_loading == CppLibraryLoading::Runtime {
+                // the library is also looked up in the directory it's installed to,
+                // so it can be used without installing it elsewhere
                 writeln!(
                     ffi_file,
-                    "#[link(name = \"{}\")]",
-                    &self.build_script_data.cpp_wrapper_lib_name
+                    "static WRAPPER_LIBRARY: ::cpp_core::RuntimeLibrary = \
+                     ::cpp_core::RuntimeLibrary::with_build_dir({:?}, {:?});\n",
+                    lib_name,
+                    path_to_str(&c_lib_install_dir)?
+                )?;
+                write!(
+                    ffi_file,
+                    "{}",
+                    file_to_string(manifest_dir.join("src").join("ffi_runtime.in.rs"))?
                 )?;
             } else {
                 writeln!(
                     ffi_file,
-                    "#[link(name = \"{}\", kind = \"static\")]",
-                    &self.build_script_data.cpp_wrapper_lib_name
+                    "pub fn load_wrapper_library() -> \
+                     ::std::result::Result<(), ::cpp_core::RuntimeLibraryError> {{\n\
+                     Ok(())\n\
+                     }}\n\n\
+                     pub fn load_wrapper_library_from(_path: &::std::path::Path) -> \
+                     ::std::result::Result<(), ::cpp_core::RuntimeLibraryError> {{\n\
+                     Ok(())\n\
+                     }}\n"
+                )?;
+                if library_type == CppLibraryType::Shared {
+                    writeln!(ffi_file, "#[link(name = \"{}\")]", lib_name)?;
+                } else {
+                    writeln!(
+                        ffi_file,
+                        "#[link(name = \"{}\", kind = \"static\")]",
+                        lib_name
+                    )?;
+                }
+                write!(
+                    ffi_file,
+                    "{}",
+                    file_to_string(manifest_dir.join("src").join("ffi.in.rs"))?
                 )?;
             }
-            write!(
-                ffi_file,
-                "{}",
-                file_to_string(manifest_dir.join("src").join("ffi.in.rs"))?
-            )?;
         }
 
-        for name in cpp_build_config_data.linked_libs() {
-            println!("cargo:rustc-link-lib={}", name);
-        }
+        // With runtime loading, the C++ libraries are only
+        // linked to the wrapper library.
+        if library_loading == CppLibraryLoading::Linked {
+            for name in cpp_build_config_data.linked_libs() {
+                println!("cargo:rustc-link-lib={}", name);
+            }
 
-        // TODO: make it configurable
-        if crate::common::target::current_os() == OS::MacOS {
-            println!("cargo:rustc-link-lib=c++");
-        } else if crate::common::target::current_env() != crate::common::target::Env::Msvc {
-            println!("cargo:rustc-link-lib=stdc++");
-        }
+            // TODO: make it configurable
+            if crate::common::target::current_os() == OS::MacOS {
+                println!("cargo:rustc-link-lib=c++");
+            } else if crate::common::target::current_env() != crate::common::target::Env::Msvc {
+                println!("cargo:rustc-link-lib=stdc++");
+            }
 
-        for name in cpp_build_config_data.linked_frameworks() {
-            println!("cargo:rustc-link-lib=framework={}", name);
+            for name in cpp_build_config_data.linked_frameworks() {
+                println!("cargo:rustc-link-lib=framework={}", name);
+            }
         }
         for path in self.cpp_build_paths.lib_paths() {
             println!("cargo:rustc-link-search=native={}", path_to_str(path)?);
@@ -226,6 +271,7 @@ impl Config {
             env_var_names::LIBRARY_PATH,
             env_var_names::FRAMEWORK_PATH,
             env_var_names::INCLUDE_PATH,
+            env_var_names::LIBRARY_LOADING,
         ] {
             println!("cargo:rerun-if-env-changed={}", var);
         }
@@ -42,6 +42,32 @@ pub enum CppLibraryType {
     Static,
 }
 
+/// The way the C++ wrapper library is made available to the Rust crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CppLibraryLoading {
+    /// The library is linked to the crate at build time.
+    Linked,
+    /// The library is loaded with `dlopen` (`LoadLibrary` on Windows) when it's used
+    /// for the first time. The crate can be used even if the library or
+    /// the C++ libraries it depends on are not available on the host, as long as
+    /// its functions are not called. The wrapper library is always shared in this mode.
+    Runtime,
+}
+
+impl CppLibraryLoading {
+    /// Parses a value of `RITUAL_LIBRARY_LOADING` environment variable.
+    pub fn from_env_value(value: &str) -> Result<Self> {
+        match value {
+            "linked" => Ok(CppLibraryLoading::Linked),
+            "runtime" => Ok(CppLibraryLoading::Runtime),
+            _ => bail!(
+                "invalid library loading mode: {:?} (expected \"linked\" or \"runtime\")",
+                value
+            ),
+        }
+    }
+}
+
 /// Platform-specific information
 /// required to build the C++ wrapper library.
 /// This type contains one configuration item of `CppBuildConfig`.
@@ -51,6 +77,7 @@ pub struct CppBuildConfigData {
     linked_frameworks: Vec<String>,
     compiler_flags: Vec<String>,
     library_type: Option<CppLibraryType>,
+    library_loading: Option<CppLibraryLoading>,
     cmake_vars: Vec<CMakeVar>,
 }
 
@@ -96,6 +123,12 @@ impl CppBuildConfigData {
         self.library_type = Some(t);
     }
 
+    /// Sets the way the C++ wrapper library is loaded.
+    /// The library is linked to the crate by default.
+    pub fn set_library_loading(&mut self, loading: CppLibraryLoading) {
+        self.library_loading = Some(loading);
+    }
+
     /// Returns names of linked libraries.
     pub fn linked_libs(&self) -> &[String] {
         &self.linked_libs
@@ -116,6 +149,11 @@ impl CppBuildConfigData {
         self.library_type
     }
 
+    /// Returns the way the C++ wrapper library is loaded.
+    pub fn library_loading(&self) -> Option<CppLibraryLoading> {
+        self.library_loading
+    }
+
     fn add_from(&mut self, other: &CppBuildConfigData) -> Result<()> {
         self.linked_libs.append(&mut other.linked_libs.clone());
         self.linked_frameworks
@@ -128,6 +166,13 @@ impl CppBuildConfigData {
         } else {
             self.library_type = other.library_type;
         }
+        if self.library_loading.is_some() {
+            if other.library_loading.is_some() && other.library_loading != self.library_loading {
+                bail!("conflicting library loading modes specified");
+            }
+        } else {
+            self.library_loading = other.library_loading;
+        }
         self.cmake_vars.extend_from_slice(&other.cmake_vars);
         Ok(())
     }
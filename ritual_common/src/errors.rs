@@ -4,7 +4,7 @@ use itertools::Itertools;
 use log::{log, log_enabled, Level};
 use std::env;
 
-pub use failure::{bail, ensure, err_msg, format_err, Error, ResultExt};
+pub use failure::{bail, ensure, err_msg, format_err, Context, Error, Fail, ResultExt};
 
 pub type Result<T> = std::result::Result<T, failure::Error>;
 